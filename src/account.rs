@@ -0,0 +1,98 @@
+//! Accounts, set in the `[accounts]` section of the config with their opening balance. There is
+//! no field for the account of an entry, so an entry belongs to the account named by one of its
+//! tags, like `savings`. Entries without one belong to no account, but still count towards the
+//! net worth.
+
+use chrono::{Months, NaiveDate};
+
+use crate::config::Config;
+use crate::date::{parse_iso, DateRange};
+use crate::expense::Expense;
+
+/// What an account holds at some point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Balance {
+    /// The account's name as set in the config, or None for the entries of no account
+    pub name: Option<String>,
+    pub opening: f64,
+    pub balance: f64,
+}
+
+/// The account of an expense, the first of its tags naming one.
+pub fn account_of<'a>(config: &'a Config, expense: &Expense) -> Option<&'a String> {
+    expense
+        .tags
+        .iter()
+        .find_map(|tag| config.account(tag).map(|(name, _)| name))
+}
+
+/// What each account of the config holds at the end of `date`, followed by the entries of no
+/// account if there are any up to then.
+pub fn balances(config: &Config, expenses: &[&Expense], date: NaiveDate) -> Vec<Balance> {
+    let mut balances: Vec<Balance> = config
+        .accounts
+        .iter()
+        .map(|(name, opening)| Balance {
+            name: Some(name.clone()),
+            opening: *opening,
+            balance: *opening,
+        })
+        .collect();
+    let mut other: Option<Balance> = None;
+    for expense in expenses {
+        if parse_iso(&expense.date).is_none_or(|day| day > date) {
+            continue;
+        }
+        let balance = match account_of(config, expense) {
+            Some(name) => balances
+                .iter_mut()
+                .find(|balance| balance.name.as_ref() == Some(name)),
+            None => Some(other.get_or_insert(Balance {
+                name: None,
+                opening: 0.0,
+                balance: 0.0,
+            })),
+        };
+        if let Some(balance) = balance {
+            balance.balance += expense.amount;
+        }
+    }
+    balances.extend(other);
+    balances
+}
+
+/// The net worth, what all the accounts hold together, at the end of each of the `months`
+/// months up to the one of `today`, the oldest first. This month's is today's.
+pub fn net_worth(
+    config: &Config,
+    expenses: &[&Expense],
+    months: u32,
+    today: NaiveDate,
+) -> Vec<(DateRange, f64)> {
+    let opening: f64 = config.accounts.values().sum();
+    let mut months: Vec<DateRange> = (0..months)
+        .rev()
+        .filter_map(|back| today.checked_sub_months(Months::new(back)))
+        .filter_map(DateRange::month_of)
+        .collect();
+    if let Some(month) = months.last_mut() {
+        month.end = today;
+    }
+    let mut amounts: Vec<(NaiveDate, f64)> = expenses
+        .iter()
+        .filter_map(|expense| Some((parse_iso(&expense.date)?, expense.amount)))
+        .collect();
+    amounts.sort_by_key(|(date, _)| *date);
+
+    let (mut total, mut next) = (opening, 0);
+    months
+        .into_iter()
+        .map(|month| {
+            while let Some((_, amount)) = amounts.get(next).filter(|(date, _)| *date <= month.end) {
+                total += amount;
+                next += 1;
+            }
+            (month, total)
+        })
+        .collect()
+}
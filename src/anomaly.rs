@@ -0,0 +1,67 @@
+//! Entries whose amount is far from what their type usually comes to, more than three standard
+//! deviations from the average of the other entries of the type, which catches mistakes like a
+//! missing decimal point.
+
+use std::collections::HashMap;
+
+use crate::expense::Expense;
+
+/// How many standard deviations from the usual amount an unusual one is.
+pub const DEVIATIONS: f64 = 3.0;
+/// How many other entries a type needs for an amount to be told unusual.
+const MIN_OTHERS: usize = 5;
+/// The least standard deviation as a share of the usual amount, so that a type whose amounts
+/// hardly vary, like the rent, doesn't make every change of them unusual.
+const MIN_SPREAD: f64 = 0.1;
+
+/// An entry whose amount is unusual for its type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unusual {
+    /// Its position in the expenses
+    pub index: usize,
+    /// The average amount of the other entries of the type
+    pub usual: f64,
+    /// How many standard deviations it is from it
+    pub deviations: f64,
+}
+
+/// The entries of the `expenses` with an unusual amount for their type, in their order. Types
+/// are matched whatever their case.
+pub fn unusual(expenses: &[&Expense]) -> Vec<Unusual> {
+    // The count, sum and sum of squares of the amounts of each type
+    let mut types: HashMap<String, (usize, f64, f64)> = HashMap::new();
+    for expense in expenses {
+        let (count, sum, squares) = types
+            .entry(expense.expense_type.trim().to_lowercase())
+            .or_default();
+        *count += 1;
+        *sum += expense.amount;
+        *squares += expense.amount * expense.amount;
+    }
+
+    let mut unusual = Vec::new();
+    for (index, expense) in expenses.iter().enumerate() {
+        let (count, sum, squares) = types[&expense.expense_type.trim().to_lowercase()];
+        // Leaving the entry out, so it doesn't hide itself by widening the spread
+        let others = count - 1;
+        if others < MIN_OTHERS {
+            continue;
+        }
+        let amount = expense.amount;
+        let usual = (sum - amount) / others as f64;
+        let variance = ((squares - amount * amount) / others as f64 - usual * usual).max(0.0);
+        let spread = variance.sqrt().max(usual.abs() * MIN_SPREAD);
+        if spread == 0.0 {
+            continue;
+        }
+        let deviations = (amount - usual).abs() / spread;
+        if deviations > DEVIATIONS {
+            unusual.push(Unusual {
+                index,
+                usual,
+                deviations,
+            });
+        }
+    }
+    unusual
+}
@@ -0,0 +1,181 @@
+//! The monthly budgets set for types in the `[budgets]` section of the config: what was spent on
+//! each of them in a month against its budget, for the reports, the Budgets tab and the warning
+//! given when an expense added takes the spending close to or over a budget. Types are matched
+//! ignoring case.
+//!
+//! A budget set to roll over in the `[rollover]` section carries what was left of it at the end
+//! of each month since the one it rolls over from into the next, or takes off what it was
+//! overspent by. It is worked out from the expenses each time, so it follows them when past
+//! ones are changed.
+
+use chrono::{Days, NaiveDate};
+
+use crate::config::Config;
+use crate::date::{parse_iso, DateRange};
+use crate::expense::Expense;
+
+/// The part of a budget from which it is close to being spent.
+const CLOSE: f64 = 0.75;
+
+/// How much of a budget is spent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Within,
+    /// Three quarters of it or more
+    Close,
+    Over,
+}
+
+/// What was spent on a type in a month against its budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// The type as written in the config
+    pub expense_type: String,
+    pub budget: f64,
+    /// Spending less refunds, so it can be below zero
+    pub spent: f64,
+    /// What was left of the budget in the months before for one that rolls over, below zero
+    /// when they were overspent
+    pub carried: f64,
+    pub month: DateRange,
+}
+
+impl Progress {
+    /// What can be spent in the month, the budget and what was carried into it.
+    pub fn available(&self) -> f64 {
+        self.budget + self.carried
+    }
+
+    /// The part of what is available that is spent, above 1 once it is overspent. With nothing
+    /// available it is 1 and the part of the budget it is overspent by.
+    pub fn ratio(&self) -> f64 {
+        match self.available() {
+            available if available > 0.0 => (self.spent / available).max(0.0),
+            available => 1.0 + (self.spent - available).max(0.0) / self.budget,
+        }
+    }
+
+    /// What is left of what is available, below zero once it is overspent.
+    pub fn left(&self) -> f64 {
+        self.available() - self.spent
+    }
+
+    /// The budget with what was carried into it, e.g. `$300.00 and $40.00 carried over`.
+    pub fn written_budget(&self, config: &Config) -> String {
+        match self.carried {
+            carried if carried > 0.0 => format!(
+                "{} and {} carried over",
+                config.money(self.budget),
+                config.money(carried)
+            ),
+            carried if carried < 0.0 => format!(
+                "{} less {} overspent before",
+                config.money(self.budget),
+                config.money(-carried)
+            ),
+            _ => config.money(self.budget),
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        match self.ratio() {
+            ratio if ratio > 1.0 => Level::Over,
+            ratio if ratio >= CLOSE => Level::Close,
+            _ => Level::Within,
+        }
+    }
+
+    /// What to warn about once the budget is close to spent or overspent, e.g. `Food: 80% of
+    /// the budget of $300.00 is spent in June 2024, $60.00 is left`.
+    pub fn warning(&self, config: &Config) -> Option<String> {
+        match self.level() {
+            Level::Within => None,
+            Level::Close => Some(format!(
+                "{}: {:.0}% of the budget of {} is spent in {}, {} is left",
+                self.expense_type,
+                self.ratio() * 100.0,
+                self.written_budget(config),
+                self.month.month_name(),
+                config.money(self.left())
+            )),
+            Level::Over => Some(format!(
+                "{}: over the budget of {} by {} in {}",
+                self.expense_type,
+                self.written_budget(config),
+                config.money(-self.left()),
+                self.month.month_name()
+            )),
+        }
+    }
+}
+
+/// Whether a budget is set for `expense_type`.
+pub fn is_budgeted(config: &Config, expense_type: &str) -> bool {
+    config.budget(expense_type).is_some()
+}
+
+/// What was spent on `expense_type` in `month` less refunds.
+fn spent_in(expenses: &[Expense], expense_type: &str, month: DateRange) -> f64 {
+    -expenses
+        .iter()
+        .filter(|expense| {
+            parse_iso(&expense.date).is_some_and(|date| month.contains(date))
+                && expense.expense_type.eq_ignore_ascii_case(expense_type)
+        })
+        .map(|expense| expense.amount)
+        .sum::<f64>()
+}
+
+/// What was left of the `budget` of `expense_type` in all the months from the one of `start`
+/// up to `month`, less what they were overspent by.
+fn carried(
+    expenses: &[Expense],
+    (expense_type, budget): (&str, f64),
+    start: NaiveDate,
+    month: DateRange,
+) -> f64 {
+    let mut carried = 0.0;
+    let mut before = DateRange::month_of(start);
+    while let Some(range) = before.filter(|range| range.start < month.start) {
+        carried += budget - spent_in(expenses, expense_type, range);
+        before = range
+            .end
+            .checked_add_days(Days::new(1))
+            .and_then(DateRange::month_of);
+    }
+    carried
+}
+
+/// The progress of every budget in the config in the month of `date`, over all the `expenses`.
+pub fn progress(config: &Config, expenses: &[Expense], date: NaiveDate) -> Vec<Progress> {
+    let month = DateRange::month_of(date).unwrap_or(DateRange::day(date));
+    config
+        .budgets
+        .iter()
+        .map(|(expense_type, budget)| Progress {
+            expense_type: expense_type.clone(),
+            budget: *budget,
+            spent: spent_in(expenses, expense_type, month),
+            carried: config.rollover(expense_type).map_or(0.0, |start| {
+                carried(expenses, (expense_type, *budget), start, month)
+            }),
+            month,
+        })
+        .collect()
+}
+
+/// The progress of the budget of the `expense`'s type in its month, the `expenses` holding it,
+/// when it is a spending of a type with a budget.
+pub fn of_expense(config: &Config, expenses: &[Expense], expense: &Expense) -> Option<Progress> {
+    if expense.amount >= 0.0 {
+        return None;
+    }
+    let date = parse_iso(&expense.date)?;
+    progress(config, expenses, date)
+        .into_iter()
+        .find(|progress| {
+            progress
+                .expense_type
+                .eq_ignore_ascii_case(expense.expense_type.trim())
+        })
+}
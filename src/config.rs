@@ -0,0 +1,569 @@
+//! User configuration, read from `~/.config/budget-tracker/config.toml`.
+//!
+//! Every setting is optional; a missing file or key falls back to the defaults.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Local, NaiveDate, Weekday};
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf, str::FromStr};
+
+use crate::date::{
+    parse_iso, parse_range, set_month_start, set_week_start, weekday_name, DateOrder, DateRange,
+};
+use crate::expense::{capitalize, Expense};
+
+/// The colors of the TUI, for dark or light terminals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "dark" => Ok(Theme::Dark),
+            "light" => Ok(Theme::Light),
+            _ => Err(format!("Unknown theme '{}', expected dark or light", input)),
+        }
+    }
+}
+
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Theme::Dark => write!(f, "dark"),
+            Theme::Light => write!(f, "light"),
+        }
+    }
+}
+
+/// What amounts are shown as while they are hidden.
+pub const MASK: &str = "•••";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    /// How dates without a year, like `12/6`, are read when adding an expense.
+    pub date_order: DateOrder,
+
+    /// The day months start on for the budgets, reports and everything about this month, e.g.
+    /// 25 for months running from payday to payday. The 1st when unset.
+    pub month_start: Option<u32>,
+
+    /// The day weeks start on for the reports and the heatmap, e.g. `sunday`. Monday when unset.
+    pub week_start: Option<String>,
+
+    /// How dates are shown in the TUI, as a strftime format like `%d %b %Y`. They are always
+    /// stored as YYYY-MM-DD.
+    pub date_format: Option<String>,
+
+    /// The symbol put before amounts in totals, like `$` or `€`.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub currency: String,
+
+    /// The type filled in for new expenses.
+    pub default_type: Option<String>,
+
+    /// Types offered when picking one, besides the ones already in use.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+
+    pub theme: Theme,
+
+    /// Whether the TUI starts with the amounts hidden.
+    pub privacy: bool,
+
+    /// Whether the amounts are hidden right now, toggled in the TUI without changing the default.
+    #[serde(skip)]
+    pub hide_amounts: bool,
+
+    /// The directory `expenses.csv` is kept in, `~/.local/share/budget-tracker` by default.
+    pub data_dir: Option<PathBuf>,
+
+    /// Saved queries by name, recalled with `--filter <name>`.
+    pub filters: BTreeMap<String, String>,
+
+    /// What can be spent on a type each month, e.g. `Food = 300`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub budgets: BTreeMap<String, f64>,
+
+    /// The budgets whose unspent part carries into the next month, and whose overspending comes
+    /// off it, each with the first day of the month it carries over from, e.g.
+    /// `Food = "2024-06-01"`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rollover: BTreeMap<String, String>,
+
+    /// The opening balance of each account, e.g. `Savings = 5000`. Entries tagged with an
+    /// account's name belong to it.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub accounts: BTreeMap<String, f64>,
+
+    /// Types and tags of entries left out of the savings rate, e.g. `["Transfer"]` for money
+    /// moved between accounts or `["reimbursed"]` for money paid back.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub savings_exclude: Vec<String>,
+
+    /// The color of a type in the TUI, e.g. `Food = "green"` or `Rent = "#8e44ad"`, for the
+    /// types that shouldn't take the one picked from their name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub colors: BTreeMap<String, String>,
+
+    /// The columns of the table in order, e.g. `["date", "description", "amount", "tags"]`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+
+    /// Whether long descriptions wrap onto a second line of the table rather than being cut.
+    pub wrap: bool,
+
+    /// Keys of the TUI by action, replacing the default ones, e.g. `delete = "x Delete"`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Reads the config file, returning the defaults if it doesn't exist.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let file_path = Self::get_config_file_path()?;
+        if !file_path.exists() {
+            trace!("No config file found, using the defaults");
+            return Ok(Self::default());
+        }
+
+        trace!("Reading the config from {} ...", file_path.display());
+        let contents = fs::read_to_string(&file_path)?;
+        let mut config: Self = toml::from_str(&contents)
+            .map_err(|err| format!("Invalid config {}: {}", file_path.display(), err))?;
+        // A budget is divided by, so it must be above zero as when set with --set
+        if let Some((expense_type, budget)) = config
+            .budgets
+            .iter()
+            .find(|(_, budget)| budget.is_nan() || **budget <= 0.0)
+        {
+            return Err(format!(
+                "Invalid config {}: the budget of {} is {}, budgets are amounts above zero",
+                file_path.display(),
+                expense_type,
+                budget
+            )
+            .into());
+        }
+        config.hide_amounts = config.privacy;
+        Ok(config)
+    }
+
+    /// Writes the config file, creating its directory if needed.
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = Self::get_config_file_path()?;
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(file_path, toml::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// The columns the table can show.
+    pub const COLUMNS: &'static [&'static str] = &["date", "description", "type", "amount", "tags"];
+
+    /// The settings that can be changed with [Config::set], besides the keys, with what they do.
+    pub const SETTINGS: &'static [(&'static str, &'static str)] = &[
+        ("theme", "Colors for a dark or light terminal"),
+        ("currency", "Symbol put before amounts in totals"),
+        ("default-type", "Type filled in for new expenses"),
+        (
+            "categories",
+            "Types to pick from besides those in use, separated by commas",
+        ),
+        ("date-format", "How dates are shown, e.g. %d %b %Y"),
+        ("date-order", "Order of day and month in dates like 12/6"),
+        (
+            "month-start",
+            "Day months start on, from 1 to 28, e.g. 25 for payday",
+        ),
+        ("week-start", "Day weeks start on, e.g. sunday"),
+        (
+            "savings-exclude",
+            "Types and tags left out of the savings rate, separated by commas",
+        ),
+        ("privacy", "Start with the amounts hidden, true or false"),
+        (
+            "columns",
+            "Columns of the table in order, separated by commas",
+        ),
+        (
+            "wrap",
+            "Wrap long descriptions onto a second line, true or false",
+        ),
+        (
+            "data-dir",
+            "Directory of expenses.csv, used from the next start",
+        ),
+    ];
+
+    /// A setting's value as written in the config file, e.g. `month-day` for `date-order`. Keys
+    /// are given as `keys.<action>`, budgets as `budgets.<type>`, where they roll over from as
+    /// `rollover.<type>`, colors as `colors.<type>` and opening balances as `accounts.<name>`.
+    pub fn get(&self, key: &str) -> Option<String> {
+        match key {
+            "theme" => Some(self.theme.to_string()),
+            "currency" => Some(self.currency.clone()),
+            "default-type" => Some(self.default_type.clone().unwrap_or_default()),
+            "categories" => Some(self.categories.join(", ")),
+            "date-format" => Some(self.date_format.clone().unwrap_or_default()),
+            "date-order" => Some(self.date_order.to_string()),
+            "month-start" => Some(
+                self.month_start
+                    .map_or(String::new(), |day| day.to_string()),
+            ),
+            "week-start" => Some(self.week_start.clone().unwrap_or_default()),
+            "savings-exclude" => Some(self.savings_exclude.join(", ")),
+            "privacy" => Some(self.privacy.to_string()),
+            "columns" => Some(self.columns.join(", ")),
+            "wrap" => Some(self.wrap.to_string()),
+            "data-dir" => Some(
+                self.data_dir
+                    .as_ref()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default(),
+            ),
+            _ if key.starts_with("colors.") => self.color(&key["colors.".len()..]).cloned(),
+            _ if key.starts_with("accounts.") => self
+                .account(&key["accounts.".len()..])
+                .map(|(_, opening)| opening.to_string()),
+            _ if key.starts_with("rollover.") => Some(
+                self.rollover(&key["rollover.".len()..])
+                    .map(|start| start.to_string())
+                    .unwrap_or_default(),
+            ),
+            _ => match key.strip_prefix("budgets.") {
+                Some(expense_type) => self
+                    .budgets
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(expense_type))
+                    .map(|(_, budget)| budget.to_string()),
+                None => self.keys.get(key.strip_prefix("keys.")?).cloned(),
+            },
+        }
+    }
+
+    /// Changes a setting by its name in the config file, e.g. `date-order` to `month-day`. An
+    /// empty value goes back to the default. Keys are set as `keys.<action>`, budgets as
+    /// `budgets.<type>`, colors as `colors.<type>` and opening balances as `accounts.<name>`, an
+    /// empty one removing the budget, color or account. `rollover.<type>` makes a budget roll
+    /// over from a month like `2024-06`, or this month for `true`, and `false` or empty stops it.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let value = value.trim();
+        let optional = || (!value.is_empty()).then(|| value.to_string());
+        match key {
+            "theme" if value.is_empty() => self.theme = Theme::default(),
+            "theme" => self.theme = value.parse()?,
+            "currency" => self.currency = value.to_string(),
+            "default-type" => self.default_type = optional(),
+            "categories" => {
+                self.categories = value
+                    .split(',')
+                    .map(|category| capitalize(category.trim().to_string()))
+                    .filter(|category| !category.is_empty())
+                    .collect()
+            }
+            "savings-exclude" => {
+                self.savings_exclude = value
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            }
+            "date-format" => {
+                if !is_valid_date_format(value) {
+                    return Err(format!("Invalid date format '{}'", value).into());
+                }
+                self.date_format = optional();
+            }
+            "date-order" if value.is_empty() => self.date_order = DateOrder::default(),
+            "date-order" => self.date_order = value.parse()?,
+            "month-start" => {
+                self.month_start = match value {
+                    "" => None,
+                    value => Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|day| (1..=28).contains(day))
+                            .ok_or_else(|| format!("'{}' isn't a day from 1 to 28", value))?,
+                    ),
+                };
+                set_month_start(self.month_start.unwrap_or(1));
+            }
+            "week-start" => {
+                let weekday = match value {
+                    "" => Weekday::Mon,
+                    value => value
+                        .parse::<Weekday>()
+                        .map_err(|_| format!("'{}' isn't a day of the week", value))?,
+                };
+                self.week_start = optional().map(|_| weekday_name(weekday).to_lowercase());
+                set_week_start(weekday);
+            }
+            "privacy" => {
+                self.privacy = match value {
+                    "" => false,
+                    value => value
+                        .parse()
+                        .map_err(|_| format!("Expected true or false, not '{}'", value))?,
+                };
+                self.hide_amounts = self.privacy;
+            }
+            "wrap" => {
+                self.wrap = match value {
+                    "" => false,
+                    value => value
+                        .parse()
+                        .map_err(|_| format!("Expected true or false, not '{}'", value))?,
+                };
+            }
+            "columns" => {
+                let mut columns: Vec<String> = Vec::new();
+                for column in value.split(',').map(|column| column.trim().to_lowercase()) {
+                    if column.is_empty() || columns.contains(&column) {
+                        continue;
+                    }
+                    if !Self::COLUMNS.contains(&column.as_str()) {
+                        return Err(format!(
+                            "Unknown column '{}', columns are: {}",
+                            column,
+                            Self::COLUMNS.join(", ")
+                        )
+                        .into());
+                    }
+                    columns.push(column);
+                }
+                self.columns = columns;
+            }
+            "data-dir" => self.data_dir = optional().map(|dir| expand_home(&dir)),
+            _ if key.starts_with("budgets.") => {
+                let expense_type = capitalize(key["budgets.".len()..].trim().to_string());
+                self.budgets
+                    .retain(|name, _| !name.eq_ignore_ascii_case(&expense_type));
+                if value.is_empty() {
+                    self.rollover
+                        .retain(|name, _| !name.eq_ignore_ascii_case(&expense_type));
+                } else {
+                    let budget = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|budget| *budget > 0.0)
+                        .ok_or_else(|| format!("'{}' isn't an amount above zero", value))?;
+                    self.budgets.insert(expense_type, budget);
+                }
+            }
+            _ if key.starts_with("rollover.") => {
+                let expense_type = capitalize(key["rollover.".len()..].trim().to_string());
+                self.rollover
+                    .retain(|name, _| !name.eq_ignore_ascii_case(&expense_type));
+                if !matches!(value, "" | "false") {
+                    if self.budget(&expense_type).is_none() {
+                        return Err(format!("No budget is set for {}", expense_type).into());
+                    }
+                    let today = Local::now().date_naive();
+                    let month = match value {
+                        "true" => DateRange::month_of(today),
+                        value => parse_range(value, today)
+                            .and_then(|range| DateRange::month_of(range.start)),
+                    }
+                    .ok_or_else(|| format!("'{}' isn't a month, like 2024-06", value))?;
+                    self.rollover.insert(expense_type, month.start.to_string());
+                }
+            }
+            _ if key.starts_with("accounts.") => {
+                let name = key["accounts.".len()..].trim().to_string();
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return Err("Account names are a single word, like a tag".into());
+                }
+                self.accounts
+                    .retain(|account, _| !account.eq_ignore_ascii_case(&name));
+                if !value.is_empty() {
+                    let opening = value
+                        .parse::<f64>()
+                        .map_err(|_| format!("'{}' isn't an amount", value))?;
+                    self.accounts.insert(name, opening);
+                }
+            }
+            _ if key.starts_with("colors.") => {
+                let expense_type = capitalize(key["colors.".len()..].trim().to_string());
+                self.colors
+                    .retain(|name, _| !name.eq_ignore_ascii_case(&expense_type));
+                if !value.is_empty() {
+                    value.parse::<ratatui::style::Color>().map_err(|_| {
+                        format!("'{}' isn't a color, like green or #8e44ad", value)
+                    })?;
+                    self.colors.insert(expense_type, value.to_string());
+                }
+            }
+            _ => match key.strip_prefix("keys.") {
+                Some(action) if value.is_empty() => {
+                    self.keys.remove(action);
+                }
+                Some(action) => {
+                    self.keys.insert(action.to_string(), value.to_string());
+                }
+                None => {
+                    return Err(format!(
+                        "Unknown setting '{}', settings are: {}, budgets.<type>, rollover.<type>, colors.<type>, accounts.<name>, keys.<action>",
+                        key,
+                        Self::SETTINGS
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                    .into())
+                }
+            },
+        }
+        trace!("Set {} to {}", key, value);
+
+        Ok(())
+    }
+
+    /// The account named `name` in the `[accounts]` section whatever its case, with its opening
+    /// balance.
+    pub fn account(&self, name: &str) -> Option<(&String, f64)> {
+        self.accounts
+            .iter()
+            .find(|(account, _)| account.eq_ignore_ascii_case(name.trim()))
+            .map(|(account, opening)| (account, *opening))
+    }
+
+    /// The monthly budget set for `expense_type` in the `[budgets]` section, whatever its case.
+    pub fn budget(&self, expense_type: &str) -> Option<f64> {
+        self.budgets
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(expense_type.trim()))
+            .map(|(_, budget)| *budget)
+    }
+
+    /// Whether the `expense` is left out of the savings rate, its type or one of its tags being
+    /// in `savings-exclude`, whatever its case.
+    pub fn excluded_from_savings(&self, expense: &Expense) -> bool {
+        self.savings_exclude.iter().any(|name| {
+            name.eq_ignore_ascii_case(expense.expense_type.trim())
+                || expense
+                    .tags
+                    .iter()
+                    .any(|tag| name.eq_ignore_ascii_case(tag))
+        })
+    }
+
+    /// The first day of the month the budget of `expense_type` rolls over from, set in the
+    /// `[rollover]` section whatever its case.
+    pub fn rollover(&self, expense_type: &str) -> Option<NaiveDate> {
+        self.rollover
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(expense_type.trim()))
+            .and_then(|(_, start)| parse_iso(start))
+    }
+
+    /// The color set for `expense_type` in the `[colors]` section, whatever its case.
+    pub fn color(&self, expense_type: &str) -> Option<&String> {
+        self.colors
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(expense_type.trim()))
+            .map(|(_, color)| color)
+    }
+
+    /// Formats an amount with the currency, e.g. `-$12.50`, or masks it while the amounts are
+    /// hidden.
+    pub fn money(&self, amount: f64) -> String {
+        if self.hide_amounts {
+            return MASK.to_string();
+        }
+        // Sums of nothing are -0.0
+        let amount = amount + 0.0;
+        if amount < 0.0 {
+            format!("-{}{:.2}", self.currency, -amount)
+        } else {
+            format!("{}{:.2}", self.currency, amount)
+        }
+    }
+
+    /// Formats an amount shortly for the charts, with thousands separators up to 10,000 and
+    /// abbreviated after, e.g. `$12.50`, `$1,250.75`, `$1,250`, `$12.5k` or `$1.2M`. Cents are
+    /// left out of whole amounts.
+    pub fn short_money(&self, amount: f64) -> String {
+        if self.hide_amounts {
+            return MASK.to_string();
+        }
+        let sign = if amount < 0.0 { "-" } else { "" };
+        let amount = amount.abs();
+        let number = if amount >= 999_950.0 {
+            format!("{:.1}M", amount / 1_000_000.0).replace(".0M", "M")
+        } else if amount >= 9_999.5 {
+            format!("{:.1}k", amount / 1_000.0).replace(".0k", "k")
+        } else {
+            let cents = (amount * 100.0).round() as u64;
+            match cents % 100 {
+                0 => group_thousands(cents / 100),
+                rest => format!("{}.{:02}", group_thousands(cents / 100), rest),
+            }
+        };
+        format!("{}{}{}", sign, self.currency, number)
+    }
+
+    /// Formats a stored YYYY-MM-DD date with the date format, leaving it as is without one.
+    pub fn format_date(&self, date: &str) -> String {
+        match (&self.date_format, parse_iso(date)) {
+            (Some(format), Some(parsed)) if is_valid_date_format(format) => {
+                parsed.format(format).to_string()
+            }
+            _ => date.to_string(),
+        }
+    }
+
+    /// Looks up a saved filter by name.
+    pub fn filter(&self, name: &str) -> Result<&str, Box<dyn std::error::Error>> {
+        self.filters.get(name).map(String::as_str).ok_or_else(|| {
+            let known = self.filters.keys().cloned().collect::<Vec<_>>().join(", ");
+            format!("Unknown filter '{}', saved filters are: {}", name, known).into()
+        })
+    }
+
+    pub fn get_config_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let config_dir = dirs::config_dir().ok_or("Unable to determine user's config directory")?;
+        Ok(config_dir.join("budget-tracker").join("config.toml"))
+    }
+}
+
+/// The path with a leading `~` standing for the home directory, as in a shell.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// A number with a comma between every three digits, e.g. `1,250`.
+fn group_thousands(number: u64) -> String {
+    let digits = number.to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Whether chrono understands every specifier of a strftime format; formatting with an unknown
+/// one panics.
+fn is_valid_date_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| item == Item::Error)
+}
@@ -0,0 +1,260 @@
+//! Resolves human friendly date expressions, like `yesterday` or `3 weeks ago`, relative to a
+//! given day.
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The day of the month months start on, see [set_month_start].
+static MONTH_START: AtomicU32 = AtomicU32::new(1);
+
+/// Starts months on `day` rather than on the 1st, e.g. on the 25th for months running from
+/// payday to payday. A month is named after the one it starts in, so with the 25th `2024-06`
+/// runs from 25 June to 24 July. Days past the 28th aren't in every month and count as the 28th.
+pub fn set_month_start(day: u32) {
+    MONTH_START.store(day.clamp(1, 28), Ordering::Relaxed);
+}
+
+/// The day of the month months start on, 1 unless set with [set_month_start].
+pub fn month_start() -> u32 {
+    MONTH_START.load(Ordering::Relaxed)
+}
+
+/// The first day of the week as days from Monday, see [set_week_start].
+static WEEK_START: AtomicU32 = AtomicU32::new(0);
+
+/// Starts weeks on `weekday` rather than on Monday, e.g. on Sunday.
+pub fn set_week_start(weekday: Weekday) {
+    WEEK_START.store(weekday.num_days_from_monday(), Ordering::Relaxed);
+}
+
+/// The first day of the week, Monday unless set with [set_week_start].
+pub fn week_start() -> Weekday {
+    Weekday::try_from(WEEK_START.load(Ordering::Relaxed) as u8).unwrap_or(Weekday::Mon)
+}
+
+/// How many days into the week `weekday` is, 0 for its first day.
+pub fn day_of_week(weekday: Weekday) -> u32 {
+    (7 + weekday.num_days_from_monday() - week_start().num_days_from_monday()) % 7
+}
+
+/// The order of day and month in short dates like `12/6`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DateOrder {
+    #[default]
+    DayMonth,
+    MonthDay,
+}
+
+impl std::fmt::Display for DateOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DateOrder::DayMonth => write!(f, "day-month"),
+            DateOrder::MonthDay => write!(f, "month-day"),
+        }
+    }
+}
+
+impl FromStr for DateOrder {
+    type Err = String;
+
+    /// Reads the order as written in the config, `day-month` or `month-day`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "day-month" => Ok(DateOrder::DayMonth),
+            "month-day" => Ok(DateOrder::MonthDay),
+            _ => Err(format!(
+                "Unknown date order '{}', expected day-month or month-day",
+                input
+            )),
+        }
+    }
+}
+
+/// An inclusive range of days.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        Self { start, end }
+    }
+
+    pub fn day(date: NaiveDate) -> Self {
+        Self::new(date, date)
+    }
+
+    /// The month starting in `month` of `year`, on the day set with [set_month_start].
+    pub fn month(year: i32, month: u32) -> Option<Self> {
+        let start = NaiveDate::from_ymd_opt(year, month, month_start())?;
+        let end = start.checked_add_months(Months::new(1))?.pred_opt()?;
+        Some(Self::new(start, end))
+    }
+
+    /// The month containing the date, which starts in the calendar month before when months
+    /// start later than the 1st and the date is before that.
+    pub fn month_of(date: NaiveDate) -> Option<Self> {
+        let start = match date.day() >= month_start() {
+            true => date,
+            false => date.checked_sub_months(Months::new(1))?,
+        };
+        Self::month(start.year(), start.month())
+    }
+
+    /// The month before the one containing the date.
+    pub fn month_before(date: NaiveDate) -> Option<Self> {
+        Self::month_of(Self::month_of(date)?.start.pred_opt()?)
+    }
+
+    pub fn year(year: i32) -> Option<Self> {
+        Some(Self::new(
+            NaiveDate::from_ymd_opt(year, 1, 1)?,
+            NaiveDate::from_ymd_opt(year, 12, 31)?,
+        ))
+    }
+
+    /// The week containing the date, starting on the day set with [set_week_start].
+    pub fn week(date: NaiveDate) -> Self {
+        let start = date - Days::new(day_of_week(date.weekday()) as u64);
+        Self::new(start, start + Days::new(6))
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// How many days the range has.
+    pub fn days(&self) -> u32 {
+        (self.end - self.start).num_days().max(0) as u32 + 1
+    }
+
+    /// The name of a month, e.g. `June 2024`, or its days when months don't start on the 1st,
+    /// e.g. `25 Jun - 24 Jul 2024`.
+    pub fn month_name(&self) -> String {
+        match self.start.day() {
+            1 => self.start.format("%B %Y").to_string(),
+            _ => format!(
+                "{} - {}",
+                self.start.format("%-d %b"),
+                self.end.format("%-d %b %Y")
+            ),
+        }
+    }
+}
+
+/// The full name of a weekday, e.g. `Saturday`.
+pub fn weekday_name(weekday: Weekday) -> String {
+    NaiveDate::from_isoywd_opt(2024, 1, weekday)
+        .map_or_else(|| weekday.to_string(), |date| date.format("%A").to_string())
+}
+
+/// Parses a date stored in the database, i.e. in the YYYY-MM-DD format.
+pub fn parse_iso(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Parses a single day: `today`, `yesterday`, `<n> <unit>(s) ago` (units being days, weeks,
+/// months or years, also written with hyphens as in `3-weeks-ago`) or a YYYY-MM-DD / YYYY/MM/DD
+/// date.
+pub fn parse_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase().replace('/', "-");
+    match input.as_str() {
+        "today" => return Some(today),
+        "yesterday" => return today.pred_opt(),
+        _ => {}
+    }
+
+    if let Some(date) = parse_iso(&input) {
+        return Some(date);
+    }
+
+    let words: Vec<&str> = input.split([' ', '-']).filter(|w| !w.is_empty()).collect();
+    match words.as_slice() {
+        [count, unit, "ago"] => subtract_units(today, count.parse().ok()?, unit),
+        _ => None,
+    }
+}
+
+/// Parses a date typed in when adding an expense. On top of everything [parse_date] accepts, this
+/// understands:
+/// - weekdays like `mon` or `friday`, meaning the latest such day up to today,
+/// - offsets like `-3d`, `-2w`, `-1m` or `-1y`,
+/// - short dates like `12/6` in the given [DateOrder], in the latest year that isn't in the future.
+pub fn parse_input_date(input: &str, today: NaiveDate, order: DateOrder) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+
+    if let Ok(weekday) = input.parse::<Weekday>() {
+        let days_back =
+            (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        return today.checked_sub_days(Days::new(days_back as u64));
+    }
+
+    if let Some(offset) = input.strip_prefix('-') {
+        let split = offset.find(|c: char| !c.is_ascii_digit())?;
+        let (count, unit) = offset.split_at(split);
+        let unit = match unit {
+            "d" => "day",
+            "w" => "week",
+            "m" => "month",
+            "y" => "year",
+            unit => unit,
+        };
+        return subtract_units(today, count.parse().ok()?, unit);
+    }
+
+    let parts: Vec<&str> = input.split(['/', '-', '.']).collect();
+    if let [first, second] = parts.as_slice() {
+        let (day, month) = match order {
+            DateOrder::DayMonth => (first, second),
+            DateOrder::MonthDay => (second, first),
+        };
+        let (day, month) = (day.parse().ok()?, month.parse().ok()?);
+        let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+        if date > today {
+            return NaiveDate::from_ymd_opt(today.year() - 1, month, day);
+        }
+        return Some(date);
+    }
+
+    parse_date(&input, today)
+}
+
+/// Goes back `count` days, weeks, months or years (singular or plural) from the date.
+fn subtract_units(date: NaiveDate, count: u32, unit: &str) -> Option<NaiveDate> {
+    match unit.trim_end_matches('s') {
+        "day" => date.checked_sub_days(Days::new(count as u64)),
+        "week" => date.checked_sub_days(Days::new(7 * count as u64)),
+        "month" => date.checked_sub_months(Months::new(count)),
+        "year" => date.checked_sub_months(Months::new(12 * count)),
+        _ => None,
+    }
+}
+
+/// Parses a range of days: `this-week`, `last-week`, `this-month`, `last-month`, `this-year`,
+/// `last-year`, a YYYY or YYYY-MM prefix, or any single day accepted by [parse_date].
+pub fn parse_range(input: &str, today: NaiveDate) -> Option<DateRange> {
+    let input = input.trim().to_lowercase().replace(['/', ' '], "-");
+    match input.as_str() {
+        "this-week" => return Some(DateRange::week(today)),
+        "last-week" => return Some(DateRange::week(today - Days::new(7))),
+        "this-month" => return DateRange::month_of(today),
+        "last-month" => return DateRange::month_before(today),
+        "this-year" => return DateRange::year(today.year()),
+        "last-year" => return DateRange::year(today.year() - 1),
+        _ => {}
+    }
+
+    let parts: Vec<&str> = input.split('-').collect();
+    match parts.as_slice() {
+        [year] if year.len() == 4 => DateRange::year(year.parse().ok()?),
+        [year, month] if year.len() == 4 => {
+            DateRange::month(year.parse().ok()?, month.parse().ok()?)
+        }
+        _ => parse_date(&input, today).map(DateRange::day),
+    }
+}
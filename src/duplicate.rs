@@ -0,0 +1,62 @@
+//! Entries likely written down twice: on the same day, for the same amount and with a similar
+//! description, like `Groceries` added from memory and `GROCERIES 0412` from the statement.
+
+use std::collections::BTreeMap;
+
+use crate::expense::Expense;
+use crate::payee;
+
+/// The letters and digits of a description's payee, in lowercase.
+fn normalized(description: &str) -> String {
+    payee::name(description)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Whether two descriptions likely name the same thing: they are the same leaving out case,
+/// spacing, punctuation and a trailing reference, or one starts with the other.
+pub fn similar(a: &str, b: &str) -> bool {
+    let (a, b) = (normalized(a), normalized(b));
+    if a.is_empty() || b.is_empty() {
+        return a == b;
+    }
+    a.starts_with(&b) || b.starts_with(&a)
+}
+
+/// Whether two entries are likely the same one written down twice.
+pub fn is_likely_duplicate(a: &Expense, b: &Expense) -> bool {
+    a.date == b.date && a.amount == b.amount && similar(&a.description, &b.description)
+}
+
+/// The groups of two or more likely duplicates among the `expenses`, as positions into them,
+/// by date and then in their order.
+pub fn groups(expenses: &[Expense]) -> Vec<Vec<usize>> {
+    // Only entries of the same day and amount can be duplicates
+    let mut candidates: BTreeMap<(&str, u64), Vec<usize>> = BTreeMap::new();
+    for (index, expense) in expenses.iter().enumerate() {
+        candidates
+            .entry((expense.date.as_str(), expense.amount.to_bits()))
+            .or_default()
+            .push(index);
+    }
+
+    let mut groups = Vec::new();
+    for indices in candidates.into_values().filter(|indices| indices.len() > 1) {
+        let mut found: Vec<Vec<usize>> = Vec::new();
+        for index in indices {
+            let expense = &expenses[index];
+            match found.iter_mut().find(|group| {
+                group
+                    .iter()
+                    .any(|&other| similar(&expenses[other].description, &expense.description))
+            }) {
+                Some(group) => group.push(index),
+                None => found.push(vec![index]),
+            }
+        }
+        groups.extend(found.into_iter().filter(|group| group.len() > 1));
+    }
+    groups
+}
@@ -1,10 +1,35 @@
 //! Defines all [Expense] struct related objects.
 
-use chrono::{Local, NaiveDate};
+use chrono::Local;
 use log::{error, trace};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
 use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::{env, process::Command};
-use std::{fs, path::PathBuf};
+
+use crate::budget;
+use crate::config::Config;
+use crate::date::{parse_input_date, parse_iso, DateOrder};
+
+/// The directory of the database when set in the config, see [set_data_dir].
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keeps the database in `dir` instead of `~/.local/share/budget-tracker`. Only the first call
+/// counts, as the database can't move while it is open.
+pub fn set_data_dir(dir: PathBuf) {
+    if DATA_DIR.set(dir).is_err() {
+        error!("The data directory was already set");
+    }
+}
+
+/// Where the database is kept unless the config says otherwise.
+pub fn default_data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let home_dir = dirs::home_dir().ok_or("Unable to determine user's home directory")?;
+    Ok(home_dir.join(".local").join("share").join("budget-tracker"))
+}
 
 pub fn capitalize(string: String) -> String {
     if string.is_empty() {
@@ -18,13 +43,51 @@ pub fn capitalize(string: String) -> String {
     first_char + &rest
 }
 
+/// The kinds of file expenses can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// The format of a file by its extension, CSV if it has none or an unknown one.
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => ExportFormat::Json,
+            Some("md" | "markdown") => ExportFormat::Markdown,
+            _ => ExportFormat::Csv,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ExportFormat::Csv => ExportFormat::Json,
+            ExportFormat::Json => ExportFormat::Markdown,
+            ExportFormat::Markdown => ExportFormat::Csv,
+        }
+    }
+}
+
 /// The [Expense] struct; helps reading/writing data in a structured manner. It reflects the schema of the database.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Expense {
     pub date: String,
     pub description: String,
+    #[serde(rename = "type")]
     pub expense_type: String,
     pub amount: f64,
+    /// Optional labels, stored `;` separated in a fifth column.
+    pub tags: Vec<String>,
 }
 
 impl Expense {
@@ -34,29 +97,74 @@ impl Expense {
             description,
             expense_type: capitalize(expense_type),
             amount,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Parses `;` or whitespace separated tags, dropping empty ones.
+    pub fn parse_tags(tags: &str) -> Vec<String> {
+        tags.split(|c: char| c == ';' || c.is_whitespace())
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The expense as a line of the database, without the trailing newline. The tags column is
+    /// only written if there are tags.
+    pub fn to_csv_record(&self) -> String {
+        let mut record = format!(
+            "{},{},{},{}",
+            self.date, self.description, self.expense_type, self.amount
+        );
+        if !self.tags.is_empty() {
+            record.push(',');
+            record.push_str(&self.tags.join(";"));
         }
+        record
     }
 
     /**
     Function to add and expense to the database.
 
     Takes input from `stdin` for date, description, expense type and amount.
-    Support YYYY-MM-DD and YYYY/MM/DD date format as input.
+    Supports YYYY-MM-DD and YYYY/MM/DD dates as well as relative ones like `yesterday` or `-3d`.
     For amount no denoination is expected as of now.
     */
-    pub fn add_expense() -> Result<(), Box<dyn std::error::Error>> {
+    pub fn add_expense(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
         trace!("Adding expense ...");
-        let date = Self::input_date()?;
+        let date = Self::input_date(config.date_order)?;
         let description = Self::input("Enter description:")?;
-        let expense_type = capitalize(Self::input(
-            "Enter expense type (Food, Travel, Fun, Medical, Personal or Other): ",
-        )?);
+        let expense_type = match &config.default_type {
+            Some(default_type) => {
+                let input = Self::input(&format!(
+                    "Enter expense type (Food, Travel, Fun, Medical, Personal or Other, leave empty for {}): ",
+                    default_type
+                ))?;
+                if input.is_empty() {
+                    default_type.clone()
+                } else {
+                    input
+                }
+            }
+            None => {
+                Self::input("Enter expense type (Food, Travel, Fun, Medical, Personal or Other): ")?
+            }
+        };
+        let expense_type = capitalize(expense_type);
         let amount = Self::input_amount()?;
-        let expense = Self::new(date, description, expense_type, amount);
+        let tags = Self::input("Enter tags separated by spaces (optional): ")?;
+        let mut expense = Self::new(date, description, expense_type, amount);
+        expense.tags = Self::parse_tags(&tags);
 
         Self::append_to_csv("expenses.csv", &expense)?;
         println!("Added your data to the db!");
         trace!("Added expense: {:?}", expense);
+        let expenses = Self::read_csv("expenses.csv")?;
+        if let Some(warning) = budget::of_expense(config, &expenses, &expense)
+            .and_then(|progress| progress.warning(config))
+        {
+            println!("Warning: {}", warning);
+        }
 
         Ok(())
     }
@@ -70,20 +178,31 @@ impl Expense {
         Ok(input.trim().to_string())
     }
 
-    /// Takes in an input of a Date format, currently defined as YYYY-MM-DD or YYYY/MM/DD
-    fn input_date() -> Result<String, Box<dyn std::error::Error>> {
+    /// Takes in a date, either as YYYY-MM-DD / YYYY/MM/DD or in a human friendly form like
+    /// `yesterday`, `mon`, `-3d` or `12/6` (see [parse_input_date]), and asks to confirm the
+    /// resolved date.
+    fn input_date(date_order: DateOrder) -> Result<String, Box<dyn std::error::Error>> {
         loop {
             let input = Self::input(
-                "Enter date (YYYY-MM-DD or YYYY/MM/DD, leave empty for today's date): ",
+                "Enter date (e.g. 2024-06-12, yesterday, mon, -3d or 12/6, leave empty for today's date): ",
             )?;
+            let today = Local::now().date_naive();
             if input.is_empty() {
-                return Ok(Local::now().format("%Y-%m-%d").to_string());
-            } else if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
-                return Ok(date.to_string());
-            } else if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y/%m/%d") {
-                return Ok(date.to_string());
-            } else {
-                println!("Invalid date format. Please enter the date in YYYY-MM-DD or YYYY/MM/DD format.");
+                return Ok(today.format("%Y-%m-%d").to_string());
+            }
+
+            let Some(date) = parse_input_date(&input, today, date_order) else {
+                println!("Invalid date. Please enter the date as YYYY-MM-DD, YYYY/MM/DD, a weekday, an offset like -3d or a day and month like 12/6.");
+                continue;
+            };
+
+            let confirmation = Self::input(&format!(
+                "Resolved to {} ({}). Is this correct? [Y/n]: ",
+                date.format("%Y-%m-%d"),
+                date.format("%A")
+            ))?;
+            if confirmation.is_empty() || confirmation.eq_ignore_ascii_case("y") {
+                return Ok(date.format("%Y-%m-%d").to_string());
             }
         }
     }
@@ -119,15 +238,193 @@ impl Expense {
         trace!("Appending to db ... ");
         let file_path = Expense::get_database_file_path(file_name)?;
         let mut file = fs::OpenOptions::new().append(true).open(file_path)?;
-        let data = format!(
-            "{},{},{},{}\n",
-            expense.date, expense.description, expense.expense_type, expense.amount
-        );
+        let data = format!("{}\n", expense.to_csv_record());
         file.write_all(data.as_bytes())?;
 
         Ok(())
     }
 
+    /// Replaces the record at position `row` (as returned by `read_csv`) with `expense`, or
+    /// removes it if `None`. The file is rewritten atomically and all other lines are kept as is.
+    pub fn replace_in_csv(
+        file_name: &str,
+        row: usize,
+        expense: Option<&Expense>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Expense::replace_rows_in_csv(file_name, &BTreeMap::from([(row, expense)]))
+    }
+
+    /// Like `replace_in_csv`, for several records at once in a single write.
+    pub fn replace_rows_in_csv(
+        file_name: &str,
+        changes: &BTreeMap<usize, Option<&Expense>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        trace!("Replacing rows {:?} in db ... ", changes.keys());
+        let file_path = Expense::get_database_file_path(file_name)?;
+        let content = fs::read_to_string(&file_path)?;
+
+        let mut data = String::with_capacity(content.len());
+        let mut records = 0;
+        let mut found = 0;
+        for (index, line) in content.lines().enumerate() {
+            if index > 0 && Self::is_record(line) {
+                records += 1;
+                if let Some(expense) = changes.get(&(records - 1)) {
+                    found += 1;
+                    if let Some(expense) = expense {
+                        data.push_str(&expense.to_csv_record());
+                        data.push('\n');
+                    }
+                    continue;
+                }
+            }
+            data.push_str(line);
+            data.push('\n');
+        }
+        if found != changes.len() {
+            return Err(format!(
+                "Only {} of the rows {:?} exist in {}",
+                found,
+                changes.keys(),
+                file_name
+            )
+            .into());
+        }
+
+        // Write to a temporary file first so a crash can't leave the database half written
+        let temp_path = file_path.with_extension("csv.tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, &file_path)?;
+
+        Ok(())
+    }
+
+    /// Inserts the expenses so they end up at the given positions (as returned by `read_csv`),
+    /// which have to be in ascending order. Undoes the removal of the records.
+    pub fn insert_rows_in_csv(
+        file_name: &str,
+        rows: &[(usize, &Expense)],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        trace!("Inserting {} rows into db ... ", rows.len());
+        let file_path = Expense::get_database_file_path(file_name)?;
+        let content = fs::read_to_string(&file_path)?;
+
+        let mut data = String::with_capacity(content.len());
+        let mut inserts = rows.iter().peekable();
+        let mut records = 0;
+        for (index, line) in content.lines().enumerate() {
+            if index > 0 && Self::is_record(line) {
+                while let Some((_, expense)) = inserts.next_if(|(row, _)| *row == records) {
+                    data.push_str(&expense.to_csv_record());
+                    data.push('\n');
+                    records += 1;
+                }
+                records += 1;
+            }
+            data.push_str(line);
+            data.push('\n');
+        }
+        for (row, expense) in inserts {
+            if *row != records {
+                return Err(format!("Can't insert at row {} of {}", row, file_name).into());
+            }
+            data.push_str(&expense.to_csv_record());
+            data.push('\n');
+            records += 1;
+        }
+
+        let temp_path = file_path.with_extension("csv.tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, &file_path)?;
+
+        Ok(())
+    }
+
+    /// Removes the records at the given positions, along with the expense stored there, and
+    /// appends them to the trash file next to the database so they can be recovered by hand.
+    pub fn delete_from_csv(
+        file_name: &str,
+        rows: &[(usize, &Expense)],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        trace!("Moving {} rows to the trash ... ", rows.len());
+        // The trash only gets the rows once they are gone from the database
+        let changes = rows.iter().map(|&(row, _)| (row, None)).collect();
+        Expense::replace_rows_in_csv(file_name, &changes)?;
+
+        let trash_path = Expense::get_database_file_path(&format!("{}.trash", file_name))?;
+        let mut trash = fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(trash_path)?;
+        let data: String = rows
+            .iter()
+            .map(|(_, expense)| format!("{}\n", expense.to_csv_record()))
+            .collect();
+        trash.write_all(data.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Writes the expenses to a new CSV file at `path`, in the same format as the database.
+    pub fn export_csv(
+        path: &Path,
+        expenses: &[&Expense],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        trace!(
+            "Exporting {} expenses to {} ... ",
+            expenses.len(),
+            path.display()
+        );
+        let mut data = String::from("Date,Description,Type,Amount\n");
+        for expense in expenses {
+            data.push_str(&expense.to_csv_record());
+            data.push('\n');
+        }
+        fs::write(path, data)?;
+
+        Ok(())
+    }
+
+    /// Writes the expenses to a new file at `path`, in the format its extension stands for: CSV
+    /// as in the database, a JSON array, or a Markdown table.
+    pub fn export(path: &Path, expenses: &[&Expense]) -> Result<(), Box<dyn std::error::Error>> {
+        match ExportFormat::of(path) {
+            ExportFormat::Csv => return Expense::export_csv(path, expenses),
+            ExportFormat::Json => {
+                let mut data = serde_json::to_string_pretty(expenses)?;
+                data.push('\n');
+                fs::write(path, data)?;
+            }
+            ExportFormat::Markdown => {
+                // Pipes would end the cell early
+                let cell = |value: &str| value.replace('|', "\\|");
+                let mut data = String::from(
+                    "| Date | Description | Type | Amount | Tags |\n|---|---|---|---:|---|\n",
+                );
+                for expense in expenses {
+                    data.push_str(&format!(
+                        "| {} | {} | {} | {:.2} | {} |\n",
+                        expense.date,
+                        cell(&expense.description),
+                        cell(&expense.expense_type),
+                        expense.amount,
+                        cell(&expense.tags.join(" "))
+                    ));
+                }
+                fs::write(path, data)?;
+            }
+        }
+        trace!("Exported {} expenses to {}", expenses.len(), path.display());
+
+        Ok(())
+    }
+
+    /// Whether a line of the database holds an expense, the same check `read_csv` makes.
+    fn is_record(line: &str) -> bool {
+        let fields = line.split(',').count();
+        fields == 4 || fields == 5
+    }
+
     /// Read the database if its present from ~/.local/share/budget-tracker/expenses.csv;
     /// if not present it returns an error.
     pub fn read_csv(file_name: &str) -> Result<Vec<Expense>, Box<dyn std::error::Error>> {
@@ -143,21 +440,64 @@ impl Expense {
             if index == 0 {
                 continue; // Skip header
             }
-            let fields: Vec<&str> = line.split(',').collect();
-            if fields.len() == 4 {
+            if Self::is_record(&line) {
+                let fields: Vec<&str> = line.split(',').collect();
                 let expense_type: String = fields[2].parse()?;
-                let expense = Expense::new(
+                let mut expense = Expense::new(
                     fields[0].to_string(),
                     fields[1].to_string(),
                     expense_type,
                     fields[3].parse::<f64>()?,
                 );
+                if let Some(tags) = fields.get(4) {
+                    expense.tags = Self::parse_tags(tags);
+                }
                 expenses.push(expense);
             }
         }
         Ok(expenses)
     }
 
+    /// What is wrong with the lines of the database that reading it passes over or that the
+    /// reports leave out: lines without the four or five fields of an entry, and entries whose
+    /// date or amount isn't one. Each is a sentence naming the line.
+    pub fn problems(file_name: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let file = fs::File::open(Expense::get_database_file_path(file_name)?)?;
+        let mut problems = Vec::new();
+        // Skipping the header, and counting lines from one as editors do
+        for (index, line) in BufReader::new(file).lines().enumerate().skip(1) {
+            let line = line?;
+            let number = index + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !Self::is_record(&line) {
+                let fields = line.split(',').count();
+                problems.push(format!(
+                    "Line {} has {} {} rather than 4 or 5.",
+                    number,
+                    fields,
+                    if fields == 1 { "field" } else { "fields" }
+                ));
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').collect();
+            if parse_iso(fields[0]).is_none() {
+                problems.push(format!(
+                    "Line {} has '{}' as its date, which isn't a YYYY-MM-DD date.",
+                    number, fields[0]
+                ));
+            }
+            if fields[3].parse::<f64>().is_err() {
+                problems.push(format!(
+                    "Line {} has '{}' as its amount, which isn't a number.",
+                    number, fields[3]
+                ));
+            }
+        }
+        Ok(problems)
+    }
+
     /// Creates the database. Usually called when running the program for the first time.
     pub fn create_expenses_csv() -> Result<(), Box<dyn std::error::Error>> {
         trace!("Creating the db ... ");
@@ -172,19 +512,18 @@ impl Expense {
         }
 
         let expenses_file = budget_tracker_dir.join("expenses.csv");
-        if let Err(err) = fs::File::create(&expenses_file) {
+        // The first line is skipped as a header when reading
+        if let Err(err) = fs::write(&expenses_file, "Date,Description,Type,Amount\n") {
             error!("Error creating file {}: {}", expenses_file.display(), err);
             return Err(err.into());
         }
         Ok(())
     }
 
-    fn get_database_file_path(file_name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let home_dir = dirs::home_dir().ok_or("Unable to determine user's home directory")?;
-        Ok(home_dir
-            .join(".local")
-            .join("share")
-            .join("budget-tracker")
-            .join(file_name))
+    pub fn get_database_file_path(file_name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        match DATA_DIR.get() {
+            Some(dir) => Ok(dir.join(file_name)),
+            None => Ok(default_data_dir()?.join(file_name)),
+        }
     }
 }
@@ -0,0 +1,146 @@
+//! What the spending of a month will come to by its end, from what was spent so far. Regular
+//! expenses, the payees paid in each of the two months before, are expected once in the month
+//! whenever they come, and the rest of the spending goes on at the pace of the days so far.
+
+use chrono::{Months, NaiveDate};
+use std::collections::HashMap;
+
+use crate::budget::is_budgeted;
+use crate::config::Config;
+use crate::date::{parse_iso, DateRange};
+use crate::expense::Expense;
+use crate::payee;
+
+/// A regular expense, paid in each of the two months before.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regular {
+    pub name: String,
+    /// What it came to on average in the two months before, as a positive amount
+    pub amount: f64,
+    /// What it came to so far this month, as a positive amount, zero while it is to come
+    pub paid: f64,
+}
+
+impl Regular {
+    pub fn is_paid(&self) -> bool {
+        self.paid > 0.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Forecast {
+    pub month: DateRange,
+    /// How many days of the month are over, today included
+    pub days: u32,
+    /// What was spent so far, as a positive amount
+    pub spent: f64,
+    /// What was spent so far on anything but the regular expenses
+    pub variable: f64,
+    /// The regular expenses, the most they usually come to first
+    pub regular: Vec<Regular>,
+}
+
+impl Forecast {
+    /// The forecast of the month of `today` from the `expenses`, which should reach back two
+    /// months to find the regular ones.
+    pub fn new(expenses: &[&Expense], today: NaiveDate) -> Option<Self> {
+        let month = DateRange::month_of(today)?;
+        let months: Vec<DateRange> = (1..=2)
+            .filter_map(|back| month.start.checked_sub_months(Months::new(back)))
+            .filter_map(DateRange::month_of)
+            .collect();
+
+        // What went to each payee in this month and each of the two before
+        let mut payees: HashMap<String, (String, [f64; 3])> = HashMap::new();
+        for expense in expenses.iter().filter(|expense| expense.amount < 0.0) {
+            let Some(date) = parse_iso(&expense.date).filter(|date| *date <= today) else {
+                continue;
+            };
+            let Some(index) = [month]
+                .iter()
+                .chain(&months)
+                .position(|range| range.contains(date))
+            else {
+                continue;
+            };
+            let name = payee::name(&expense.description);
+            payees
+                .entry(name.to_lowercase())
+                .or_insert_with(|| (name, [0.0; 3]))
+                .1[index] -= expense.amount;
+        }
+
+        let (mut spent, mut variable) = (0.0, 0.0);
+        let mut regular = Vec::new();
+        for (name, [now, before, earlier]) in payees.into_values() {
+            spent += now;
+            if months.len() == 2 && before > 0.0 && earlier > 0.0 {
+                regular.push(Regular {
+                    name,
+                    amount: (before + earlier) / 2.0,
+                    paid: now,
+                });
+            } else {
+                variable += now;
+            }
+        }
+        regular.sort_by(|a, b| {
+            b.amount
+                .total_cmp(&a.amount)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Some(Self {
+            month,
+            days: DateRange::new(month.start, today).days(),
+            spent,
+            variable,
+            regular,
+        })
+    }
+
+    /// The forecast of the spending on the types with a budget in the config, with what the
+    /// budgets add up to, if any are set.
+    pub fn of_budgets(
+        config: &Config,
+        expenses: &[&Expense],
+        today: NaiveDate,
+    ) -> Option<(Self, f64)> {
+        if config.budgets.is_empty() {
+            return None;
+        }
+        let budgeted: Vec<&Expense> = expenses
+            .iter()
+            .filter(|expense| is_budgeted(config, &expense.expense_type))
+            .copied()
+            .collect();
+        let forecast = Self::new(&budgeted, today)?;
+        Some((forecast, config.budgets.values().sum()))
+    }
+
+    /// How many days the month has.
+    pub fn month_days(&self) -> u32 {
+        self.month.days()
+    }
+
+    /// What the spending other than the regular expenses comes to a day so far.
+    pub fn daily_pace(&self) -> f64 {
+        self.variable / self.days.max(1) as f64
+    }
+
+    /// The regular expenses still to come this month.
+    pub fn upcoming(&self) -> impl Iterator<Item = &Regular> {
+        self.regular.iter().filter(|regular| !regular.is_paid())
+    }
+
+    /// What the regular expenses still to come add up to.
+    pub fn upcoming_total(&self) -> f64 {
+        self.upcoming().map(|upcoming| upcoming.amount).sum()
+    }
+
+    /// What the month's spending comes to by its end, as a positive amount: what was spent so
+    /// far, the rest of the days at the pace so far and the regular expenses to come.
+    pub fn projected(&self) -> f64 {
+        let days_left = self.month_days().saturating_sub(self.days) as f64;
+        self.spent + self.daily_pace() * days_left + self.upcoming_total()
+    }
+}
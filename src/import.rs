@@ -0,0 +1,227 @@
+//! Reading bank statements and other CSV files into expenses, with the meaning of each column
+//! chosen by hand since every bank lays them out differently.
+
+use chrono::{Local, NaiveDate};
+use log::trace;
+use std::{fs, path::Path};
+
+use crate::date::{parse_input_date, DateOrder};
+use crate::duplicate::is_likely_duplicate;
+use crate::expense::Expense;
+
+/// What a column of a statement holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Ignore,
+    Date,
+    Description,
+    Type,
+    Amount,
+}
+
+impl Field {
+    pub const ALL: [Field; 5] = [
+        Field::Ignore,
+        Field::Date,
+        Field::Description,
+        Field::Type,
+        Field::Amount,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Field::Ignore => "-",
+            Field::Date => "Date",
+            Field::Description => "Description",
+            Field::Type => "Type",
+            Field::Amount => "Amount",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&field| field == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&field| field == self)
+            .unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// The field a column is likely to hold from its title, e.g. `Payee` for the description.
+    fn guess(title: &str) -> Self {
+        let title = title.to_lowercase();
+        let any = |words: &[&str]| words.iter().any(|word| title.contains(word));
+        if any(&["date", "booked", "posted"]) {
+            Field::Date
+        } else if any(&["amount", "value", "sum"]) {
+            Field::Amount
+        } else if any(&["category", "type"]) {
+            Field::Type
+        } else if any(&[
+            "description",
+            "payee",
+            "memo",
+            "narrative",
+            "details",
+            "name",
+        ]) {
+            Field::Description
+        } else {
+            Field::Ignore
+        }
+    }
+}
+
+/// The lines of a CSV file split into columns, the first one being the titles.
+pub struct Statement {
+    pub titles: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Statement {
+    pub fn read(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        trace!("Reading the statement {} ...", path.display());
+        let content = fs::read_to_string(path)?;
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        let titles = split_csv_line(lines.next().ok_or("The file is empty")?);
+        let rows = lines.map(split_csv_line).collect();
+        Ok(Self { titles, rows })
+    }
+
+    /// The field of every column guessed from its title, each field used at most once.
+    pub fn guess_fields(&self) -> Vec<Field> {
+        let mut fields: Vec<Field> = Vec::new();
+        for title in &self.titles {
+            let field = Field::guess(title);
+            fields.push(if fields.contains(&field) {
+                Field::Ignore
+            } else {
+                field
+            });
+        }
+        fields
+    }
+
+    /// Every row read as an expense with the columns holding the `fields`, or why it can't be.
+    /// Rows without a type column get an empty type, to be picked later.
+    pub fn expenses(&self, fields: &[Field], order: DateOrder) -> Vec<Result<Expense, String>> {
+        for required in [Field::Date, Field::Description, Field::Amount] {
+            if !fields.contains(&required) {
+                let error = format!("No column holds the {}", required.label().to_lowercase());
+                return self.rows.iter().map(|_| Err(error.clone())).collect();
+            }
+        }
+        self.rows
+            .iter()
+            .map(|row| Self::expense(row, fields, order))
+            .collect()
+    }
+
+    fn expense(row: &[String], fields: &[Field], order: DateOrder) -> Result<Expense, String> {
+        let column = |wanted: Field| -> &str {
+            fields
+                .iter()
+                .position(|&field| field == wanted)
+                .and_then(|index| row.get(index))
+                .map_or("", |value| value.trim())
+        };
+        let date = parse_statement_date(column(Field::Date), order)
+            .ok_or_else(|| format!("Can't read the date '{}'", column(Field::Date)))?;
+        let amount = parse_amount(column(Field::Amount))
+            .ok_or_else(|| format!("Can't read the amount '{}'", column(Field::Amount)))?;
+        // Commas would break the columns of the database
+        let clean = |value: &str| {
+            value
+                .split(',')
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        Ok(Expense::new(
+            date.format("%Y-%m-%d").to_string(),
+            clean(column(Field::Description)),
+            clean(column(Field::Type)),
+            amount,
+        ))
+    }
+}
+
+/// Whether an expense with the same date and amount and a similar description is already among
+/// `expenses`, as when a statement overlaps with one imported before or with entries added by
+/// hand.
+pub fn is_duplicate(expense: &Expense, expenses: &[Expense]) -> bool {
+    expenses
+        .iter()
+        .any(|existing| is_likely_duplicate(existing, expense))
+}
+
+/// Splits a line of CSV into its values. Values can be quoted to hold commas, with `""` standing
+/// for a quote inside them.
+pub fn split_csv_line(line: &str) -> Vec<String> {
+    let mut values = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                values.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => values.push(String::new()),
+            c => values.last_mut().unwrap().push(c),
+        }
+    }
+    values
+}
+
+/// Reads the dates banks use: everything [parse_input_date] accepts, and full dates like
+/// `12/06/2024` in the given order.
+fn parse_statement_date(input: &str, order: DateOrder) -> Option<NaiveDate> {
+    let parts: Vec<&str> = input.split(['/', '.', '-']).collect();
+    if let [first, second, year] = parts.as_slice() {
+        if year.len() == 4 {
+            let (day, month) = match order {
+                DateOrder::DayMonth => (first, second),
+                DateOrder::MonthDay => (second, first),
+            };
+            return NaiveDate::from_ymd_opt(
+                year.parse().ok()?,
+                month.parse().ok()?,
+                day.parse().ok()?,
+            );
+        }
+    }
+    parse_input_date(input, Local::now().date_naive(), order)
+}
+
+/// Reads an amount like `-12.50`, `$1,200.00`, `1.234,56`, `12,50` or `(12.50)`, the last one
+/// negative as accountants write it. The last `.` or `,` is the decimal point when one or two
+/// digits follow it, any other is a thousands separator.
+fn parse_amount(input: &str) -> Option<f64> {
+    let negative = input.starts_with('(') && input.ends_with(')');
+    let amount: String = input
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | ','))
+        .collect();
+    let point = amount
+        .rfind(['.', ','])
+        .filter(|&point| matches!(amount.len() - point - 1, 1 | 2));
+    let amount: String = amount
+        .char_indices()
+        .filter_map(|(position, c)| match c {
+            '.' | ',' if Some(position) == point => Some('.'),
+            '.' | ',' => None,
+            c => Some(c),
+        })
+        .collect();
+    let amount: f64 = amount.parse().ok()?;
+    Some(if negative { -amount } else { amount })
+}
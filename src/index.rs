@@ -0,0 +1,115 @@
+//! A token index over the database, so searches don't have to look at every row.
+//!
+//! The index maps every lowercase word of the description, type and tags to the rows (positions
+//! in the database, header excluded) containing it, the fields a search looks at. The payee is
+//! read from the description, so its words are in it too. It is stored next to the database as
+//! `<database>.idx` together with the size and modification time of the database, and rebuilt
+//! whenever those or what is indexed change.
+
+use log::trace;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+use crate::expense::Expense;
+use crate::search::{Search, Term};
+
+/// Changed whenever what is indexed changes, so indexes written before are rebuilt.
+const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchIndex {
+    tokens: BTreeMap<String, BTreeSet<usize>>,
+}
+
+impl SearchIndex {
+    pub fn build(expenses: &[Expense]) -> Self {
+        let mut tokens: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+        for (row, expense) in expenses.iter().enumerate() {
+            let tags = expense.tags.iter().flat_map(|tag| tokenize(tag));
+            for token in tokenize(&expense.description)
+                .chain(tokenize(&expense.expense_type))
+                .chain(tags)
+            {
+                tokens.entry(token).or_default().insert(row);
+            }
+        }
+        Self { tokens }
+    }
+
+    /// Loads the index of the database, rebuilding and saving it if the database changed since
+    /// it was last written. `expenses` has to be the database as read by [Expense::read_csv].
+    pub fn load_or_build(
+        file_name: &str,
+        expenses: &[Expense],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let database_path = Expense::get_database_file_path(file_name)?;
+        let index_path = Expense::get_database_file_path(&format!("{}.idx", file_name))?;
+        let metadata = fs::metadata(&database_path)?;
+        let fingerprint = format!(
+            "{} {} {}",
+            VERSION,
+            metadata.len(),
+            metadata.modified()?.duration_since(UNIX_EPOCH)?.as_nanos()
+        );
+
+        if let Ok(contents) = fs::read_to_string(&index_path) {
+            let mut lines = contents.lines();
+            if lines.next() == Some(fingerprint.as_str()) {
+                trace!("Using the search index at {}", index_path.display());
+                return Ok(Self::parse(lines));
+            }
+        }
+
+        trace!("Rebuilding the search index ...");
+        let index = Self::build(expenses);
+        let mut contents = fingerprint;
+        for (token, rows) in &index.tokens {
+            let rows: Vec<String> = rows.iter().map(usize::to_string).collect();
+            contents.push_str(&format!("\n{}\t{}", token, rows.join(" ")));
+        }
+        fs::write(index_path, contents)?;
+
+        Ok(index)
+    }
+
+    fn parse<'a>(lines: impl Iterator<Item = &'a str>) -> Self {
+        let tokens = lines
+            .filter_map(|line| {
+                let (token, rows) = line.split_once('\t')?;
+                let rows = rows.split(' ').filter_map(|row| row.parse().ok()).collect();
+                Some((token.to_string(), rows))
+            })
+            .collect();
+        Self { tokens }
+    }
+
+    /// Rows containing a word starting with `prefix`.
+    pub fn rows_with_prefix(&self, prefix: &str) -> BTreeSet<usize> {
+        self.tokens
+            .range(prefix.to_string()..)
+            .take_while(|(token, _)| token.starts_with(prefix))
+            .flat_map(|(_, rows)| rows.iter().copied())
+            .collect()
+    }
+
+    /// Rows which might match the search, i.e. contain every word of it at the start of a word.
+    /// Returns [None] if the search has no words to look up.
+    pub fn candidates(&self, search: &Search) -> Option<BTreeSet<usize>> {
+        search
+            .terms
+            .iter()
+            .flat_map(|term| match term {
+                Term::Word(text) | Term::Phrase(text) => tokenize(text),
+            })
+            .map(|token| self.rows_with_prefix(&token))
+            .reduce(|candidates, rows| &candidates & &rows)
+    }
+}
+
+/// Splits text into lowercase words of letters and digits.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+}
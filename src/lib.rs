@@ -1 +1,16 @@
+pub mod account;
+pub mod anomaly;
+pub mod budget;
+pub mod config;
+pub mod date;
+pub mod duplicate;
 pub mod expense;
+pub mod forecast;
+pub mod import;
+pub mod index;
+pub mod lock;
+pub mod payee;
+pub mod query;
+pub mod savings;
+pub mod search;
+pub mod stats;
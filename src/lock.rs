@@ -0,0 +1,38 @@
+//! A lock on the database held by the TUI, so a second TUI opened on it only reads it rather than
+//! both writing over each other's changes.
+//!
+//! The lock is taken on `<database>.lock` next to the database, and released by the system when
+//! the program exits, even if it crashes.
+
+use log::trace;
+use std::fs::{File, OpenOptions, TryLockError};
+
+use crate::expense::Expense;
+
+pub struct DatabaseLock {
+    /// Holds the lock until it is dropped
+    _file: File,
+}
+
+impl DatabaseLock {
+    /// Takes the lock of the database, or returns `None` if another instance holds it.
+    pub fn acquire(file_name: &str) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let path = Expense::get_database_file_path(&format!("{}.lock", file_name))?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {
+                trace!("Took the lock {}", path.display());
+                Ok(Some(Self { _file: file }))
+            }
+            Err(TryLockError::WouldBlock) => {
+                trace!("The lock {} is held by another instance", path.display());
+                Ok(None)
+            }
+            Err(TryLockError::Error(err)) => Err(err.into()),
+        }
+    }
+}
@@ -1,21 +1,37 @@
-//! Implements the TUI interface
+//! Parses the command line and starts the TUI
 
-use chrono::Utc;
-use clap::Parser;
+use chrono::{Datelike, Local, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
-use log::{debug, info, trace, error};
+use log::{error, info, trace};
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::{prelude::*, widgets::*};
-use std::{collections::HashMap, path::PathBuf};
-use std::{io, process::Command};
+use ratatui::Terminal;
+use std::io::{IsTerminal, Write};
+use std::{fs, io, path::PathBuf, process::Command};
 
+use budget_tracker::config::{expand_home, Config};
+use budget_tracker::date::{parse_iso, parse_range, set_month_start, set_week_start, DateRange};
+use budget_tracker::duplicate;
 use budget_tracker::expense::*;
+use budget_tracker::index::SearchIndex;
+use budget_tracker::lock::DatabaseLock;
+use budget_tracker::query::Query;
+use budget_tracker::search::{RegexSearch, Search};
+use budget_tracker::stats::Stats;
+
+mod report;
+mod tui;
+use report::compare::Comparison;
+use report::template::Data;
+use report::trend::By;
+use report::{Document, Format};
+use tui::chart::{ChartKind, Metric};
+use tui::setup::Setup;
+use tui::{App, Filter};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -32,17 +48,638 @@ struct Args {
     #[arg(short, long)]
     logs: bool,
 
-    /// Search entries
+    /// Search entries, optionally scoped with 'desc:<text>' or 'type:<text>'
     #[arg(short, long)]
     search: Option<String>,
+
+    /// Search descriptions with a case insensitive regular expression
+    #[arg(long, value_name = "PATTERN")]
+    search_regex: Option<String>,
+
+    /// Filter entries with a query, e.g. 'type:food AND amount<-100 AND date:2024-06'
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// Apply a filter saved in the config
+    #[arg(short, long, value_name = "NAME")]
+    filter: Option<String>,
+
+    /// Save the given query as a named filter in the config
+    #[arg(long, value_name = "NAME", requires = "query")]
+    save_filter: Option<String>,
+
+    /// Change a setting in the config, e.g. 'date-order=month-day'
+    #[arg(long, value_name = "KEY=VALUE")]
+    set: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Write a report of the entries, limited by the search, query and filter given, instead
+    /// of starting the TUI
+    Report {
+        #[command(subcommand)]
+        report: Option<Report>,
+
+        /// Write the report of a week, a month or a year against the one before instead, e.g.
+        /// last-month, to run from cron: the database is checked first, failing on any problem
+        #[arg(long, value_name = "PERIOD")]
+        period: Option<String>,
+
+        /// Write the report of the period to this file, or to a directory under a name made of
+        /// the period, e.g. month-2024-06.txt
+        #[arg(long = "output", value_name = "PATH", requires = "period")]
+        destination: Option<String>,
+
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Print statistics of the spending of the entries, limited by the search, query and filter
+    /// given: what a day and a month cost on average, how the days spread out, the busiest
+    /// weekday and the average expense
+    Stats {
+        /// The days to look at, e.g. this-year, last-month, 2024 or all
+        #[arg(short, long, default_value = "all")]
+        period: String,
+
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Check the database for problems and fix them, every check unless some are picked
+    Doctor {
+        /// Find entries likely written down twice, on the same day for the same amount with a
+        /// similar description, and pick which to keep
+        #[arg(long)]
+        duplicates: bool,
+    },
+}
+
+/// How a report is written out.
+#[derive(clap::Args, Debug)]
+struct Output {
+    /// How to write the report: as text, Markdown, a web page with charts to share or a PDF,
+    /// e.g. `report month --format html > month.html`. `compare` also writes json and csv
+    #[arg(short, long, value_enum, default_value_t, global = true)]
+    format: Format,
+
+    /// Write the report through a Tera template instead, a NAME.tera in the templates
+    /// directory beside the config or the path to one
+    #[arg(long, value_name = "NAME", global = true)]
+    template: Option<String>,
+
+    /// Post the report to this URL instead of printing it, e.g. an ntfy topic or a chat's
+    /// webhook, with its title in a Title header
+    #[arg(long, value_name = "URL", global = true)]
+    post_url: Option<String>,
+
+    /// Print nothing when the report was written to a file or posted
+    #[arg(short, long, global = true)]
+    quiet: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Report {
+    /// Draw a chart as the TUI shows it to a PNG or SVG image
+    Chart {
+        /// The image to write, an SVG if it ends in .svg and a PNG otherwise
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// The kind of chart
+        #[arg(short, long, value_enum, default_value_t)]
+        kind: ChartKind,
+
+        /// What the chart measures
+        #[arg(short, long, value_enum, default_value_t)]
+        metric: Metric,
+
+        /// The size of the image in pixels
+        #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "1200x800", value_parser = parse_size)]
+        size: (u32, u32),
+    },
+    /// Print a month's income and expenses against the month before, the total of each type
+    /// and the largest expenses
+    Month {
+        /// The month, as YYYY-MM, this-month or last-month
+        #[arg(value_name = "YYYY-MM", default_value = "this-month")]
+        month: String,
+    },
+    /// Print the income and expenses of a week against the week before, the total of each type
+    /// and the largest expenses, e.g. to run from cron and mail elsewhere
+    Week {
+        /// The week, as last-week, this-week or a day in it
+        #[arg(value_name = "WEEK", default_value = "last-week")]
+        week: String,
+    },
+    /// Compare the total of each type and each month between years, e.g. --year 2023 --year
+    /// 2024, with the change from the first year to the last
+    Compare {
+        /// A year to compare, given twice or more
+        #[arg(long = "year", value_name = "YEAR", required = true)]
+        years: Vec<i32>,
+    },
+    /// Print what was spent on each type in each of the last months, with a sparkline of it and
+    /// whether it is rising or falling
+    Trend {
+        /// What to add the spending up by, months or ISO weeks like 2024-W23
+        #[arg(long, value_enum, default_value_t)]
+        by: By,
+
+        /// How many months, up to this one
+        #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..=120))]
+        months: u32,
+
+        /// How many ISO weeks up to this one, with --by iso-week
+        #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..=520))]
+        weeks: u32,
+    },
+    /// Print the largest expenses, and the descriptions and types the most was spent on
+    Top {
+        /// How many to list
+        #[arg(short, long, default_value_t = 10)]
+        n: usize,
+
+        /// The days to look at, e.g. this-month, last-year, 2024-06 or all
+        #[arg(short, long, default_value = "this-month")]
+        period: String,
+    },
+    /// Print what was spent on each payee, read from the descriptions, with how many times and
+    /// how much on average
+    Payees {
+        /// How many to list
+        #[arg(short, long, default_value_t = 20)]
+        n: usize,
+    },
+    /// Print the totals of each tag, an entry with several tags counting towards each
+    Tags,
+    /// Print the income and the expenses of each of the last months side by side, with what
+    /// was saved of the income
+    Cashflow {
+        /// How many months, up to this one
+        #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..=120))]
+        months: u32,
+    },
+    /// Print what each account of the config holds from its opening balance, and the net worth
+    /// at the end of each of the last months
+    Networth {
+        /// How many months, up to this one
+        #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..=120))]
+        months: u32,
+    },
+    /// Print what was spent on each budgeted type in a month against its budget, with what is
+    /// left, the part used and the days left
+    Budget {
+        /// The month, as YYYY-MM, this-month or last-month
+        #[arg(long, value_name = "YYYY-MM", default_value = "this-month")]
+        month: String,
+    },
+    /// Print the entries whose amount is far from what their type usually comes to, likely
+    /// mistakes like a missing decimal point
+    Unusual {
+        /// The days to look at, e.g. this-month, last-year, 2024-06 or all
+        #[arg(short, long, default_value = "all")]
+        period: String,
+    },
+}
+
+/// Parses the size of an image given as WIDTHxHEIGHT.
+fn parse_size(size: &str) -> Result<(u32, u32), String> {
+    size.split_once('x')
+        .and_then(|(width, height)| Some((width.trim().parse().ok()?, height.trim().parse().ok()?)))
+        .filter(|(width, height)| *width > 0 && *height > 0)
+        .ok_or_else(|| format!("'{}' isn't a size like 1200x800", size))
+}
+
+/// Parses the month of a report, given as YYYY-MM or as a range like `last-month`.
+fn parse_month(month: &str) -> Result<DateRange, Box<dyn std::error::Error>> {
+    let start = parse_range(month, Local::now().date_naive())
+        .ok_or_else(|| format!("'{}' isn't a month like 2024-06", month))?
+        .start;
+    Ok(DateRange::month_of(start).ok_or("The month is out of range")?)
+}
+
+/// Parses the days a report looks at, given as a range like `last-month` or `2024`, or `all`
+/// for every day.
+fn parse_period(period: &str) -> Result<Option<DateRange>, Box<dyn std::error::Error>> {
+    if period.trim().eq_ignore_ascii_case("all") {
+        return Ok(None);
+    }
+    let range = parse_range(period, Local::now().date_naive())
+        .ok_or_else(|| format!("'{}' isn't a period like this-month or 2024-06", period))?;
+    Ok(Some(range))
+}
+
+/// The expenses of the database the `filters` keep.
+fn read_filtered(filters: &[Filter]) -> Result<Vec<Expense>, Box<dyn std::error::Error>> {
+    let expenses = Expense::read_csv("expenses.csv")?;
+    let index = SearchIndex::load_or_build("expenses.csv", &expenses)?;
+    let mut rows: Vec<usize> = (0..expenses.len()).collect();
+    for filter in filters {
+        rows = filter.apply(&expenses, &rows, &index);
+    }
+    Ok(rows.into_iter().map(|row| expenses[row].clone()).collect())
+}
+
+/// Prints the statistics of the expenses the `filters` keep in the `period`, written out as the
+/// `output` says. Every day from the first entry to today is looked at for `all`.
+fn run_stats(
+    config: &Config,
+    period: &str,
+    output: &Output,
+    filters: &[Filter],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let expenses = read_filtered(filters)?;
+    let shown: Vec<&Expense> = expenses.iter().collect();
+    let today = Local::now().date_naive();
+    // Days still to come aren't counted
+    let range = match parse_period(period)? {
+        Some(range) => DateRange::new(range.start, range.end.min(today).max(range.start)),
+        None => {
+            let first = shown
+                .iter()
+                .filter_map(|expense| parse_iso(&expense.date))
+                .min()
+                .unwrap_or(today);
+            DateRange::new(first.min(today), today)
+        }
+    };
+    let document = report::stats::report(config, &Stats::new(&shown, range), &shown);
+    print_report(config, &document, &Data::new(&shown, Some(range)), output)
+}
+
+/// Asks which of each group of likely duplicates in the database to keep, and moves the others
+/// to the trash.
+fn check_duplicates(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let expenses = Expense::read_csv("expenses.csv")?;
+    let groups = duplicate::groups(&expenses);
+    if groups.is_empty() {
+        println!("No likely duplicates found.");
+        return Ok(());
+    }
+    let _lock = DatabaseLock::acquire("expenses.csv")?
+        .ok_or("The database is open in the TUI, close it to remove duplicates")?;
+
+    println!("Found {} groups of likely duplicates.", groups.len());
+    let mut removed: Vec<(usize, &Expense)> = Vec::new();
+    for (number, group) in groups.iter().enumerate() {
+        println!("\n{} of {}:", number + 1, groups.len());
+        for (choice, &row) in group.iter().enumerate() {
+            let expense = &expenses[row];
+            println!(
+                "  {}  {}  {}  {}  {}",
+                choice + 1,
+                config.format_date(&expense.date),
+                expense.description,
+                expense.expense_type,
+                config.money(expense.amount)
+            );
+        }
+        let keep = loop {
+            print!(
+                "Keep only one of them (1-{}), all of them (a) or stop here (q)? ",
+                group.len()
+            );
+            io::stdout().flush()?;
+            let mut input = String::new();
+            // The end of the input stops like q
+            if io::stdin().read_line(&mut input)? == 0 {
+                break None;
+            }
+            match input.trim() {
+                "a" | "A" | "" => break Some(None),
+                "q" | "Q" => break None,
+                choice => match choice.parse::<usize>() {
+                    Ok(choice) if (1..=group.len()).contains(&choice) => {
+                        break Some(Some(choice - 1))
+                    }
+                    _ => println!("Type a number from 1 to {}, a or q.", group.len()),
+                },
+            }
+        };
+        match keep {
+            Some(Some(kept)) => removed.extend(
+                group
+                    .iter()
+                    .enumerate()
+                    .filter(|&(choice, _)| choice != kept)
+                    .map(|(_, &row)| (row, &expenses[row])),
+            ),
+            Some(None) => {}
+            None => break,
+        }
+    }
+
+    if removed.is_empty() {
+        println!("Nothing was removed.");
+        return Ok(());
+    }
+    Expense::delete_from_csv("expenses.csv", &removed)?;
+    println!(
+        "Moved {} {} to expenses.csv.trash.",
+        removed.len(),
+        if removed.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        }
+    );
+    Ok(())
+}
+
+/// The `document` of a report written out through the template, given the numbers of its
+/// `data` too, or in the format of the `output`.
+fn render_report(
+    config: &Config,
+    document: &Document,
+    data: &Data,
+    output: &Output,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    match &output.template {
+        Some(name) => Ok(report::template::render(config, document, data, name)?.into_bytes()),
+        None => output.format.write(document),
+    }
+}
+
+/// Posts a `report` with the `title` written out as the `output` says to the URL it gives, if
+/// any. Whether there was one.
+fn post_report(
+    title: &str,
+    report: &[u8],
+    output: &Output,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let Some(url) = &output.post_url else {
+        return Ok(false);
+    };
+    let content_type = match output.template {
+        Some(_) => "text/plain; charset=utf-8",
+        None => output.format.content_type(),
+    };
+    report::post(url, title, content_type, report)?;
+    if !output.quiet {
+        println!("Posted the report to {}", url);
+    }
+    Ok(true)
+}
+
+/// Prints the `document` of a report written out as the `output` says, or posts it when the
+/// `output` gives a URL. A PDF is never printed to the terminal, which its bytes would only
+/// fill.
+fn print_report(
+    config: &Config,
+    document: &Document,
+    data: &Data,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = render_report(config, document, data, output)?;
+    if post_report(&document.title, &report, output)? {
+        return Ok(());
+    }
+    if output.template.is_none() && output.format == Format::Pdf && io::stdout().is_terminal() {
+        return Err(
+            "A PDF can't be shown in the terminal, write it to a file, e.g. > report.pdf".into(),
+        );
+    }
+    io::stdout().write_all(&report)?;
+    Ok(())
+}
+
+/// Writes the report of the week, month or year `period` of the expenses the `filters` keep,
+/// against the one before, as the `output` says. It is made to run unattended, e.g. from cron,
+/// so it fails on any problem in the database rather than leave entries out, and writes to the
+/// `destination` file or to a directory under a name made of the period, and posts it too when
+/// the `output` gives a URL.
+fn run_periodic(
+    config: &Config,
+    period: &str,
+    destination: Option<&str>,
+    output: &Output,
+    filters: &[Filter],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let problems = Expense::problems("expenses.csv")?;
+    if !problems.is_empty() {
+        return Err(format!(
+            "The database has problems, fix them with --edit. {}",
+            problems.join(" ")
+        )
+        .into());
+    }
+    let expenses = read_filtered(filters)?;
+    let shown: Vec<&Expense> = expenses.iter().collect();
+    let today = Local::now().date_naive();
+    let range = parse_range(period, today)
+        .ok_or_else(|| format!("'{}' isn't a period like last-month or 2024", period))?;
+    let (mut document, name) = if range == DateRange::week(range.start) {
+        (
+            report::period::week(config, &shown, range, today),
+            format!("week-{}", range.start),
+        )
+    } else if DateRange::month_of(range.start) == Some(range) {
+        (
+            report::period::month(config, &shown, range, today),
+            format!("month-{}", range.start.format("%Y-%m")),
+        )
+    } else if DateRange::year(range.start.year()) == Some(range) {
+        (
+            report::period::year(config, &shown, range.start.year(), today),
+            format!("year-{}", range.start.year()),
+        )
+    } else {
+        return Err(
+            "--period takes a week, a month or a year, like last-week, last-month or 2024".into(),
+        );
+    };
+    if output.format == Format::Html && name.starts_with("month") {
+        report::period::charts(&mut document, config, &shown, range)?;
+    }
+
+    let data = Data::new(&shown, Some(range));
+    let Some(destination) = destination else {
+        return print_report(config, &document, &data, output);
+    };
+    let report = render_report(config, &document, &data, output)?;
+    let mut path = expand_home(destination);
+    if path.is_dir() || destination.ends_with(std::path::MAIN_SEPARATOR) {
+        let extension = match output.template {
+            Some(_) => "txt",
+            None => output.format.extension(),
+        };
+        path = path.join(format!("{}.{}", name, extension));
+    }
+    if let Some(directory) = path
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+    {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(&path, &report)?;
+    if !output.quiet {
+        println!("Wrote the report to {}", path.display());
+    }
+    post_report(&document.title, &report, output)?;
+    Ok(())
 }
 
-fn get_expenses_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let home_dir = dirs::home_dir().ok_or("Unable to determine user's home directory")?;
-    Ok(home_dir.join(".local").join("share").join("budget-tracker"))
+/// The days from the first of the `count` months or weeks `by` up to the one of `today` to the
+/// last.
+fn span(by: By, count: u32, today: NaiveDate) -> Option<DateRange> {
+    let buckets = report::trend::buckets(by, count, today);
+    let (first, last) = (buckets.first()?.0, buckets.last()?.0);
+    Some(DateRange::new(first.start, last.end))
+}
+
+/// Writes the `report` of the expenses the `filters` keep as the `output` says.
+fn run_report(
+    config: &Config,
+    report: Report,
+    output: &Output,
+    filters: &[Filter],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format = output.format;
+    let expenses = read_filtered(filters)?;
+    let shown: Vec<&Expense> = expenses.iter().collect();
+    let today = Local::now().date_naive();
+    // The report and the days it covers, for the numbers given to a template
+    let (document, range) = match report {
+        Report::Chart {
+            output: path,
+            kind,
+            metric,
+            size,
+        } => {
+            if format != Format::Table || output.template.is_some() {
+                return Err(
+                    "A chart is an image, so --format and --template don't apply to it".into(),
+                );
+            }
+            report::export_chart(config, &shown, (kind, metric), &path, size)?;
+            println!("Wrote the chart to {}", path.display());
+            return Ok(());
+        }
+        Report::Month { month } => {
+            let month = parse_month(&month)?;
+            let mut document = report::period::month(config, &shown, month, today);
+            if format == Format::Html {
+                report::period::charts(&mut document, config, &shown, month)?;
+            }
+            (document, Some(month))
+        }
+        Report::Week { week } => {
+            let week = parse_range(&week, today)
+                .map(|range| DateRange::week(range.start))
+                .ok_or_else(|| format!("'{}' isn't a week like last-week or 2024-06-10", week))?;
+            (
+                report::period::week(config, &shown, week, today),
+                Some(week),
+            )
+        }
+        Report::Compare { years } => {
+            if years.len() < 2 {
+                return Err(
+                    "Give two years or more to compare, e.g. --year 2023 --year 2024".into(),
+                );
+            }
+            let comparison = Comparison::new(&shown, &years);
+            // The numbers alone, which no document holds
+            let numbers = match format {
+                Format::Json => format!("{}\n", serde_json::to_string_pretty(&comparison)?),
+                Format::Csv => comparison.to_csv(),
+                _ => String::new(),
+            };
+            if numbers.is_empty() {
+                let first = years.iter().min().copied().and_then(DateRange::year);
+                let last = years.iter().max().copied().and_then(DateRange::year);
+                let range = first
+                    .zip(last)
+                    .map(|(first, last)| DateRange::new(first.start, last.end));
+                (comparison.to_document(config), range)
+            } else {
+                let title = format!(
+                    "Comparison of {}",
+                    years
+                        .iter()
+                        .map(i32::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                if !post_report(&title, numbers.as_bytes(), output)? {
+                    print!("{}", numbers);
+                }
+                return Ok(());
+            }
+        }
+        Report::Trend { by, months, weeks } => {
+            let count = match by {
+                By::Month => months,
+                By::IsoWeek => weeks,
+            };
+            (
+                report::trend::report(config, &shown, (by, count), today),
+                span(by, count, today),
+            )
+        }
+        Report::Top { n, period } => {
+            let period = parse_period(&period)?;
+            (report::top::report(config, &shown, n, period), period)
+        }
+        Report::Payees { n } => (report::payees::report(config, &shown, n), None),
+        Report::Tags => (report::tags::report(config, &shown), None),
+        Report::Cashflow { months } => (
+            report::cashflow::report(config, &shown, months, today),
+            span(By::Month, months, today),
+        ),
+        Report::Networth { months } => (
+            report::networth::report(config, &shown, months, today),
+            span(By::Month, months, today),
+        ),
+        Report::Budget { month } => {
+            let month = parse_month(&month)?;
+            (
+                report::budget::report(config, &expenses, month, today),
+                Some(month),
+            )
+        }
+        Report::Unusual { period } => {
+            let period = parse_period(&period)?;
+            (report::unusual::report(config, &shown, period), period)
+        }
+    };
+    print_report(config, &document, &Data::new(&shown, range), output)
+}
+
+/// Saves the answers to the first start's questions to the config, and creates the database
+/// where it was asked for with the starting balance as its first entry.
+fn apply_setup(config: &mut Config, setup: Setup) -> Result<(), Box<dyn std::error::Error>> {
+    config.currency = setup.currency;
+    config.categories = setup.categories;
+    if setup.data_dir != default_data_dir()? {
+        set_data_dir(setup.data_dir.clone());
+        config.data_dir = Some(setup.data_dir);
+    }
+    config.save()?;
+
+    if !Expense::get_database_file_path("expenses.csv")?.exists() {
+        Expense::create_expenses_csv()?;
+    }
+    if setup.balance != 0.0 {
+        let balance = Expense::new(
+            Local::now().format("%Y-%m-%d").to_string(),
+            "Starting balance".to_string(),
+            "Income".to_string(),
+            setup.balance,
+        );
+        Expense::append_to_csv("expenses.csv", &balance)?;
+    }
+    info!("Finished the first start setup");
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Nothing written yet means this is the first start, which asks a few questions
+    let first_run = !default_data_dir()?.exists() && !Config::get_config_file_path()?.exists();
+    fs::create_dir_all(default_data_dir()?)?;
     fern::Dispatch::new()
         .format(|out, message, record| {
             out.finish(format_args!(
@@ -53,13 +690,28 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 message
             ))
         })
-        .chain(fern::log_file(get_expenses_dir()?.join("expenses.log"))?)
+        .chain(fern::log_file(default_data_dir()?.join("expenses.log"))?)
         .apply()?;
     info!("====Starting program====");
     let args = Args::parse();
 
+    let mut config = Config::load()?;
+    if let Some(dir) = &config.data_dir {
+        set_data_dir(expand_home(&dir.to_string_lossy()));
+    }
+    if let Some(day) = config.month_start {
+        set_month_start(day);
+    }
+    if let Some(weekday) = config.week_start.as_deref() {
+        set_week_start(
+            weekday
+                .parse()
+                .map_err(|_| format!("'{}' in week-start isn't a day of the week", weekday))?,
+        );
+    }
+
     if args.add {
-        Expense::add_expense()?;
+        Expense::add_expense(&config)?;
         trace!("Added the expense succesfully");
     }
 
@@ -72,21 +724,109 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         trace!("Opening the log file ...");
         Command::new("tail")
             .arg("-f")
-            .arg(get_expenses_dir()?.join("expenses.log").to_str().unwrap())
+            .arg(default_data_dir()?.join("expenses.log").to_str().unwrap())
             .status()?;
         trace!("Closed log file view succesfully");
         return invoke_gracefull_exit();
     }
 
+    let query = args.query.as_deref().map(Query::parse).transpose()?;
+    let filter = args
+        .filter
+        .as_deref()
+        .map(|name| config.filter(name).and_then(Query::parse))
+        .transpose()?;
+
+    let regex_search = args
+        .search_regex
+        .as_deref()
+        .map(RegexSearch::new)
+        .transpose()?;
+    let search = args.search.as_deref().map(Search::parse);
+
+    if let (Some(name), Some(query)) = (&args.save_filter, &args.query) {
+        config.filters.insert(name.clone(), query.clone());
+        config.save()?;
+        println!("Saved filter '{}'", name);
+        trace!("Saved filter '{}' as: {}", name, query);
+    }
+
+    if let Some(setting) = &args.set {
+        let (key, value) = setting
+            .split_once('=')
+            .ok_or("Settings are given as KEY=VALUE")?;
+        config.set(key.trim(), value.trim())?;
+        config.save()?;
+        println!("Set {} to {}", key.trim(), value.trim());
+    }
+
+    let mut filters: Vec<Filter> = Vec::new();
+
+    if let Some(search) = search {
+        trace!("Found user search: {:?}", search);
+        filters.push(Filter::Text(search));
+    }
+
+    if let Some(regex_search) = regex_search {
+        trace!("Found user regex search: {}", regex_search.regex);
+        filters.push(Filter::Regex(regex_search));
+    }
+
+    for query in query.into_iter().chain(filter) {
+        trace!("Found user query: {:?}", query);
+        filters.push(Filter::Query(query));
+    }
+
+    match args.command {
+        Some(Commands::Report {
+            report,
+            period,
+            destination,
+            output,
+        }) => {
+            return match (report, period) {
+                (Some(report), None) => run_report(&config, report, &output, &filters),
+                (None, Some(period)) => run_periodic(
+                    &config,
+                    &period,
+                    destination.as_deref(),
+                    &output,
+                    &filters,
+                ),
+                (Some(_), Some(_)) => {
+                    Err("--period writes the report of a period, give it without a report".into())
+                }
+                (None, None) => {
+                    Err("Give a report, e.g. report month, or --period for the report of a period, e.g. report --period last-month".into())
+                }
+            }
+        }
+        Some(Commands::Stats { period, output }) => {
+            return run_stats(&config, &period, &output, &filters)
+        }
+        // Duplicates are the only check so far, so it runs whether picked or not
+        Some(Commands::Doctor { duplicates: _ }) => return check_duplicates(&config),
+        None => {}
+    }
+
     trace!("Starting the TUI ...");
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     stdout.execute(EnterAlternateScreen)?;
+    stdout.execute(EnableMouseCapture)?;
+    stdout.execute(EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
+    if first_run {
+        trace!("First start, asking for the setup ...");
+        if let Some(setup) = tui::setup::run(&mut terminal, default_data_dir()?)? {
+            apply_setup(&mut config, setup)?;
+        }
+    }
+
     trace!("Reading expenses.csv ...");
-    let mut expenses = match Expense::read_csv("expenses.csv") {
+    let expenses = match Expense::read_csv("expenses.csv") {
         Ok(expenses) => expenses,
         Err(err) => {
             error!("Error reading CSV, trying to create it: {}", err);
@@ -99,250 +839,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
     };
+    let index = SearchIndex::load_or_build("expenses.csv", &expenses)?;
 
-    if let Some(query) = &args.search {
-        trace!("Found user query: {}", query);
-        let matcher = SkimMatcherV2::default();
-        expenses = expenses
-            .iter()
-            .filter(|expense| {
-                matcher.fuzzy_match(&expense.description, query).is_some()
-                    || matcher
-                        .fuzzy_match(&expense.expense_type.to_string(), query)
-                        .is_some()
-            })
-            .cloned()
-            .collect();
-    }
-
-    // Sort expenses by date in descending order
-    expenses.sort_by(|a, b| b.date.cmp(&a.date));
+    // Another TUI open on the database keeps this one from writing to it, rather than the two
+    // writing over each other's changes
+    let lock = DatabaseLock::acquire("expenses.csv")?;
+    let mut app = App::new(config, expenses, index, filters);
+    app.read_only = lock.is_none();
+    tui::run(&mut terminal, &mut app)?;
 
-    let mut should_quit = false;
-    let mut table_state = TableState::default().with_selected(Some(0));
-    let table_size = expenses.len();
-    while !should_quit {
-        terminal.draw(|f| ui(f, &expenses, &mut table_state))?;
-        should_quit = handle_events(&mut table_state, table_size)?;
-    }
-    
     invoke_gracefull_exit()
 }
 
-fn invoke_gracefull_exit() -> Result<(), Box<dyn std::error::Error>>{
+fn invoke_gracefull_exit() -> Result<(), Box<dyn std::error::Error>> {
     disable_raw_mode()?;
     let mut stdout = io::stdout();
+    stdout.execute(DisableBracketedPaste)?;
+    stdout.execute(DisableMouseCapture)?;
     stdout.execute(LeaveAlternateScreen)?;
     info!("====Exiting the program====");
-    
-    Ok(())
-}
 
-fn handle_events(table_state: &mut TableState, table_size: usize) -> io::Result<bool> {
-    if event::poll(std::time::Duration::from_millis(50))? {
-        if let Event::Key(KeyEvent {
-            kind: KeyEventKind::Press,
-            code,
-            ..
-        }) = event::read()?
-        {
-            debug!("Read in key: {:?}", code);
-            match code {
-                KeyCode::Char('q') => return Ok(true),
-                KeyCode::Down | KeyCode::Char('s') => {
-                    if let Some(selected) = table_state.selected() {
-                        let next_index = if selected >= table_size - 1 {
-                            0
-                        } else {
-                            selected + 1
-                        };
-                        table_state.select(Some(next_index));
-                    }
-                }
-                KeyCode::Up | KeyCode::Char('w') => {
-                    if let Some(selected) = table_state.selected() {
-                        let next_index = if selected == 0 {
-                            table_size - 1
-                        } else {
-                            selected - 1
-                        };
-                        table_state.select(Some(next_index));
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
-    Ok(false)
-}
-
-fn ui(frame: &mut Frame, expenses: &[Expense], table_state: &mut TableState) {
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .margin(2)
-        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
-        .split(frame.size());
-
-    // Split the second chunk (chunks[1]) vertically into two equal parts
-    let charts_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(chunks[1]);
-
-    let positive_chunk = charts_chunks[0];
-    let negative_chunk = charts_chunks[1];
-
-    // Calculate the total sum of amounts
-    let total_amount: f64 = expenses.iter().map(|expense| expense.amount).sum();
-    let total_spent: f64 = expenses
-        .iter()
-        .filter(|expense| expense.amount < 0.0)
-        .map(|expense| expense.amount)
-        .sum();
-    let total_earned: f64 = expenses
-        .iter()
-        .filter(|expense| expense.amount >= 0.0)
-        .map(|expense| expense.amount)
-        .sum();
-
-    // Expense Table
-    let rows = expenses
-        .iter()
-        .map(|expense| {
-            Row::new(vec![
-                expense.date.clone(),
-                expense.description.clone(),
-                capitalize(expense.expense_type.to_string()),
-                expense.amount.to_string(),
-            ])
-        })
-        .collect::<Vec<Row>>();
-
-    let widths = [
-        Constraint::Length(15),
-        Constraint::Length(65),
-        Constraint::Length(20),
-        Constraint::Length(10),
-    ];
-
-    let expense_table = Table::new(rows, widths)
-        .block(Block::default().borders(Borders::ALL))
-        .header(
-            Row::new(vec!["Date", "Description", "Type", "Amount"]).style(Style::default().bold()),
-        )
-        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
-        .highlight_symbol(">>");
-
-    let table_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
-        .split(chunks[0]);
-
-    // frame.render_widget(expense_table, chunks[0]);
-    frame.render_stateful_widget(expense_table, table_chunks[0], table_state);
-
-    let rows = vec![
-        Row::new(vec![
-            "".to_string(),
-            "".to_string(),
-            "Net Total Spent".to_string(),
-            total_amount.to_string(),
-        ])
-        .style(Style::default().bold())
-        .top_margin(1),
-        Row::new(vec![
-            "".to_string(),
-            "".to_string(),
-            "Total Spent".to_string(),
-            total_spent.to_string(),
-        ])
-        .style(Style::default().bold()),
-        Row::new(vec![
-            "".to_string(),
-            "".to_string(),
-            "Total Earned".to_string(),
-            total_earned.to_string(),
-        ])
-        .style(Style::default().bold()),
-    ];
-
-    let data_table = Table::new(rows, widths);
-
-    frame.render_widget(data_table, table_chunks[1]);
-
-    // Aggregate expenses by date
-    let mut aggregated_expenses: HashMap<String, f64> = HashMap::new();
-    for expense in expenses {
-        let entry = aggregated_expenses
-            .entry(expense.expense_type.to_string())
-            .or_insert(0.0);
-        *entry += expense.amount;
-    }
-
-    // Separate positive and negative expenses
-    let total_earned_data: Vec<(String, f64)> = aggregated_expenses
-        .clone()
-        .into_iter()
-        .filter(|(_, amount)| *amount >= 0.0)
-        .collect();
-
-    let total_spent_data: Vec<(String, f64)> = aggregated_expenses
-        .clone()
-        .into_iter()
-        .filter(|(_, amount)| *amount < 0.0)
-        .map(|(expense_type, amount)| (capitalize(expense_type), -amount))
-        .collect();
-
-    for (mut expense_data, chunk, title, color) in [
-        (
-            total_spent_data.clone(),
-            positive_chunk,
-            "Expenditure",
-            Style::default().cyan(),
-        ),
-        (
-            total_earned_data,
-            negative_chunk,
-            "Income",
-            Style::default().red(),
-        ),
-    ] {
-        expense_data.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-
-        // Find the maximum expense amount
-        let max_expense_amount = expense_data
-            .iter()
-            .map(|(_, amount)| *amount)
-            .fold(f64::NEG_INFINITY, f64::max);
-
-        // Convert type expenses to bar chart data
-        let type_data: Vec<(&str, u64)> = expense_data
-            .iter()
-            .map(|(date, amount)| (date.as_str(), *amount as u64))
-            .collect();
-
-        // Calculate dynamic bar width
-        let available_width = chunk.width as usize;
-        let num_types = expense_data.len() + 5;
-        let min_bar_width = 1;
-
-        let bar_width = if num_types > 0 {
-            (available_width / num_types).max(min_bar_width) as u16
-        } else {
-            min_bar_width as u16
-        };
-
-        let type_barchart = BarChart::default()
-            .block(Block::default().title(title).borders(Borders::ALL))
-            .bar_width(bar_width)
-            // .bar_gap(1)
-            // .group_gap(3)
-            .bar_style(color)
-            .value_style(Style::default().white().bold())
-            .label_style(Style::default().white())
-            .data(&type_data)
-            .max(max_expense_amount.ceil() as u64);
-
-        frame.render_widget(type_barchart, chunk); // Render the type barchart
-    }
+    Ok(())
 }
@@ -0,0 +1,59 @@
+//! Who was paid, read from the descriptions of the expenses as there is no field of its own for
+//! it. Descriptions that differ only in case, spacing or a trailing reference, like
+//! `AMAZON Mktplace 1234` and `Amazon mktplace #5678`, are the same payee.
+
+use std::collections::HashMap;
+
+use crate::expense::Expense;
+
+/// What was spent on a payee.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payee {
+    /// The description as first written, without its reference
+    pub name: String,
+    pub spent: f64,
+    pub count: usize,
+}
+
+impl Payee {
+    /// What was spent on an entry on average.
+    pub fn average(&self) -> f64 {
+        self.spent / self.count.max(1) as f64
+    }
+}
+
+/// The words of a description naming the payee, leaving out the references with digits at
+/// its end.
+pub fn name(description: &str) -> String {
+    let mut words: Vec<&str> = description.split_whitespace().collect();
+    while words.len() > 1
+        && words
+            .last()
+            .is_some_and(|word| word.chars().any(|c| c.is_ascii_digit()))
+    {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// What was spent on each payee of the `expenses`, the most first. Income isn't counted.
+pub fn payees(expenses: &[&Expense]) -> Vec<Payee> {
+    let mut payees: HashMap<String, Payee> = HashMap::new();
+    for expense in expenses.iter().filter(|expense| expense.amount < 0.0) {
+        let name = name(&expense.description);
+        let payee = payees.entry(name.to_lowercase()).or_insert(Payee {
+            name,
+            spent: 0.0,
+            count: 0,
+        });
+        payee.spent -= expense.amount;
+        payee.count += 1;
+    }
+    let mut payees: Vec<Payee> = payees.into_values().collect();
+    payees.sort_by(|a, b| {
+        b.spent
+            .total_cmp(&a.spent)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    payees
+}
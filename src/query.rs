@@ -0,0 +1,381 @@
+//! A small query language for filtering [Expense]s.
+//!
+//! A query combines terms with `AND`, `OR` and `NOT`, grouped with parentheses. Terms next to
+//! each other without an operator are joined with `AND`, which binds stronger than `OR`:
+//!
+//! ```text
+//! type:food AND amount<-100 AND date:2024-06 AND desc:"bus"
+//! (type:Food OR type:Fun) AND NOT tag:reimbursed AND amount<-20
+//! ```
+//!
+//! - `type:<name>` matches the expense type, ignoring case.
+//! - `desc:<text>` matches a substring of the description, ignoring case.
+//! - `tag:<name>` matches expenses with the tag, ignoring case.
+//! - `date:<when>` matches dates within a year, month or day like `2024`, `2024-06` or
+//!   `2024-06-01`, or a relative period: `today`, `yesterday`, `this-week`, `last-week`,
+//!   `this-month`, `last-month`, `this-year`, `last-year` or `<n> <unit>s ago`.
+//! - `since:<when>` matches dates on or after a day, e.g. `since:3 weeks ago` or `since:2024-06-01`.
+//! - `amount<op><number>` compares the amount, where `<op>` is one of `<`, `<=`, `>`, `>=` or `=`.
+//! - A bare word matches a substring of either the description or the type.
+//!
+//! Values containing spaces can be wrapped in double quotes. Alternatives for a `type`, `desc`,
+//! `tag` or bare word term can be separated with `|`, e.g. `desc:bus|train`.
+
+use chrono::{Local, NaiveDate};
+use std::ops::Range;
+
+use crate::date::{self, DateRange};
+use crate::expense::Expense;
+use crate::search::{Field, Highlight};
+
+/// Comparison operator used by `amount` terms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn compare(&self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+/// A single condition of a [Query].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Type(String),
+    Description(String),
+    Tag(String),
+    Date(DateRange),
+    Since(NaiveDate),
+    Amount(Comparison, f64),
+    Text(String),
+    /// Matches if any of the terms matches, written as `desc:bus|train`.
+    Either(Vec<Term>),
+}
+
+impl Term {
+    fn matches(&self, expense: &Expense) -> bool {
+        match self {
+            Term::Type(expense_type) => expense.expense_type.to_lowercase() == *expense_type,
+            Term::Description(text) => expense.description.to_lowercase().contains(text),
+            Term::Tag(tag) => expense.tags.iter().any(|t| t.to_lowercase() == *tag),
+            Term::Date(range) => date::parse_iso(&expense.date).is_some_and(|d| range.contains(d)),
+            Term::Since(since) => date::parse_iso(&expense.date).is_some_and(|d| d >= *since),
+            Term::Amount(comparison, amount) => comparison.compare(expense.amount, *amount),
+            Term::Text(text) => {
+                expense.description.to_lowercase().contains(text)
+                    || expense.expense_type.to_lowercase().contains(text)
+            }
+            Term::Either(terms) => terms.iter().any(|term| term.matches(expense)),
+        }
+    }
+}
+
+impl Highlight for Term {
+    fn highlights(&self, field: Field, text: &str) -> Vec<Range<usize>> {
+        let lowercase = text.to_lowercase();
+        match (self, field) {
+            (Term::Type(expense_type), Field::Type) if lowercase == *expense_type => {
+                std::iter::once(0..text.len()).collect()
+            }
+            (Term::Description(needle), Field::Description) | (Term::Text(needle), _) => lowercase
+                .match_indices(needle.as_str())
+                .map(|(index, _)| index..index + needle.len())
+                .collect(),
+            (Term::Either(terms), _) => terms
+                .iter()
+                .flat_map(|term| term.highlights(field, text))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// A boolean combination of [Term]s.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Term(Term),
+    /// Matches if every expression matches; an empty list matches everything.
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Default for Expr {
+    fn default() -> Self {
+        Expr::And(Vec::new())
+    }
+}
+
+impl Expr {
+    pub fn matches(&self, expense: &Expense) -> bool {
+        match self {
+            Expr::Term(term) => term.matches(expense),
+            Expr::And(exprs) => exprs.iter().all(|expr| expr.matches(expense)),
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr.matches(expense)),
+            Expr::Not(expr) => !expr.matches(expense),
+        }
+    }
+}
+
+impl Highlight for Expr {
+    fn highlights(&self, field: Field, text: &str) -> Vec<Range<usize>> {
+        match self {
+            Expr::Term(term) => term.highlights(field, text),
+            Expr::And(exprs) | Expr::Or(exprs) => exprs
+                .iter()
+                .flat_map(|expr| expr.highlights(field, text))
+                .collect(),
+            // Text which must not match is never shown
+            Expr::Not(_) => Vec::new(),
+        }
+    }
+}
+
+/// A parsed query.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub expr: Expr,
+}
+
+impl Query {
+    /// Parses a query string, see the [module documentation](self) for the syntax. Relative
+    /// dates are resolved against today.
+    pub fn parse(input: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::parse_relative_to(input, Local::now().date_naive())
+    }
+
+    pub fn parse_relative_to(
+        input: &str,
+        today: NaiveDate,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+            today,
+        };
+        // An empty query matches everything
+        if parser.tokens.is_empty() {
+            return Ok(Self {
+                expr: Expr::default(),
+            });
+        }
+        let expr = parser.parse_or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(Self { expr }),
+            Some(Token::Close) => Err("Unmatched ')' in query".into()),
+            Some(token) => Err(format!("Unexpected {:?} in query", token).into()),
+        }
+    }
+
+    pub fn matches(&self, expense: &Expense) -> bool {
+        self.expr.matches(expense)
+    }
+
+    /// Keeps only the expenses matching the query.
+    pub fn filter(&self, expenses: &[Expense]) -> Vec<Expense> {
+        expenses
+            .iter()
+            .filter(|expense| self.matches(expense))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Highlight for Query {
+    fn highlights(&self, field: Field, text: &str) -> Vec<Range<usize>> {
+        self.expr.highlights(field, text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+
+/// Splits the input on whitespace and parentheses, keeping quoted sections together and removing
+/// the quotes. Unquoted relative dates like `since:3 weeks ago` are joined into one word.
+fn tokenize(input: &str) -> Result<Vec<Token>, Box<dyn std::error::Error>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if (c.is_whitespace() || c == '(' || c == ')') && !in_quotes => {
+                if !current.is_empty() {
+                    words.push(Token::Word(std::mem::take(&mut current)));
+                }
+                match c {
+                    '(' => words.push(Token::Open),
+                    ')' => words.push(Token::Close),
+                    _ => {}
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err("Unterminated quote in query".into());
+    }
+    if !current.is_empty() {
+        words.push(Token::Word(current));
+    }
+
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut words = words.into_iter().peekable();
+    while let Some(token) = words.next() {
+        if let Token::Word(word) = &token {
+            if word.ends_with(|c: char| c.is_ascii_digit()) && word.contains(':') {
+                let rest: Vec<Token> = words.clone().take(2).collect();
+                if let [Token::Word(unit), Token::Word(ago)] = rest.as_slice() {
+                    if ago == "ago" {
+                        tokens.push(Token::Word(format!("{} {} ago", word, unit)));
+                        words.nth(1);
+                        continue;
+                    }
+                }
+            }
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the tokens, with `OR` binding weakest and `NOT` strongest.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    today: NaiveDate,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.position), Some(Token::Word(word)) if word == keyword)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, Box<dyn std::error::Error>> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.peek_keyword("OR") {
+            self.position += 1;
+            exprs.push(self.parse_and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, Box<dyn std::error::Error>> {
+        let mut exprs = Vec::new();
+        loop {
+            match self.tokens.get(self.position) {
+                None | Some(Token::Close) => break,
+                _ if self.peek_keyword("OR") => break,
+                _ if self.peek_keyword("AND") => self.position += 1,
+                _ => exprs.push(self.parse_not()?),
+            }
+        }
+        // Nothing between two `OR`s, after the last one or inside `()`
+        if exprs.is_empty() {
+            return Err("Missing term in query".into());
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, Box<dyn std::error::Error>> {
+        if self.peek_keyword("NOT") {
+            self.position += 1;
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+
+        match self.tokens.get(self.position).cloned() {
+            Some(Token::Open) => {
+                self.position += 1;
+                let expr = self.parse_or()?;
+                if self.tokens.get(self.position) != Some(&Token::Close) {
+                    return Err("Missing ')' in query".into());
+                }
+                self.position += 1;
+                Ok(expr)
+            }
+            Some(Token::Word(word)) => {
+                self.position += 1;
+                Ok(Expr::Term(parse_term(&word, self.today)?))
+            }
+            Some(Token::Close) | None => Err("Missing term in query".into()),
+        }
+    }
+}
+
+fn parse_term(token: &str, today: NaiveDate) -> Result<Term, Box<dyn std::error::Error>> {
+    if let Some(rest) = token.strip_prefix("amount") {
+        let (comparison, value) = if let Some(value) = rest.strip_prefix("<=") {
+            (Comparison::LessOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix(">=") {
+            (Comparison::GreaterOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix('<') {
+            (Comparison::Less, value)
+        } else if let Some(value) = rest.strip_prefix('>') {
+            (Comparison::Greater, value)
+        } else if let Some(value) = rest.strip_prefix('=').or(rest.strip_prefix(':')) {
+            (Comparison::Equal, value)
+        } else {
+            return Ok(Term::Text(token.to_lowercase()));
+        };
+        let amount = value
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid amount '{}' in query", value))?;
+        return Ok(Term::Amount(comparison, amount));
+    }
+
+    let (field, value) = match token.split_once(':') {
+        Some((field, value)) => (Some(field), value),
+        None => (None, token),
+    };
+    if value.contains('|') && matches!(field, None | Some("type") | Some("desc") | Some("tag")) {
+        let terms = value
+            .split('|')
+            .map(|alternative| match field {
+                Some(field) => parse_term(&format!("{}:{}", field, alternative), today),
+                None => parse_term(alternative, today),
+            })
+            .collect::<Result<_, _>>()?;
+        return Ok(Term::Either(terms));
+    }
+
+    match token.split_once(':') {
+        Some((field, "")) => Err(format!("Missing value for '{}' in query", field).into()),
+        Some(("type", value)) => Ok(Term::Type(value.to_lowercase())),
+        Some(("desc", value)) => Ok(Term::Description(value.to_lowercase())),
+        Some(("tag", value)) => Ok(Term::Tag(value.to_lowercase())),
+        Some(("date", value)) => date::parse_range(value, today)
+            .map(Term::Date)
+            .ok_or_else(|| format!("Invalid date '{}' in query", value).into()),
+        Some(("since", value)) => date::parse_date(value, today)
+            .map(Term::Since)
+            .ok_or_else(|| format!("Invalid date '{}' in query", value).into()),
+        Some((field, _)) => Err(format!("Unknown field '{}' in query", field).into()),
+        None => Ok(Term::Text(token.to_lowercase())),
+    }
+}
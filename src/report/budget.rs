@@ -0,0 +1,138 @@
+//! `report budget`: what was spent on each budgeted type in a month against its budget, with
+//! what is left of it and, while the month is going on, what can be spent a day to keep within.
+
+use chrono::NaiveDate;
+
+use budget_tracker::budget::{is_budgeted, progress, Level};
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::Expense;
+
+use super::document::{Bars, Document, Table};
+use super::share;
+
+/// A part of a budget in percent, e.g. `45%`, and nothing when none of it is available.
+fn used(spent: f64, budget: f64) -> String {
+    if budget <= 0.0 {
+        return String::new();
+    }
+    format!("{:.0}%", spent / budget * 100.0)
+}
+
+/// The report of the budgets in the config in `month`, with the days left in it after `today`.
+pub fn report(
+    config: &Config,
+    expenses: &[Expense],
+    month: DateRange,
+    today: NaiveDate,
+) -> Document {
+    let mut document = Document::new(format!("Budgets for {}", month.month_name()));
+    let budgets = progress(config, expenses, month.start);
+    if budgets.is_empty() {
+        document.text("No budgets are set up yet. Add them to the [budgets] section of the config, e.g. Food = 300, or with --set budgets.food=300.");
+        return document;
+    }
+    // Counting from tomorrow, as today's spending may not be over
+    let days_left = match today {
+        today if today < month.start => (month.end - month.start).num_days() + 1,
+        today if month.contains(today) => (month.end - today).num_days(),
+        _ => 0,
+    };
+
+    // All that was spent in the month, for each budget's share of it
+    let in_month: Vec<&Expense> = expenses
+        .iter()
+        .filter(|expense| parse_iso(&expense.date).is_some_and(|date| month.contains(date)))
+        .collect();
+    let month_spent: f64 = in_month
+        .iter()
+        .filter(|expense| expense.amount < 0.0)
+        .map(|expense| -expense.amount)
+        .sum();
+
+    // What the budgets that roll over carried in, only when there are any
+    let rolls_over = budgets.iter().any(|progress| progress.carried != 0.0);
+    let mut header = vec!["Type", "Budget"];
+    if rolls_over {
+        header.push("Carried");
+    }
+    header.extend(["Spent", "Left", "Used", "Share"]);
+    if days_left > 0 {
+        header.push("A day");
+    }
+    let mut table = Table::new(&header);
+    let total = [(
+        "Total".to_string(),
+        budgets.iter().map(|progress| progress.budget).sum(),
+        budgets.iter().map(|progress| progress.carried).sum(),
+        budgets.iter().map(|progress| progress.spent).sum(),
+    )];
+    for (name, budget, carried, spent) in budgets
+        .iter()
+        .map(|progress| {
+            (
+                progress.expense_type.clone(),
+                progress.budget,
+                progress.carried,
+                progress.spent,
+            )
+        })
+        .chain(total)
+    {
+        let left = budget + carried - spent;
+        let mut row = vec![name, config.money(budget)];
+        if rolls_over {
+            row.push(config.money(carried));
+        }
+        row.extend([
+            config.money(spent),
+            config.money(left),
+            used(spent, budget + carried),
+            share(spent, month_spent),
+        ]);
+        if days_left > 0 {
+            row.push(config.money((left / days_left as f64).max(0.0)));
+        }
+        table.push(row);
+    }
+    document.table(table);
+    let mut bars = Bars::default();
+    for progress in budgets.iter().filter(|progress| progress.budget > 0.0) {
+        bars.push(
+            progress.expense_type.clone(),
+            used(progress.spent, progress.available()),
+            progress.ratio(),
+        );
+    }
+    document.bars(bars);
+
+    let over: Vec<&str> = budgets
+        .iter()
+        .filter(|progress| progress.level() == Level::Over)
+        .map(|progress| progress.expense_type.as_str())
+        .collect();
+    let mut notes = vec![match days_left {
+        0 => format!("{} is over.", month.month_name()),
+        1 => "1 day is left in the month.".to_string(),
+        days => format!("{} days are left in the month.", days),
+    }];
+    if !over.is_empty() {
+        notes.push(format!("Overspent on {}.", over.join(", ")));
+    }
+
+    // Spending on the types without a budget, so none of it goes unnoticed
+    let unbudgeted: f64 = in_month
+        .iter()
+        .filter(|expense| !is_budgeted(config, &expense.expense_type))
+        .filter(|expense| expense.amount < 0.0)
+        .map(|expense| -expense.amount)
+        .sum();
+    if unbudgeted > 0.0 {
+        notes.push(format!(
+            "{} was spent on types without a budget.",
+            config.money(unbudgeted)
+        ));
+    }
+    document.text(notes.join(" "));
+    document
+}
@@ -0,0 +1,113 @@
+//! `report cashflow`: the income and the expenses of each of the last months side by side, with
+//! what was saved of the income, to tell whether money is being saved at all. The savings rate
+//! leaves out the entries of `savings-exclude`, which the amounts count.
+
+use chrono::NaiveDate;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::Expense;
+use budget_tracker::savings::Savings;
+
+use super::document::{Align, Bars, Document, Table};
+use super::trend::{buckets, By};
+use super::Totals;
+
+/// How many characters the longest bar of the table takes.
+const BAR_WIDTH: usize = 20;
+
+/// A bar of `value` out of `highest`, at least a character long when there is anything.
+fn bar(value: f64, highest: f64) -> String {
+    if value <= 0.0 || highest <= 0.0 {
+        return String::new();
+    }
+    let length = (value / highest * BAR_WIDTH as f64).round() as usize;
+    "█".repeat(length.max(1))
+}
+
+/// The report of the income and expenses of the `months` up to the one of `today`.
+pub fn report(config: &Config, expenses: &[&Expense], months: u32, today: NaiveDate) -> Document {
+    let buckets = buckets(By::Month, months, today);
+    let mut totals = vec![Totals::default(); buckets.len()];
+    for expense in expenses {
+        let Some(date) = parse_iso(&expense.date) else {
+            continue;
+        };
+        if let Some(month) = buckets.iter().position(|(month, _)| month.contains(date)) {
+            totals[month].add(expense.amount);
+        }
+    }
+
+    let mut document = match (buckets.first(), buckets.last()) {
+        (Some((first, _)), Some((last, _))) => Document::new(format!(
+            "Income and expenses from {} to {}",
+            first.start.format("%B %Y"),
+            last.start.format("%B %Y")
+        )),
+        _ => Document::new("Income and expenses"),
+    };
+    // The income and the expenses drawn to the same scale, so the longer bar won
+    let highest = totals
+        .iter()
+        .map(|totals| totals.earned.max(-totals.spent))
+        .fold(0.0, f64::max);
+    let mut table = Table::new(&["Month", "Income", "Expenses", "Saved", "Rate", "In", "Out"])
+        .align(5, Align::Left)
+        .align(6, Align::Left);
+    let mut bars = Bars::default();
+    for ((month, _), totals) in buckets.iter().zip(&totals) {
+        let name = month.month_name();
+        table.push(vec![
+            name.clone(),
+            config.money(totals.earned),
+            config.money(totals.spent),
+            config.money(totals.net()),
+            Savings::new(config, expenses, *month).written_rate(),
+            bar(totals.earned, highest),
+            bar(-totals.spent, highest),
+        ]);
+        bars.push(name, config.money(totals.net()), totals.net());
+    }
+    let all = totals.iter().fold(Totals::default(), |all, totals| Totals {
+        spent: all.spent + totals.spent,
+        earned: all.earned + totals.earned,
+        count: all.count + totals.count,
+    });
+    let count = buckets.len().max(1) as f64;
+    let savings = match (buckets.first(), buckets.last()) {
+        (Some((first, _)), Some((last, _))) => {
+            Savings::new(config, expenses, DateRange::new(first.start, last.end))
+        }
+        _ => Savings::default(),
+    };
+    for (label, earned, spent) in [
+        ("Total", all.earned, all.spent),
+        ("A month", all.earned / count, all.spent / count),
+    ] {
+        table.push(vec![
+            label.to_string(),
+            config.money(earned),
+            config.money(spent),
+            config.money(earned + spent),
+            savings.written_rate(),
+            String::new(),
+            String::new(),
+        ]);
+    }
+    document.table(table);
+    document.bars(bars);
+    let saving = totals.iter().filter(|totals| totals.net() > 0.0).count();
+    document.text(format!(
+        "{} {} over the {} months, {} a month on average. More came in than went out in {} of them.{}",
+        if all.net() < 0.0 { "Lost" } else { "Saved" },
+        config.money(all.net().abs()),
+        buckets.len(),
+        config.money(all.net().abs() / count),
+        saving,
+        match savings.rate() {
+            Some(_) => format!(" The savings rate was {}.", savings.written_rate()),
+            None => String::new(),
+        }
+    ));
+    document
+}
@@ -0,0 +1,402 @@
+//! Images of the TUI's charts, drawn with plotters for `report chart` and the HTML reports.
+
+use chrono::Duration;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use ratatui::style::Color as TermColor;
+use std::path::Path;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::parse_iso;
+use budget_tracker::expense::{capitalize, Expense};
+
+use crate::tui::chart::{self, ChartKind, Metric, OverTime};
+use crate::tui::palette::type_color;
+
+/// How much of a donut's radius is left empty in the middle, as in the TUI.
+const HOLE: f64 = 0.55;
+
+/// About how many values are written beside a chart.
+const TICKS: usize = 6;
+
+/// The color of the image for a color of the TUI, as a terminal usually draws it.
+fn rgb(color: TermColor) -> RGBColor {
+    match color {
+        TermColor::Red => RGBColor(205, 49, 49),
+        TermColor::Green => RGBColor(13, 188, 121),
+        TermColor::Yellow => RGBColor(229, 180, 16),
+        TermColor::Blue => RGBColor(36, 114, 200),
+        TermColor::Magenta => RGBColor(188, 63, 188),
+        TermColor::Cyan => RGBColor(17, 168, 205),
+        TermColor::LightCyan => RGBColor(41, 184, 219),
+        TermColor::LightMagenta => RGBColor(214, 112, 214),
+        TermColor::LightGreen => RGBColor(35, 209, 139),
+        TermColor::LightYellow => RGBColor(245, 245, 67),
+        TermColor::LightBlue => RGBColor(59, 142, 234),
+        TermColor::LightRed => RGBColor(241, 76, 76),
+        TermColor::Gray => RGBColor(192, 192, 192),
+        TermColor::DarkGray => RGBColor(128, 128, 128),
+        TermColor::Rgb(red, green, blue) => RGBColor(red, green, blue),
+        _ => RGBColor(64, 64, 64),
+    }
+}
+
+/// Draws the chart of `kind` of the `expenses` measured by `metric`, as the TUI shows it, to
+/// the image at `output` `size` pixels wide and high: an SVG if its extension is `svg` and a
+/// PNG otherwise, which needs the `chart-image` feature.
+pub fn export_chart(
+    config: &Config,
+    expenses: &[&Expense],
+    (kind, metric): (ChartKind, Metric),
+    output: &Path,
+    size: (u32, u32),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let svg = output
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    if svg {
+        let root = SVGBackend::new(output, size).into_drawing_area();
+        draw_chart(&root, config, expenses, kind, metric)?;
+        root.present()?;
+    } else {
+        png(config, expenses, (kind, metric), output, size)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "chart-image")]
+fn png(
+    config: &Config,
+    expenses: &[&Expense],
+    (kind, metric): (ChartKind, Metric),
+    output: &Path,
+    size: (u32, u32),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let root = BitMapBackend::new(output, size).into_drawing_area();
+    draw_chart(&root, config, expenses, kind, metric)?;
+    root.present()?;
+    Ok(())
+}
+
+/// Without the `chart-image` feature charts can only be drawn as SVG.
+#[cfg(not(feature = "chart-image"))]
+fn png(
+    _config: &Config,
+    _expenses: &[&Expense],
+    _chart: (ChartKind, Metric),
+    output: &Path,
+    _size: (u32, u32),
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err(format!(
+        "This build can only draw charts as SVG, write to a .svg file instead of {} or build it with `cargo install budget-tracker --features chart-image`",
+        output.display()
+    )
+    .into())
+}
+
+/// Draws the chart of `kind` of the `expenses` measured by `metric` as SVG markup `size` pixels
+/// wide and high, to put in an HTML report.
+pub fn chart_svg(
+    config: &Config,
+    expenses: &[&Expense],
+    (kind, metric): (ChartKind, Metric),
+    size: (u32, u32),
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+        draw_chart(&root, config, expenses, kind, metric)?;
+        root.present()?;
+    }
+    Ok(svg)
+}
+
+fn draw_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    config: &Config,
+    expenses: &[&Expense],
+    kind: ChartKind,
+    metric: Metric,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    if kind == ChartKind::Scatter {
+        return draw_scatter(root, config, expenses);
+    }
+    let panels = root.split_evenly((2, 1));
+    if kind == ChartKind::Line {
+        return draw_over_time(&panels, config, expenses, metric);
+    }
+    let sides = chart::groups(kind, metric, expenses);
+    let titles = chart::titles(kind, metric);
+    for ((groups, title), (panel, color)) in sides
+        .iter()
+        .zip(&titles)
+        .zip(panels.iter().zip([TermColor::Cyan, TermColor::Red]))
+    {
+        if kind == ChartKind::Pie {
+            draw_share(panel, config, metric, groups, title)?;
+        } else {
+            // Types in their own colors, days and months in the color of their side
+            let color = |group: &chart::Group| match kind {
+                ChartKind::Bar => rgb(type_color(config, &group.label)),
+                _ => rgb(color),
+            };
+            draw_bars(panel, config, metric, groups, title, &color)?;
+        }
+    }
+    Ok(())
+}
+
+/// A bar per group as high as its value, with the group's name below it.
+fn draw_bars<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    config: &Config,
+    metric: Metric,
+    groups: &[chart::Group],
+    title: &str,
+    color: &dyn Fn(&chart::Group) -> RGBColor,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let highest = groups.iter().map(|group| group.value).fold(0.0, f64::max);
+    let ticks = chart::ticks(0.0, highest, TICKS, metric.min_step());
+    let mut chart = ChartBuilder::on(area)
+        .caption(title, ("sans-serif", 22))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(
+            (0..groups.len().max(2) - 1).into_segmented(),
+            ticks[0]..ticks[ticks.len() - 1],
+        )?;
+    chart
+        .configure_mesh()
+        .label_style(("sans-serif", 14))
+        .disable_x_mesh()
+        .y_labels(ticks.len())
+        .x_labels(groups.len().clamp(1, 40))
+        .x_label_formatter(&|segment| match segment {
+            SegmentValue::CenterOf(index) => groups
+                .get(*index)
+                .map_or(String::new(), |group| group.label.clone()),
+            _ => String::new(),
+        })
+        .y_label_formatter(&|value| metric.format(config, *value))
+        .draw()?;
+    chart.draw_series(groups.iter().enumerate().map(|(index, group)| {
+        let mut bar = Rectangle::new(
+            [
+                (SegmentValue::Exact(index), 0.0),
+                (SegmentValue::Exact(index + 1), group.value),
+            ],
+            color(group).filled(),
+        );
+        bar.set_margin(0, 0, 4, 4);
+        bar
+    }))?;
+    Ok(())
+}
+
+/// A donut with a slice per group, biggest first going clockwise from the top, labeled with
+/// each group's share.
+fn draw_share<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    config: &Config,
+    metric: Metric,
+    groups: &[chart::Group],
+    title: &str,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let area = area.titled(title, ("sans-serif", 22))?;
+    let total: f64 = groups.iter().map(|group| group.value).sum();
+    if total <= 0.0 {
+        return Ok(());
+    }
+    let mut slices = groups.to_vec();
+    slices.sort_by(|a, b| {
+        b.value
+            .total_cmp(&a.value)
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    let (width, height) = area.dim_in_pixel();
+    let center = (width as i32 / 2, height as i32 / 2);
+    let radius = width.min(height) as f64 * 0.35;
+    let sizes: Vec<f64> = slices.iter().map(|slice| slice.value).collect();
+    let colors: Vec<RGBColor> = slices
+        .iter()
+        .map(|slice| rgb(type_color(config, &slice.label)))
+        .collect();
+    let labels: Vec<String> = slices
+        .iter()
+        .map(|slice| {
+            format!(
+                "{} {:.1}% ({})",
+                slice.label,
+                slice.value / total * 100.0,
+                metric.format(config, slice.value)
+            )
+        })
+        .collect();
+    let mut pie = Pie::new(&center, &radius, &sizes, &colors, &labels);
+    pie.start_angle(-90.0);
+    pie.donut_hole(radius * HOLE);
+    pie.label_style(("sans-serif", 16).into_font());
+    area.draw(&pie)?;
+    Ok(())
+}
+
+/// The running total and the spending per day with its rolling averages, as `OverTime` has
+/// them, in the two `panels`.
+fn draw_over_time<DB: DrawingBackend>(
+    panels: &[DrawingArea<DB, Shift>],
+    config: &Config,
+    expenses: &[&Expense],
+    metric: Metric,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let Some(over_time) = OverTime::new(metric, expenses) else {
+        return Ok(());
+    };
+    let [running_title, spent_title] = OverTime::titles(metric);
+    let [week, month] = &over_time.averages;
+    let running = vec![("", TermColor::Green, &over_time.running)];
+    let spent = vec![
+        ("Per day", TermColor::DarkGray, &over_time.spent),
+        ("7 days", TermColor::Cyan, week),
+        ("30 days", TermColor::Yellow, month),
+    ];
+    let mut lines = [(running_title, running), (spent_title, spent)];
+    if !OverTime::running_first(metric) {
+        lines.reverse();
+    }
+
+    let days = (over_time.last - over_time.first).num_days().max(1) as f64;
+    let date = |day: &f64| {
+        let date = over_time.first + Duration::days(*day as i64);
+        config.format_date(&date.format("%Y-%m-%d").to_string())
+    };
+    for (panel, (title, lines)) in panels.iter().zip(lines) {
+        let points = lines.iter().flat_map(|(_, _, points)| points.iter());
+        let (low, high) = points.fold((0.0_f64, 0.0_f64), |(low, high), (_, y)| {
+            (low.min(*y), high.max(*y))
+        });
+        let ticks = chart::ticks(low, high, TICKS, metric.min_step());
+        let mut chart = ChartBuilder::on(panel)
+            .caption(title, ("sans-serif", 22))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(70)
+            .build_cartesian_2d(0.0..days, ticks[0]..ticks[ticks.len() - 1])?;
+        chart
+            .configure_mesh()
+            .label_style(("sans-serif", 14))
+            .y_labels(ticks.len())
+            .x_labels(6)
+            .x_label_formatter(&date)
+            .y_label_formatter(&|value| metric.format(config, *value))
+            .draw()?;
+        for (name, color, points) in &lines {
+            let color = rgb(*color);
+            let series = chart.draw_series(LineSeries::new(points.iter().copied(), color))?;
+            if !name.is_empty() {
+                series
+                    .label(*name)
+                    .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+            }
+        }
+        if lines.len() > 1 {
+            chart
+                .configure_series_labels()
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()?;
+        }
+    }
+    Ok(())
+}
+
+/// A dot per entry at its date and amount, colored by its type.
+fn draw_scatter<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    config: &Config,
+    expenses: &[&Expense],
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let entries: Vec<_> = expenses
+        .iter()
+        .filter_map(|expense| parse_iso(&expense.date).map(|date| (date, *expense)))
+        .collect();
+    let (Some(first), Some(last)) = (
+        entries.iter().map(|(date, _)| *date).min(),
+        entries.iter().map(|(date, _)| *date).max(),
+    ) else {
+        return Ok(());
+    };
+    let (low, high) = entries
+        .iter()
+        .fold((0.0_f64, 0.0_f64), |(low, high), (_, expense)| {
+            (low.min(expense.amount), high.max(expense.amount))
+        });
+    let mut types: Vec<String> = entries
+        .iter()
+        .map(|(_, expense)| capitalize(expense.expense_type.clone()))
+        .collect();
+    types.sort();
+    types.dedup();
+
+    let days = (last - first).num_days().max(1) as f64;
+    let ticks = chart::ticks(low, high, TICKS, Metric::Net.min_step());
+    let mut chart = ChartBuilder::on(root)
+        .caption("Entries by date and amount", ("sans-serif", 22))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(0.0..days, ticks[0]..ticks[ticks.len() - 1])?;
+    chart
+        .configure_mesh()
+        .label_style(("sans-serif", 14))
+        .y_labels(ticks.len())
+        .x_labels(6)
+        .x_label_formatter(&|day| {
+            let date = first + Duration::days(*day as i64);
+            config.format_date(&date.format("%Y-%m-%d").to_string())
+        })
+        .y_label_formatter(&|value| config.short_money(*value))
+        .draw()?;
+    for expense_type in &types {
+        let color = rgb(type_color(config, expense_type));
+        chart
+            .draw_series(
+                entries
+                    .iter()
+                    .filter(|(_, expense)| {
+                        capitalize(expense.expense_type.clone()) == *expense_type
+                    })
+                    .map(|(date, expense)| {
+                        Circle::new(
+                            ((*date - first).num_days() as f64, expense.amount),
+                            4,
+                            color.filled(),
+                        )
+                    }),
+            )?
+            .label(expense_type.as_str())
+            .legend(move |(x, y)| Circle::new((x + 10, y), 4, color.filled()));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
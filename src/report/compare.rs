@@ -0,0 +1,167 @@
+//! `report compare`: the total of each type and of each month in some years side by side, with
+//! how much the last year changed from the first. Months are those of `month-start`, each in
+//! the year it starts in.
+
+use chrono::Datelike;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::{capitalize, Expense};
+
+use super::document::{Document, Table};
+use super::{change, csv_field, share};
+
+/// The totals of a type or month in each year compared.
+#[derive(Debug, Serialize)]
+pub struct Row {
+    pub name: String,
+    pub totals: Vec<f64>,
+    /// The last year's total less the first's
+    pub change: f64,
+    /// The change in percent of the first year's total, if it isn't zero
+    pub percent: Option<f64>,
+}
+
+impl Row {
+    fn new(name: String, totals: Vec<f64>) -> Self {
+        let (first, last) = (totals[0], totals[totals.len() - 1]);
+        Self {
+            name,
+            change: last - first,
+            percent: (first != 0.0).then(|| (last - first) / first.abs() * 100.0),
+            totals,
+        }
+    }
+}
+
+/// The net totals of the years by type and by month.
+#[derive(Debug, Serialize)]
+pub struct Comparison {
+    pub years: Vec<i32>,
+    pub types: Vec<Row>,
+    pub months: Vec<Row>,
+}
+
+impl Comparison {
+    /// Compares the `years` given, in that order, over the `expenses`.
+    pub fn new(expenses: &[&Expense], years: &[i32]) -> Self {
+        let mut types: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        let mut months = vec![vec![0.0; years.len()]; 12];
+        for expense in expenses {
+            let Some(date) = parse_iso(&expense.date)
+                .and_then(DateRange::month_of)
+                .map(|month| month.start)
+            else {
+                continue;
+            };
+            let Some(year) = years.iter().position(|&year| year == date.year()) else {
+                continue;
+            };
+            types
+                .entry(capitalize(expense.expense_type.clone()))
+                .or_insert_with(|| vec![0.0; years.len()])[year] += expense.amount;
+            months[date.month0() as usize][year] += expense.amount;
+        }
+
+        // The biggest spending in the last year first
+        let mut types: Vec<Row> = types
+            .into_iter()
+            .map(|(name, totals)| Row::new(name, totals))
+            .collect();
+        types.sort_by(|a, b| a.totals[years.len() - 1].total_cmp(&b.totals[years.len() - 1]));
+        let months = months
+            .into_iter()
+            .enumerate()
+            .map(|(month, totals)| {
+                let name =
+                    DateRange::month(2001, month as u32 + 1).map_or_else(String::new, |month| {
+                        match month.start.day() {
+                            1 => month.start.format("%B").to_string(),
+                            _ => format!(
+                                "{} - {}",
+                                month.start.format("%-d %b"),
+                                month.end.format("%-d %b")
+                            ),
+                        }
+                    });
+                Row::new(name, totals)
+            })
+            .collect();
+        Self {
+            years: years.to_vec(),
+            types,
+            months,
+        }
+    }
+
+    /// The comparison as tables of text, with a total under each.
+    pub fn to_document(&self, config: &Config) -> Document {
+        let years: Vec<String> = self.years.iter().map(i32::to_string).collect();
+        let mut document = Document::new(format!("{} compared", years.join(", ")));
+        for (heading, title, rows) in [
+            ("By type", "Type", &self.types),
+            ("By month", "Month", &self.months),
+        ] {
+            document.heading(heading);
+            let mut header = vec![title];
+            header.extend(years.iter().map(String::as_str));
+            header.push("Change");
+            // Each type's part of the spending of the last year
+            let by_type = heading == "By type";
+            if by_type {
+                header.push("Share");
+            }
+            let spent: f64 = rows
+                .iter()
+                .map(|row| -row.totals[row.totals.len() - 1].min(0.0))
+                .sum();
+            let mut table = Table::new(&header);
+            let mut totals = vec![0.0; self.years.len()];
+            for row in rows.iter() {
+                for (total, value) in totals.iter_mut().zip(&row.totals) {
+                    *total += value;
+                }
+                let mut cells = self.cells(config, &row.name, &row.totals);
+                if by_type {
+                    cells.push(share(-row.totals[row.totals.len() - 1], spent));
+                }
+                table.push(cells);
+            }
+            table.push(self.cells(config, "Total", &totals));
+            document.table(table);
+        }
+        document
+    }
+
+    /// A line of a table: the name, the total of each year and the change.
+    fn cells(&self, config: &Config, name: &str, totals: &[f64]) -> Vec<String> {
+        let mut cells = vec![name.to_string()];
+        cells.extend(totals.iter().map(|&total| config.money(total)));
+        cells.push(change(config, totals[totals.len() - 1], totals[0]));
+        cells
+    }
+
+    /// The comparison as CSV, a line per type and per month.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("Section,Name");
+        for year in &self.years {
+            csv.push_str(&format!(",{}", year));
+        }
+        csv.push_str(",Change,Percent\n");
+        for (section, rows) in [("type", &self.types), ("month", &self.months)] {
+            for row in rows.iter() {
+                csv.push_str(&format!("{},{}", section, csv_field(&row.name)));
+                for total in &row.totals {
+                    csv.push_str(&format!(",{:.2}", total));
+                }
+                let percent = row
+                    .percent
+                    .map_or_else(String::new, |percent| format!("{:.1}", percent));
+                csv.push_str(&format!(",{:.2},{}\n", row.change, percent));
+            }
+        }
+        csv
+    }
+}
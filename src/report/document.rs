@@ -0,0 +1,348 @@
+//! What a report says, as headings, lines of text and tables, apart from how it is written out.
+
+use serde::Serialize;
+
+/// How the cells of a column line up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Rows of cells under a header.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub align: Vec<Align>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// A table with the given column titles, the first one left aligned and the others right
+    /// aligned, as they mostly hold amounts.
+    pub fn new(header: &[&str]) -> Self {
+        Self {
+            header: header.iter().map(|title| title.to_string()).collect(),
+            align: (0..header.len())
+                .map(|column| match column {
+                    0 => Align::Left,
+                    _ => Align::Right,
+                })
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Aligns the `column` to the left or right.
+    pub fn align(mut self, column: usize, align: Align) -> Self {
+        self.align[column] = align;
+        self
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// The table as an HTML `<table>`, the cells lined up by the class of their column.
+    fn to_html(&self) -> String {
+        let class = |column: usize| match self.align.get(column) {
+            Some(Align::Right) => "right",
+            _ => "left",
+        };
+        let mut html = String::from("<table>\n<thead><tr>");
+        for (column, title) in self.header.iter().enumerate() {
+            html.push_str(&format!(
+                "<th class=\"{}\">{}</th>",
+                class(column),
+                escape(title)
+            ));
+        }
+        html.push_str("</tr></thead>\n<tbody>\n");
+        for row in &self.rows {
+            html.push_str("<tr>");
+            for (column, cell) in row.iter().enumerate() {
+                html.push_str(&format!(
+                    "<td class=\"{}\">{}</td>",
+                    class(column),
+                    escape(cell)
+                ));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+        html
+    }
+
+    /// The table as a Markdown table, its columns lined up by the colons of the line under
+    /// the header.
+    fn to_markdown(&self) -> String {
+        // A pipe would end the cell
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+            format!("| {} |\n", cells.join(" | "))
+        };
+        let mut markdown = line(&self.header);
+        let rule: Vec<String> = self
+            .align
+            .iter()
+            .map(|align| match align {
+                Align::Left => ":---".to_string(),
+                Align::Right => "---:".to_string(),
+            })
+            .collect();
+        markdown.push_str(&format!("|{}|\n", rule.join("|")));
+        for row in &self.rows {
+            markdown.push_str(&line(row));
+        }
+        markdown
+    }
+
+    /// The table as plain text, with its columns padded to line up.
+    pub fn to_text(&self) -> String {
+        let mut widths: Vec<usize> = self
+            .header
+            .iter()
+            .map(|title| title.chars().count())
+            .collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .zip(&self.align)
+                .map(|((cell, &width), align)| match align {
+                    Align::Left => format!("{:<width$}", cell),
+                    Align::Right => format!("{:>width$}", cell),
+                })
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        };
+        let mut text = line(&self.header);
+        text.push('\n');
+        for row in &self.rows {
+            text.push_str(&line(row));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// How many characters the longest bar of a chart takes.
+const BAR_WIDTH: usize = 30;
+
+/// A bar chart drawn with characters, a bar per label as long as its value.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Bars {
+    /// Each bar's label, its value as written beside it and the value it is as long as
+    pub rows: Vec<(String, String, f64)>,
+}
+
+impl Bars {
+    pub fn push(&mut self, label: impl Into<String>, written: impl Into<String>, value: f64) {
+        self.rows.push((label.into(), written.into(), value));
+    }
+
+    /// The chart as lines of `#`, those below zero as long as they are far from it.
+    fn to_text(&self) -> String {
+        let longest = self
+            .rows
+            .iter()
+            .map(|(_, _, value)| value.abs())
+            .fold(0.0, f64::max);
+        let label_width = self
+            .rows
+            .iter()
+            .map(|(label, _, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let written_width = self
+            .rows
+            .iter()
+            .map(|(_, written, _)| written.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut text = String::new();
+        for (label, written, value) in &self.rows {
+            let length = match longest {
+                0.0 => 0,
+                // Any value other than zero gets at least one character
+                longest => ((value.abs() / longest * BAR_WIDTH as f64).round() as usize)
+                    .max(usize::from(*value != 0.0)),
+            };
+            text.push_str(&format!(
+                "{:<label_width$}  {:<BAR_WIDTH$}  {:>written_width$}\n",
+                label,
+                "#".repeat(length),
+                written
+            ));
+        }
+        text
+    }
+}
+
+/// A part of a report.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "content", rename_all = "lowercase")]
+pub enum Block {
+    Heading(String),
+    Text(String),
+    Table(Table),
+    /// A chart as SVG markup, only written out in HTML
+    Chart(String),
+    /// A chart of characters for where an image can't go, written out in Markdown and HTML
+    /// but left out of the text for the terminal, which keeps to the tables
+    Bars(Bars),
+}
+
+/// A report as written out, a title followed by its blocks.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Document {
+    pub title: String,
+    pub blocks: Vec<Block>,
+}
+
+impl Document {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn heading(&mut self, heading: impl Into<String>) {
+        self.blocks.push(Block::Heading(heading.into()));
+    }
+
+    pub fn text(&mut self, text: impl Into<String>) {
+        self.blocks.push(Block::Text(text.into()));
+    }
+
+    pub fn table(&mut self, table: Table) {
+        self.blocks.push(Block::Table(table));
+    }
+
+    pub fn bars(&mut self, bars: Bars) {
+        if !bars.rows.is_empty() {
+            self.blocks.push(Block::Bars(bars));
+        }
+    }
+
+    /// Adds a chart drawn as SVG, see `chart_svg`.
+    pub fn chart(&mut self, svg: String) {
+        self.blocks.push(Block::Chart(svg));
+    }
+
+    /// The report as plain text for the terminal, with the title and headings underlined.
+    pub fn to_text(&self) -> String {
+        let underlined = |text: &str, line: char| {
+            format!(
+                "{}\n{}\n",
+                text,
+                line.to_string().repeat(text.chars().count())
+            )
+        };
+        let mut text = underlined(&self.title, '=');
+        let blocks: Vec<&Block> = self
+            .blocks
+            .iter()
+            .filter(|block| !matches!(block, Block::Chart(_) | Block::Bars(_)))
+            .collect();
+        for (index, block) in blocks.iter().enumerate() {
+            // A blank line between blocks, but none under a heading
+            if index == 0 || !matches!(blocks[index - 1], Block::Heading(_)) {
+                text.push('\n');
+            }
+            match block {
+                Block::Heading(heading) => text.push_str(&underlined(heading, '-')),
+                Block::Text(line) => {
+                    text.push_str(line);
+                    text.push('\n');
+                }
+                Block::Table(table) => text.push_str(&table.to_text()),
+                Block::Chart(_) | Block::Bars(_) => {}
+            }
+        }
+        text
+    }
+
+    /// The report as a standalone HTML page, styled to be read in a browser and with its
+    /// charts drawn in, e.g. to mail to someone who doesn't use the terminal.
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+            escape(&self.title),
+            STYLE,
+            escape(&self.title)
+        );
+        for block in &self.blocks {
+            match block {
+                Block::Heading(heading) => {
+                    html.push_str(&format!("<h2>{}</h2>\n", escape(heading)))
+                }
+                Block::Text(line) => html.push_str(&format!("<p>{}</p>\n", escape(line))),
+                Block::Table(table) => html.push_str(&table.to_html()),
+                Block::Chart(svg) => html.push_str(&format!("<figure>\n{}\n</figure>\n", svg)),
+                Block::Bars(bars) => {
+                    html.push_str(&format!("<pre>{}</pre>\n", escape(&bars.to_text())))
+                }
+            }
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// The report as Markdown, e.g. to paste into notes, with its tables as Markdown tables
+    /// and its charts of characters in code blocks.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.title);
+        for block in &self.blocks {
+            match block {
+                Block::Heading(heading) => markdown.push_str(&format!("\n## {}\n", heading)),
+                Block::Text(line) => markdown.push_str(&format!("\n{}\n", line)),
+                Block::Table(table) => {
+                    markdown.push('\n');
+                    markdown.push_str(&table.to_markdown());
+                }
+                Block::Bars(bars) => {
+                    markdown.push_str(&format!("\n```text\n{}```\n", bars.to_text()))
+                }
+                Block::Chart(_) => {}
+            }
+        }
+        markdown
+    }
+}
+
+/// The style of the HTML pages, kept plain so they print well too.
+const STYLE: &str = "body { font-family: system-ui, sans-serif; max-width: 56rem; margin: 2rem auto; padding: 0 1rem; color: #222; line-height: 1.5; }
+h1 { border-bottom: 2px solid #2472c8; padding-bottom: 0.3rem; }
+h2 { margin-top: 2rem; color: #2472c8; }
+table { border-collapse: collapse; margin: 1rem 0; font-variant-numeric: tabular-nums; }
+th, td { padding: 0.3rem 0.8rem; border-bottom: 1px solid #ddd; }
+th { background: #f3f6fa; }
+tbody tr:last-child td { border-bottom: none; }
+.left { text-align: left; }
+.right { text-align: right; }
+figure { margin: 1rem 0; }
+figure svg { max-width: 100%; height: auto; }
+";
+
+/// The `text` with the characters HTML gives a meaning escaped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
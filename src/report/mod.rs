@@ -0,0 +1,192 @@
+//! Reports of the expenses written from the command line, rather than shown in the TUI.
+
+pub mod budget;
+pub mod cashflow;
+mod chart;
+pub mod compare;
+mod document;
+pub mod networth;
+pub mod payees;
+#[cfg(feature = "pdf")]
+mod pdf;
+pub mod period;
+pub mod stats;
+pub mod tags;
+pub mod template;
+pub mod top;
+pub mod trend;
+pub mod unusual;
+
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::{capitalize, Expense};
+
+pub use chart::{chart_svg, export_chart};
+pub use document::Document;
+
+/// How a report is written out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Format {
+    /// Aligned tables of text
+    #[default]
+    Table,
+    Json,
+    Csv,
+    /// Markdown, e.g. for notes, with charts of characters where the report has them
+    Markdown,
+    /// A standalone web page, with charts where the report has them
+    Html,
+    /// A statement to print or keep, when built with the pdf feature
+    Pdf,
+}
+
+impl Format {
+    /// The extension of a file of a report written out in the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Table => "txt",
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// The media type of a report written out in the format, for posting it.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Table => "text/plain; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+            Self::Pdf => "application/pdf",
+        }
+    }
+
+    /// The `document` of a report written out, as text, Markdown, HTML or PDF. The numbers
+    /// alone are only written out by the reports that have them apart.
+    pub fn write(self, document: &Document) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match self {
+            Self::Table => Ok(document.to_text().into_bytes()),
+            Self::Markdown => Ok(document.to_markdown().into_bytes()),
+            Self::Html => Ok(document.to_html().into_bytes()),
+            #[cfg(feature = "pdf")]
+            Self::Pdf => pdf::to_pdf(document),
+            #[cfg(not(feature = "pdf"))]
+            Self::Pdf => {
+                Err("This build can't write PDF, build it with `cargo install budget-tracker --features pdf`".into())
+            }
+            Self::Json | Self::Csv => Err(format!(
+                "This report can't be written as {}, only as table, markdown, html or pdf",
+                self.to_possible_value()
+                    .map_or(String::new(), |value| value.get_name().to_string())
+            )
+            .into()),
+        }
+    }
+}
+
+/// Posts a `report` of the `content_type` to the `url`, e.g. to an ntfy topic or a chat's
+/// webhook, with its `title` in a `Title` header as ntfy shows it.
+pub fn post(
+    url: &str,
+    title: &str,
+    content_type: &str,
+    report: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    ureq::post(url)
+        .set("Content-Type", content_type)
+        .set("Title", title)
+        .send_bytes(report)
+        .map_err(|error| format!("Couldn't post the report: {}", error))?;
+    Ok(())
+}
+
+/// What was spent and earned over some expenses.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Totals {
+    pub spent: f64,
+    pub earned: f64,
+    pub count: usize,
+}
+
+impl Totals {
+    pub fn of(expenses: &[&Expense]) -> Self {
+        let mut totals = Self::default();
+        for expense in expenses {
+            totals.add(expense.amount);
+        }
+        totals
+    }
+
+    pub fn add(&mut self, amount: f64) {
+        if amount < 0.0 {
+            self.spent += amount;
+        } else {
+            self.earned += amount;
+        }
+        self.count += 1;
+    }
+
+    pub fn net(&self) -> f64 {
+        self.spent + self.earned
+    }
+}
+
+/// The expenses dated within `range`.
+pub fn within<'a>(expenses: &[&'a Expense], range: DateRange) -> Vec<&'a Expense> {
+    expenses
+        .iter()
+        .filter(|expense| parse_iso(&expense.date).is_some_and(|date| range.contains(date)))
+        .copied()
+        .collect()
+}
+
+/// The net total of each type, the types written as in the TUI.
+pub fn by_type(expenses: &[&Expense]) -> BTreeMap<String, f64> {
+    let mut types: BTreeMap<String, f64> = BTreeMap::new();
+    for expense in expenses {
+        *types
+            .entry(capitalize(expense.expense_type.clone()))
+            .or_default() += expense.amount;
+    }
+    types
+}
+
+/// How much `now` differs from `before`, with the change in percent when there was something
+/// before, e.g. `+$12.50 (+8%)`.
+pub fn change(config: &Config, now: f64, before: f64) -> String {
+    let difference = now - before;
+    let money = match difference {
+        difference if difference > 0.0 => format!("+{}", config.money(difference)),
+        difference => config.money(difference),
+    };
+    if before == 0.0 || difference == 0.0 || config.hide_amounts {
+        return money;
+    }
+    format!("{} ({:+.0}%)", money, difference / before.abs() * 100.0)
+}
+
+/// What was `spent` on something as a share of all that was spent, the `total`, in percent,
+/// e.g. `12.5%`. Both are amounts spent, above zero, and nothing is written for an income or
+/// when nothing was spent.
+pub fn share(spent: f64, total: f64) -> String {
+    if spent <= 0.0 || total <= 0.0 {
+        return String::new();
+    }
+    format!("{:.1}%", spent / total * 100.0)
+}
+
+/// A field of a CSV line, quoted if it holds a comma, quote or line break.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
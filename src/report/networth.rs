@@ -0,0 +1,76 @@
+//! `report networth`: what each account holds today from its opening balance, and what they
+//! held together at the end of each of the last months.
+
+use chrono::NaiveDate;
+
+use budget_tracker::account::{balances, net_worth};
+use budget_tracker::config::Config;
+use budget_tracker::date::DateRange;
+use budget_tracker::expense::Expense;
+
+use super::change;
+use super::document::{Bars, Document, Table};
+use crate::tui::group::sparkline;
+
+/// The report of the accounts on `today` and of the net worth in the `months` up to its one.
+pub fn report(config: &Config, expenses: &[&Expense], months: u32, today: NaiveDate) -> Document {
+    let mut document = Document::new("Net worth");
+    if config.accounts.is_empty() {
+        document.text("No accounts are set up yet, so only the entries are added up. Add them with their opening balance to the [accounts] section of the config, e.g. Savings = 5000, or with --set accounts.savings=5000, and tag the entries of each with its name.");
+    }
+
+    let balances = balances(config, expenses, today);
+    let mut table = Table::new(&["Account", "Opening", "Balance"]);
+    for balance in &balances {
+        table.push(vec![
+            balance
+                .name
+                .clone()
+                .unwrap_or_else(|| "No account".to_string()),
+            config.money(balance.opening),
+            config.money(balance.balance),
+        ]);
+    }
+    table.push(vec![
+        "Total".to_string(),
+        config.money(balances.iter().map(|balance| balance.opening).sum()),
+        config.money(balances.iter().map(|balance| balance.balance).sum()),
+    ]);
+    document.table(table);
+    if !config.accounts.is_empty() && balances.iter().any(|balance| balance.name.is_none()) {
+        document.text("No account holds the entries without an account's name in their tags.");
+    }
+
+    document.heading("Over time");
+    let worth = net_worth(config, expenses, months, today);
+    let mut table = Table::new(&["Month", "Net worth", "Change"]);
+    let mut bars = Bars::default();
+    let mut before = None;
+    for (month, total) in &worth {
+        // The month up to today is named as a whole
+        let name =
+            DateRange::month_of(month.start).map_or_else(String::new, |month| month.month_name());
+        bars.push(name.clone(), config.money(*total), *total);
+        table.push(vec![
+            name,
+            config.money(*total),
+            before.map_or(String::new(), |before| change(config, *total, before)),
+        ]);
+        before = Some(*total);
+    }
+    document.table(table);
+    // From the lowest month up, which may be below zero, rather than from zero
+    let low = worth
+        .iter()
+        .map(|(_, total)| *total)
+        .fold(f64::MAX, f64::min);
+    let high = worth
+        .iter()
+        .map(|(_, total)| *total)
+        .fold(f64::MIN, f64::max);
+    let step = ((high - low) / 7.0).max(1.0);
+    let values: Vec<f64> = worth.iter().map(|(_, total)| total - low + step).collect();
+    document.text(sparkline(&values));
+    document.bars(bars);
+    document
+}
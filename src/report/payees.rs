@@ -0,0 +1,35 @@
+//! `report payees`: what was spent on each payee, with how many times and how much on average.
+
+use budget_tracker::config::Config;
+use budget_tracker::expense::Expense;
+use budget_tracker::payee::payees;
+
+use super::document::{Document, Table};
+
+/// The report of the payees of the `expenses`, the `count` most spent on.
+pub fn report(config: &Config, expenses: &[&Expense], count: usize) -> Document {
+    let payees = payees(expenses);
+    let mut document = Document::new("Spending by payee");
+    if payees.is_empty() {
+        document.text("Nothing was spent.");
+        return document;
+    }
+    let mut table = Table::new(&["Payee", "Spent", "Entries", "Average"]);
+    for payee in payees.iter().take(count) {
+        table.push(vec![
+            payee.name.clone(),
+            config.money(payee.spent),
+            payee.count.to_string(),
+            config.money(payee.average()),
+        ]);
+    }
+    document.table(table);
+    if payees.len() > count {
+        document.text(format!(
+            "{} more payees, -n {} to list them all.",
+            payees.len() - count,
+            payees.len()
+        ));
+    }
+    document
+}
@@ -0,0 +1,235 @@
+//! Reports written as PDF with printpdf, laid out like a bank statement on A4 pages: the title
+//! in a band at the top, the tables in a fixed-width font and the charts of characters drawn as
+//! bars. Only built with the `pdf` feature.
+
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Point, Rect, Rgb,
+};
+
+use super::document::{Bars, Block, Document, Table};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 18.0;
+
+/// The font sizes in points, and how much of the size Helvetica takes a letter on average.
+const TITLE_SIZE: f32 = 18.0;
+const HEADING_SIZE: f32 = 12.0;
+const TEXT_SIZE: f32 = 10.0;
+const TABLE_SIZE: f32 = 9.0;
+const LETTER_WIDTH: f32 = 0.5;
+/// Courier's letters are all 0.6 of its size wide.
+const COURIER_WIDTH: f32 = 0.6;
+
+/// Millimetres in a point.
+const POINT: f32 = 0.3528;
+
+/// The blue of the TUI's charts, for the title band and the bars.
+const BLUE: (f32, f32, f32) = (0.14, 0.45, 0.78);
+
+fn color((red, green, blue): (f32, f32, f32)) -> Color {
+    Color::Rgb(Rgb::new(red, green, blue, None))
+}
+
+/// Where the next line goes, moving on to a new page when the page is full.
+struct Writer {
+    pdf: PdfDocumentReference,
+    layer: PdfLayerReference,
+    page: usize,
+    y: f32,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    fixed: IndirectFontRef,
+    fixed_bold: IndirectFontRef,
+}
+
+impl Writer {
+    /// Makes room for `height` millimetres, on a new page if they don't fit on this one.
+    fn room(&mut self, height: f32) {
+        if self.y - height >= MARGIN {
+            return;
+        }
+        let (page, layer) = self.pdf.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+        self.layer = self.pdf.get_page(page).get_layer(layer);
+        self.page += 1;
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.footer();
+    }
+
+    /// The page's number at its bottom.
+    fn footer(&self) {
+        self.layer.set_fill_color(color((0.5, 0.5, 0.5)));
+        self.layer.use_text(
+            format!("Page {}", self.page),
+            8.0,
+            Mm(PAGE_WIDTH - MARGIN - 12.0),
+            Mm(MARGIN / 2.0),
+            &self.regular,
+        );
+        self.layer.set_fill_color(color((0.0, 0.0, 0.0)));
+    }
+
+    /// Writes a line of `size` points at the left margin and moves below it.
+    fn line(&mut self, text: &str, size: f32, font: &IndirectFontRef) {
+        let height = size * POINT * 1.4;
+        self.room(height);
+        self.y -= height;
+        self.layer
+            .use_text(text, size, Mm(MARGIN), Mm(self.y), font);
+    }
+
+    fn rule(&self, y: f32, thickness: f32) {
+        self.layer.set_outline_color(color((0.75, 0.75, 0.75)));
+        self.layer.set_outline_thickness(thickness);
+        self.layer.add_line(Line::from_iter([
+            (Point::new(Mm(MARGIN), Mm(y)), false),
+            (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(y)), false),
+        ]));
+    }
+
+    fn heading(&mut self, heading: &str) {
+        self.y -= 4.0;
+        self.layer.set_fill_color(color(BLUE));
+        let bold = self.bold.clone();
+        self.line(heading, HEADING_SIZE, &bold);
+        self.layer.set_fill_color(color((0.0, 0.0, 0.0)));
+    }
+
+    /// Writes the `text` wrapped to the width of the page.
+    fn text(&mut self, text: &str) {
+        let letters = ((PAGE_WIDTH - 2.0 * MARGIN) / (TEXT_SIZE * LETTER_WIDTH * POINT)) as usize;
+        let regular = self.regular.clone();
+        let mut line = String::new();
+        for word in text.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > letters {
+                self.line(&line, TEXT_SIZE, &regular);
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        if !line.is_empty() {
+            self.line(&line, TEXT_SIZE, &regular);
+        }
+        self.y -= 2.0;
+    }
+
+    /// Writes the `table` as its lines of text in Courier, so its columns line up, smaller
+    /// when it is too wide for the page, with the header in bold above a rule.
+    fn table(&mut self, table: &Table) {
+        let text = table.to_text();
+        let widest = text
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let size = TABLE_SIZE
+            .min((PAGE_WIDTH - 2.0 * MARGIN) / (widest.max(1) as f32 * COURIER_WIDTH * POINT));
+        let (fixed, fixed_bold) = (self.fixed.clone(), self.fixed_bold.clone());
+        for (index, line) in text.lines().enumerate() {
+            if index == 0 {
+                self.line(line, size, &fixed_bold);
+                self.rule(self.y - 1.0, 0.5);
+                self.y -= 1.0;
+            } else {
+                self.line(line, size, &fixed);
+            }
+        }
+        self.y -= 3.0;
+    }
+
+    /// Draws the `bars` as filled bars, each with its label on the left and its value on the
+    /// right.
+    fn bars(&mut self, bars: &Bars) {
+        let longest = bars
+            .rows
+            .iter()
+            .map(|(_, _, value)| value.abs())
+            .fold(0.0, f64::max);
+        let (label_width, value_width) = (40.0, 30.0);
+        let width = PAGE_WIDTH - 2.0 * MARGIN - label_width - value_width;
+        let regular = self.regular.clone();
+        for (label, written, value) in &bars.rows {
+            let height = TABLE_SIZE * POINT * 1.6;
+            self.room(height);
+            self.y -= height;
+            self.layer
+                .use_text(label, TABLE_SIZE, Mm(MARGIN), Mm(self.y), &regular);
+            let length = match longest {
+                0.0 => 0.0,
+                longest => (value.abs() / longest) as f32 * width,
+            };
+            if length > 0.0 {
+                self.layer.set_fill_color(color(BLUE));
+                self.layer.add_rect(Rect::new(
+                    Mm(MARGIN + label_width),
+                    Mm(self.y - 0.5),
+                    Mm(MARGIN + label_width + length),
+                    Mm(self.y + height - 2.0),
+                ));
+                self.layer.set_fill_color(color((0.0, 0.0, 0.0)));
+            }
+            self.layer.use_text(
+                written,
+                TABLE_SIZE,
+                Mm(MARGIN + label_width + length + 2.0),
+                Mm(self.y),
+                &regular,
+            );
+        }
+        self.y -= 4.0;
+    }
+}
+
+/// The `document` as a PDF.
+pub fn to_pdf(document: &Document) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let (pdf, page, layer) =
+        PdfDocument::new(&document.title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+    let layer = pdf.get_page(page).get_layer(layer);
+    let mut writer = Writer {
+        regular: pdf.add_builtin_font(BuiltinFont::Helvetica)?,
+        bold: pdf.add_builtin_font(BuiltinFont::HelveticaBold)?,
+        fixed: pdf.add_builtin_font(BuiltinFont::Courier)?,
+        fixed_bold: pdf.add_builtin_font(BuiltinFont::CourierBold)?,
+        pdf,
+        layer,
+        page: 1,
+        y: PAGE_HEIGHT,
+    };
+    writer.footer();
+
+    // The title in white on a blue band across the top
+    let band = 22.0;
+    writer.layer.set_fill_color(color(BLUE));
+    writer.layer.add_rect(Rect::new(
+        Mm(0.0),
+        Mm(PAGE_HEIGHT - band),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+    ));
+    writer.layer.set_fill_color(color((1.0, 1.0, 1.0)));
+    writer.layer.use_text(
+        &document.title,
+        TITLE_SIZE,
+        Mm(MARGIN),
+        Mm(PAGE_HEIGHT - band + 8.0),
+        &writer.bold,
+    );
+    writer.layer.set_fill_color(color((0.0, 0.0, 0.0)));
+    writer.y = PAGE_HEIGHT - band - 6.0;
+
+    for block in &document.blocks {
+        match block {
+            Block::Heading(heading) => writer.heading(heading),
+            Block::Text(text) => writer.text(text),
+            Block::Table(table) => writer.table(table),
+            Block::Bars(bars) => writer.bars(bars),
+            // An SVG can't go in, the report's bars stand in for it
+            Block::Chart(_) => {}
+        }
+    }
+    Ok(writer.pdf.save_to_bytes()?)
+}
@@ -0,0 +1,298 @@
+//! `report month` and `report week`: what came in and went out in a month or a week against the
+//! one before, the total of each type and the largest expenses.
+
+use chrono::{Days, Months, NaiveDate};
+
+use budget_tracker::config::Config;
+use budget_tracker::date::DateRange;
+use budget_tracker::expense::Expense;
+use budget_tracker::forecast::Forecast;
+use budget_tracker::savings::Savings;
+
+use super::chart_svg;
+use super::document::{Align, Bars, Document, Table};
+use super::{by_type, change, share, unusual, within, Totals};
+use crate::tui::chart::{ChartKind, Metric};
+
+/// How many of the largest expenses are listed.
+const LARGEST: usize = 5;
+
+/// A period summed up, its name and its days.
+struct Period {
+    name: String,
+    range: DateRange,
+}
+
+/// The report of `month` out of the `expenses`, with a forecast of its end while it is going
+/// on `today`.
+pub fn month(
+    config: &Config,
+    expenses: &[&Expense],
+    month: DateRange,
+    today: NaiveDate,
+) -> Document {
+    let previous = month
+        .start
+        .checked_sub_months(Months::new(1))
+        .map(|start| DateRange::new(start, month.start.pred_opt().unwrap_or(start)))
+        .unwrap_or(month);
+    let name = month.month_name();
+    let mut document = summary(
+        config,
+        expenses,
+        name.clone(),
+        "month",
+        [
+            Period { name, range: month },
+            Period {
+                name: previous.month_name(),
+                range: previous,
+            },
+        ],
+    );
+    if month.contains(today) {
+        forecast(&mut document, config, expenses, today);
+    }
+    document
+}
+
+/// Adds the charts of what was spent and earned in `month`, by type and by day, for the
+/// report written as HTML.
+pub fn charts(
+    document: &mut Document,
+    config: &Config,
+    expenses: &[&Expense],
+    month: DateRange,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let expenses = within(expenses, month);
+    if expenses.is_empty() {
+        return Ok(());
+    }
+    document.heading("Charts");
+    for kind in [ChartKind::Bar, ChartKind::Days] {
+        document.chart(chart_svg(
+            config,
+            &expenses,
+            (kind, Metric::Gross),
+            (800, 500),
+        )?);
+    }
+    Ok(())
+}
+
+/// Adds what the spending of the month of `today` comes to by its end, against the budgets.
+fn forecast(document: &mut Document, config: &Config, expenses: &[&Expense], today: NaiveDate) {
+    let Some(forecast) = Forecast::new(expenses, today) else {
+        return;
+    };
+    document.heading("Forecast");
+    document.text(format!(
+        "At this pace {} will be spent by {}: {} so far, {} a day for the {} days left{}.",
+        config.money(forecast.projected()),
+        forecast.month.end.format("%-d %B"),
+        config.money(forecast.spent),
+        config.money(forecast.daily_pace()),
+        forecast.month_days() - forecast.days,
+        match forecast.upcoming().count() {
+            0 => String::new(),
+            _ => format!(
+                " and {} of regular expenses to come",
+                config.money(forecast.upcoming_total())
+            ),
+        }
+    ));
+    if forecast.upcoming().next().is_some() {
+        let mut table = Table::new(&["To come", "Usually"]);
+        for upcoming in forecast.upcoming() {
+            table.push(vec![upcoming.name.clone(), config.money(upcoming.amount)]);
+        }
+        document.table(table);
+    }
+    if let Some((budgeted, budgets)) = Forecast::of_budgets(config, expenses, today) {
+        let difference = budgeted.projected() - budgets;
+        document.text(format!(
+            "The budgeted types will come to {}, {} {} the {} of the budgets.",
+            config.money(budgeted.projected()),
+            config.money(difference.abs()),
+            if difference > 0.0 { "over" } else { "within" },
+            config.money(budgets)
+        ));
+    }
+}
+
+/// The report of the `week` out of the `expenses`, against the one before, with how much of it
+/// is over while it is going on `today`.
+pub fn week(config: &Config, expenses: &[&Expense], week: DateRange, today: NaiveDate) -> Document {
+    let previous = DateRange::week(week.start - Days::new(7));
+    let title = match week.contains(today) {
+        true => format!("This week so far, {}", span(week)),
+        false => format!("The week of {}", span(week)),
+    };
+    summary(
+        config,
+        expenses,
+        title,
+        "week",
+        [
+            Period {
+                name: span(week),
+                range: week,
+            },
+            Period {
+                name: span(previous),
+                range: previous,
+            },
+        ],
+    )
+}
+
+/// The report of the `year` out of the `expenses`, against the year before.
+pub fn year(config: &Config, expenses: &[&Expense], year: i32, today: NaiveDate) -> Document {
+    let (Some(range), Some(previous)) = (DateRange::year(year), DateRange::year(year - 1)) else {
+        return Document::new(year.to_string());
+    };
+    let title = match range.contains(today) {
+        true => format!("This year so far, {}", year),
+        false => year.to_string(),
+    };
+    summary(
+        config,
+        expenses,
+        title,
+        "year",
+        [
+            Period {
+                name: year.to_string(),
+                range,
+            },
+            Period {
+                name: (year - 1).to_string(),
+                range: previous,
+            },
+        ],
+    )
+}
+
+/// The days of `range` written shortly, e.g. `9 Oct - 15 Oct`.
+fn span(range: DateRange) -> String {
+    format!(
+        "{} - {}",
+        range.start.format("%-d %b"),
+        range.end.format("%-d %b")
+    )
+}
+
+/// The report of the `period` against the one before it, a `unit` like `month` earlier.
+fn summary(
+    config: &Config,
+    expenses: &[&Expense],
+    title: String,
+    unit: &str,
+    [period, previous]: [Period; 2],
+) -> Document {
+    let (name, previous_name) = (period.name, previous.name);
+    let current = within(expenses, period.range);
+    let before = within(expenses, previous.range);
+    let mut document = Document::new(title);
+
+    let (totals, previous_totals) = (Totals::of(&current), Totals::of(&before));
+    let mut summary = Table::new(&["", &name, &previous_name, "Change"]);
+    for (label, now, then) in [
+        ("Income", totals.earned, previous_totals.earned),
+        ("Expenses", totals.spent, previous_totals.spent),
+        ("Net", totals.net(), previous_totals.net()),
+    ] {
+        summary.push(vec![
+            label.to_string(),
+            config.money(now),
+            config.money(then),
+            change(config, now, then),
+        ]);
+    }
+    let savings = Savings::new(config, expenses, period.range);
+    let previous_savings = Savings::new(config, expenses, previous.range);
+    summary.push(vec![
+        "Savings rate".to_string(),
+        savings.written_rate(),
+        previous_savings.written_rate(),
+        match (savings.rate(), previous_savings.rate()) {
+            (Some(now), Some(then)) => format!("{:+.0} points", now - then),
+            _ => String::new(),
+        },
+    ]);
+    document.table(summary);
+    document.text(format!(
+        "{} {} this {}, {} the {} before.",
+        totals.count,
+        if totals.count == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        unit,
+        previous_totals.count,
+        unit
+    ));
+
+    // The biggest spending first, then the income
+    document.heading("By type");
+    let (types, previous_types) = (by_type(&current), by_type(&before));
+    let mut names: Vec<&String> = types.keys().chain(previous_types.keys()).collect();
+    names.sort_by(|a, b| {
+        let total = |name: &String| types.get(name).copied().unwrap_or_default();
+        total(a).total_cmp(&total(b)).then_with(|| a.cmp(b))
+    });
+    names.dedup();
+    let mut table = Table::new(&["Type", &name, &previous_name, "Change", "Share"]);
+    let mut bars = Bars::default();
+    for expense_type in names {
+        let now = types.get(expense_type).copied().unwrap_or_default();
+        let then = previous_types
+            .get(expense_type)
+            .copied()
+            .unwrap_or_default();
+        table.push(vec![
+            expense_type.clone(),
+            config.money(now),
+            config.money(then),
+            change(config, now, then),
+            share(-now, -totals.spent),
+        ]);
+        if now < 0.0 {
+            bars.push(expense_type.clone(), config.money(-now), -now);
+        }
+    }
+    document.table(table);
+    document.bars(bars);
+
+    document.heading("Largest expenses");
+    let mut largest: Vec<&Expense> = current
+        .iter()
+        .filter(|expense| expense.amount < 0.0)
+        .copied()
+        .collect();
+    largest.sort_by(|a, b| a.amount.total_cmp(&b.amount));
+    if largest.is_empty() {
+        document.text("Nothing was spent.");
+    } else {
+        let mut table = Table::new(&["Date", "Description", "Type", "Amount"])
+            .align(1, Align::Left)
+            .align(2, Align::Left);
+        for expense in largest.into_iter().take(LARGEST) {
+            table.push(vec![
+                config.format_date(&expense.date),
+                expense.description.clone(),
+                expense.expense_type.clone(),
+                config.money(expense.amount),
+            ]);
+        }
+        document.table(table);
+    }
+
+    // Against every entry, so a type doesn't need many in the period to tell
+    if let Some(table) = unusual::table(config, expenses, Some(period.range)) {
+        document.heading("Unusual amounts");
+        document.table(table);
+    }
+    document
+}
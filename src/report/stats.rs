@@ -0,0 +1,96 @@
+//! `stats`: what a day and a month cost on average, how the days spread out, the busiest
+//! weekday and the average expense.
+
+use std::collections::BTreeMap;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::weekday_name;
+use budget_tracker::expense::{capitalize, Expense};
+use budget_tracker::savings::Savings;
+use budget_tracker::stats::Stats;
+
+use super::document::{Document, Table};
+use super::{share, within};
+
+/// The percentiles of the daily spending listed.
+const PERCENTILES: [f64; 5] = [25.0, 50.0, 75.0, 90.0, 95.0];
+
+/// The report of the `stats` of the `expenses`.
+pub fn report(config: &Config, stats: &Stats, expenses: &[&Expense]) -> Document {
+    let mut document = Document::new(format!(
+        "Spending from {} to {}",
+        stats.range.start.format("%-d %b %Y"),
+        stats.range.end.format("%-d %b %Y")
+    ));
+    document.text(format!(
+        "{} spent over {} days in {} expenses.",
+        config.money(stats.spent),
+        stats.days(),
+        stats.count
+    ));
+    let mut table = Table::new(&["", "Amount"]);
+    let busiest = match stats.busiest_weekday() {
+        Some((weekday, average)) => format!(
+            "{}, {} on average",
+            weekday_name(weekday),
+            config.money(average)
+        ),
+        None => "-".to_string(),
+    };
+    let savings = Savings::new(config, expenses, stats.range);
+    let savings = match savings.rate() {
+        Some(_) => format!(
+            "{} of {} earned",
+            savings.written_rate(),
+            config.money(savings.income)
+        ),
+        None => "-".to_string(),
+    };
+    for (label, value) in [
+        ("A day on average", config.money(stats.daily_mean())),
+        ("The median day", config.money(stats.daily_median())),
+        ("A month on average", config.money(stats.monthly_mean())),
+        ("The average expense", config.money(stats.average_expense())),
+        ("The busiest weekday", busiest),
+        ("The savings rate", savings),
+    ] {
+        table.push(vec![label.to_string(), value]);
+    }
+    document.table(table);
+
+    // The most spent on first
+    let mut types: BTreeMap<String, f64> = BTreeMap::new();
+    for expense in within(expenses, stats.range) {
+        if expense.amount < 0.0 {
+            *types
+                .entry(capitalize(expense.expense_type.clone()))
+                .or_default() -= expense.amount;
+        }
+    }
+    if !types.is_empty() {
+        document.heading("By type");
+        let mut types: Vec<(String, f64)> = types.into_iter().collect();
+        types.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let mut table = Table::new(&["Type", "Spent", "Share"]);
+        for (expense_type, spent) in types {
+            table.push(vec![
+                expense_type,
+                config.money(spent),
+                share(spent, stats.spent),
+            ]);
+        }
+        document.table(table);
+    }
+
+    document.heading("Daily spending");
+    let mut table = Table::new(&["Days", "Spent up to"]);
+    for percent in PERCENTILES {
+        table.push(vec![
+            format!("{:.0}%", percent),
+            config.money(stats.daily_percentile(percent)),
+        ]);
+    }
+    document.table(table);
+    document.text("Days with nothing spent are counted too.");
+    document
+}
@@ -0,0 +1,50 @@
+//! `report tags`: the totals of each tag. An entry with several tags counts towards each of
+//! them, so tags can follow something like a trip across every type it was spent on.
+
+use std::collections::HashMap;
+
+use budget_tracker::config::Config;
+use budget_tracker::expense::Expense;
+
+use super::document::{Document, Table};
+use super::Totals;
+
+/// The report of the tags of the `expenses`, the most spent on first.
+pub fn report(config: &Config, expenses: &[&Expense]) -> Document {
+    // Tags are matched whatever their case, and shown as first written
+    let mut tags: HashMap<String, (String, Totals)> = HashMap::new();
+    let mut untagged = Totals::default();
+    for expense in expenses {
+        if expense.tags.is_empty() {
+            untagged.add(expense.amount);
+        }
+        for tag in &expense.tags {
+            tags.entry(tag.to_lowercase())
+                .or_insert_with(|| (tag.clone(), Totals::default()))
+                .1
+                .add(expense.amount);
+        }
+    }
+    let mut tags: Vec<(String, Totals)> = tags.into_values().collect();
+    tags.sort_by(|a, b| a.1.spent.total_cmp(&b.1.spent).then_with(|| a.0.cmp(&b.0)));
+
+    let mut document = Document::new("Totals by tag");
+    if tags.is_empty() {
+        document.text("No entries have tags.");
+        return document;
+    }
+    let mut table = Table::new(&["Tag", "Spent", "Earned", "Net", "Entries"]);
+    let untagged = ("Untagged".to_string(), untagged);
+    for (tag, totals) in tags.iter().chain([&untagged]) {
+        table.push(vec![
+            tag.clone(),
+            config.money(totals.spent),
+            config.money(totals.earned),
+            config.money(totals.net()),
+            totals.count.to_string(),
+        ]);
+    }
+    document.table(table);
+    document.text("Entries with several tags count towards each of them.");
+    document
+}
@@ -0,0 +1,201 @@
+//! Reports written out through a Tera template of the user's, e.g. `templates/monthly.tera`
+//! in the config directory, for a layout none of the formats has.
+//!
+//! A template is given the report's `title` and its `blocks` in order, each with a `kind` of
+//! `heading`, `text`, `table`, `bars` or `chart` and its `content`, and the same blocks grouped
+//! under their headings as `sections`, each with its `heading`, `texts`, `tables` and `bars`.
+//! A table has its `header` and `rows` of cells as written in the terminal, and a row of bars
+//! its label, its value as written and its value. `today` and `currency` are given too.
+//!
+//! The numbers behind the report are given as they are, to be formatted or computed with: the
+//! days it covers as `from` and `to`, the `totals` spent, earned and net with the `count` of
+//! entries, and the same for each type in `types`, the most spent on first, with its `share` of
+//! the spending in percent. Amounts spent are above zero.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Local;
+use serde::{Serialize, Serializer};
+use tera::{Context, Tera};
+
+use budget_tracker::config::{expand_home, Config};
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::{capitalize, Expense};
+
+use super::document::{Bars, Block, Document, Table};
+use super::{within, Totals};
+
+/// [Totals] as a template is given them, with what was spent above zero and the net beside.
+#[derive(Serialize)]
+struct Amounts {
+    spent: f64,
+    earned: f64,
+    net: f64,
+    count: usize,
+}
+
+impl From<&Totals> for Amounts {
+    fn from(totals: &Totals) -> Self {
+        Self {
+            // Rather than -0 for nothing spent
+            spent: 0.0 - totals.spent,
+            earned: totals.earned,
+            net: totals.net(),
+            count: totals.count,
+        }
+    }
+}
+
+fn amounts<S: Serializer>(totals: &Totals, serializer: S) -> Result<S::Ok, S::Error> {
+    Amounts::from(totals).serialize(serializer)
+}
+
+/// What was spent and earned on a type, with its share of all the spending in percent.
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeTotals {
+    pub name: String,
+    #[serde(flatten, serialize_with = "amounts")]
+    pub totals: Totals,
+    pub share: Option<f64>,
+}
+
+/// The numbers behind a report, given to its template beside the document.
+#[derive(Debug, Default, Serialize)]
+pub struct Data {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(serialize_with = "amounts")]
+    pub totals: Totals,
+    pub types: Vec<TypeTotals>,
+}
+
+impl Data {
+    /// The numbers of the `expenses` dated within `range`, or of all of them for a report of
+    /// no range.
+    pub fn new(expenses: &[&Expense], range: Option<DateRange>) -> Self {
+        let expenses = match range {
+            Some(range) => within(expenses, range),
+            None => expenses.to_vec(),
+        };
+        let mut totals = Totals::default();
+        let mut types: BTreeMap<String, Totals> = BTreeMap::new();
+        for expense in &expenses {
+            totals.add(expense.amount);
+            types
+                .entry(capitalize(expense.expense_type.clone()))
+                .or_default()
+                .add(expense.amount);
+        }
+        let mut types: Vec<TypeTotals> = types
+            .into_iter()
+            .map(|(name, type_totals)| TypeTotals {
+                name,
+                share: (type_totals.spent < 0.0 && totals.spent < 0.0)
+                    .then(|| type_totals.spent / totals.spent * 100.0),
+                totals: type_totals,
+            })
+            .collect();
+        types.sort_by(|a, b| a.totals.spent.total_cmp(&b.totals.spent));
+
+        let dates = || {
+            expenses
+                .iter()
+                .filter_map(|expense| parse_iso(&expense.date))
+        };
+        let (from, to) = match range {
+            Some(range) => (Some(range.start), Some(range.end)),
+            None => (dates().min(), dates().max()),
+        };
+        Self {
+            from: from.map(|date| date.to_string()),
+            to: to.map(|date| date.to_string()),
+            totals,
+            types,
+        }
+    }
+}
+
+/// The blocks under a heading, or before the first one.
+#[derive(Debug, Default, Serialize)]
+struct Section<'a> {
+    heading: Option<&'a str>,
+    texts: Vec<&'a str>,
+    tables: Vec<&'a Table>,
+    bars: Vec<&'a Bars>,
+}
+
+/// The blocks of the `document` grouped under their headings.
+fn sections(document: &Document) -> Vec<Section<'_>> {
+    let mut sections = vec![Section::default()];
+    for block in &document.blocks {
+        if let Block::Heading(heading) = block {
+            sections.push(Section {
+                heading: Some(heading),
+                ..Section::default()
+            });
+            continue;
+        }
+        let Some(section) = sections.last_mut() else {
+            continue;
+        };
+        match block {
+            Block::Text(text) => section.texts.push(text),
+            Block::Table(table) => section.tables.push(table),
+            Block::Bars(bars) => section.bars.push(bars),
+            Block::Heading(_) | Block::Chart(_) => {}
+        }
+    }
+    // Nothing may come before the first heading
+    if sections.len() > 1 && sections[0].texts.is_empty() && sections[0].tables.is_empty() {
+        sections.remove(0);
+    }
+    sections
+}
+
+/// The file of the template `name`: the path itself if it has a directory or an extension,
+/// and `templates/<name>.tera` in the config directory otherwise.
+pub fn template_path(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let path = expand_home(name);
+    if path.components().count() > 1 || path.extension().is_some() {
+        return Ok(path);
+    }
+    Ok(Config::get_config_file_path()?
+        .with_file_name("templates")
+        .join(format!("{}.tera", name)))
+}
+
+/// The `document` written out through the template `name`, with the numbers of its `data`.
+pub fn render(
+    config: &Config,
+    document: &Document,
+    data: &Data,
+    name: &str,
+) -> Result<String, Box<dyn Error>> {
+    let path = template_path(name)?;
+    let template = fs::read_to_string(&path)
+        .map_err(|error| format!("Can't read the template {}: {}", path.display(), error))?;
+
+    let mut context = Context::new();
+    context.insert("title", &document.title);
+    context.insert("blocks", &document.blocks);
+    context.insert("sections", &sections(document));
+    context.insert("from", &data.from);
+    context.insert("to", &data.to);
+    context.insert("totals", &Amounts::from(&data.totals));
+    context.insert("types", &data.types);
+    context.insert("today", &Local::now().date_naive().to_string());
+    context.insert("currency", &config.currency);
+    Tera::one_off(&template, &context, false).map_err(|error| {
+        // Tera says what went wrong in the errors under its own
+        let mut message = format!("Can't fill in the template {}", path.display());
+        let mut source = error.source();
+        while let Some(error) = source {
+            message.push_str(&format!(": {}", error));
+            source = error.source();
+        }
+        message.into()
+    })
+}
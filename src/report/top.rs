@@ -0,0 +1,117 @@
+//! `report top`: the largest expenses, and the descriptions and types the most was spent on,
+//! with their share of all the spending.
+
+use std::collections::HashMap;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::DateRange;
+use budget_tracker::expense::{capitalize, Expense};
+
+use super::document::{Align, Document, Table};
+use super::{share, within};
+
+/// The `count` largest of the totals spent by name, with how many entries each has.
+fn largest(totals: HashMap<String, (f64, usize)>, count: usize) -> Vec<(String, f64, usize)> {
+    let mut totals: Vec<(String, f64, usize)> = totals
+        .into_iter()
+        .map(|(name, (spent, entries))| (name, spent, entries))
+        .collect();
+    totals.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals.truncate(count);
+    totals
+}
+
+/// The report of the `count` largest of the `expenses` in the `period`, or of all of them.
+pub fn report(
+    config: &Config,
+    expenses: &[&Expense],
+    count: usize,
+    period: Option<DateRange>,
+) -> Document {
+    let (expenses, title) = match period {
+        Some(period) => (
+            within(expenses, period),
+            format!(
+                "The largest expenses from {} to {}",
+                period.start.format("%-d %b %Y"),
+                period.end.format("%-d %b %Y")
+            ),
+        ),
+        None => (expenses.to_vec(), "The largest expenses".to_string()),
+    };
+    let mut spending: Vec<&Expense> = expenses
+        .iter()
+        .filter(|expense| expense.amount < 0.0)
+        .copied()
+        .collect();
+    spending.sort_by(|a, b| a.amount.total_cmp(&b.amount));
+    let total: f64 = spending.iter().map(|expense| -expense.amount).sum();
+
+    let mut document = Document::new(title);
+    if spending.is_empty() {
+        document.text("Nothing was spent.");
+        return document;
+    }
+    let mut table = Table::new(&["Date", "Description", "Type", "Amount", "Share"])
+        .align(1, Align::Left)
+        .align(2, Align::Left);
+    for expense in spending.iter().take(count) {
+        table.push(vec![
+            config.format_date(&expense.date),
+            expense.description.clone(),
+            expense.expense_type.clone(),
+            config.money(expense.amount),
+            share(-expense.amount, total),
+        ]);
+    }
+    document.table(table);
+    let shown = spending.len().min(count);
+    let top: f64 = spending[..shown]
+        .iter()
+        .map(|expense| -expense.amount)
+        .sum();
+    document.text(format!(
+        "The {} largest of {} expenses are {} of the {} spent.",
+        shown,
+        spending.len(),
+        share(top, total),
+        config.money(total)
+    ));
+
+    // Descriptions are matched whatever their case, and shown as first written
+    let mut descriptions: HashMap<String, (f64, usize)> = HashMap::new();
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut types: HashMap<String, (f64, usize)> = HashMap::new();
+    for expense in &spending {
+        let key = expense.description.trim().to_lowercase();
+        names
+            .entry(key.clone())
+            .or_insert_with(|| expense.description.trim().to_string());
+        let (spent, entries) = descriptions.entry(key).or_default();
+        *spent -= expense.amount;
+        *entries += 1;
+        let (spent, entries) = types
+            .entry(capitalize(expense.expense_type.clone()))
+            .or_default();
+        *spent -= expense.amount;
+        *entries += 1;
+    }
+    for (heading, title, totals, names) in [
+        ("By description", "Description", descriptions, Some(&names)),
+        ("By type", "Type", types, None),
+    ] {
+        document.heading(heading);
+        let mut table = Table::new(&[title, "Entries", "Spent", "Share"]);
+        for (name, spent, entries) in largest(totals, count) {
+            let name = names.and_then(|names| names.get(&name)).unwrap_or(&name);
+            table.push(vec![
+                name.clone(),
+                entries.to_string(),
+                config.money(spent),
+                share(spent, total),
+            ]);
+        }
+        document.table(table);
+    }
+    document
+}
@@ -0,0 +1,160 @@
+//! `report trend`: what was spent on each type in each of the last months or ISO weeks, with a
+//! sparkline of it and whether the spending is rising, falling or steady.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use std::collections::BTreeMap;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::{capitalize, Expense};
+
+use super::document::{Align, Bars, Document, Table};
+use super::share;
+use crate::tui::group::sparkline;
+
+/// How much the spending has to change over the months, next to its average, to be called a
+/// trend rather than noise.
+const STEADY: f64 = 0.1;
+
+/// The direction of the least squares line through `values`, against their average.
+fn direction(values: &[f64]) -> &'static str {
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let middle = (count - 1.0) / 2.0;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (index, value) in values.iter().enumerate() {
+        covariance += (index as f64 - middle) * (value - mean);
+        variance += (index as f64 - middle).powi(2);
+    }
+    if variance == 0.0 || mean == 0.0 {
+        return "steady →";
+    }
+    // How much the line rises from the first month to the last
+    let rise = covariance / variance * (count - 1.0);
+    match rise / mean {
+        change if change > STEADY => "rising ↑",
+        change if change < -STEADY => "falling ↓",
+        _ => "steady →",
+    }
+}
+
+/// What the spending is added up by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum By {
+    /// Months, starting on the month-start setting's day
+    #[default]
+    Month,
+    /// ISO 8601 weeks, from Monday to Sunday and numbered like 2024-W23
+    IsoWeek,
+}
+
+/// The `count` months or ISO weeks up to the one of `today`, the oldest first, with their
+/// names.
+pub fn buckets(by: By, count: u32, today: NaiveDate) -> Vec<(DateRange, String)> {
+    match by {
+        By::Month => {
+            let this_month = DateRange::month_of(today).map_or(today, |month| month.start);
+            let first = this_month
+                .checked_sub_months(Months::new(count.saturating_sub(1)))
+                .unwrap_or(this_month);
+            // The year is only needed when a month's name comes up twice
+            let format = if count > 12 { "%b %y" } else { "%b" };
+            (0..count)
+                .filter_map(|month| first.checked_add_months(Months::new(month)))
+                .filter_map(DateRange::month_of)
+                .map(|month| (month, month.start.format(format).to_string()))
+                .collect()
+        }
+        By::IsoWeek => {
+            let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+            (0..count)
+                .rev()
+                .filter_map(|back| monday.checked_sub_days(Days::new(7 * u64::from(back))))
+                .map(|start| {
+                    let week = start.iso_week();
+                    (
+                        DateRange::new(start, start + Days::new(6)),
+                        format!("{}-W{:02}", week.year(), week.week()),
+                    )
+                })
+                .collect()
+        }
+    }
+}
+
+/// The report of the spending by type in the `count` months or ISO weeks up to the one of
+/// `today`.
+pub fn report(
+    config: &Config,
+    expenses: &[&Expense],
+    (by, count): (By, u32),
+    today: NaiveDate,
+) -> Document {
+    let buckets = buckets(by, count, today);
+    let index = |date: NaiveDate| buckets.iter().position(|(range, _)| range.contains(date));
+
+    let mut types: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for expense in expenses.iter().filter(|expense| expense.amount < 0.0) {
+        if let Some(bucket) = parse_iso(&expense.date).and_then(index) {
+            types
+                .entry(capitalize(expense.expense_type.clone()))
+                .or_insert_with(|| vec![0.0; buckets.len()])[bucket] -= expense.amount;
+        }
+    }
+    // The biggest spending over the months or weeks first
+    let mut types: Vec<(String, Vec<f64>)> = types.into_iter().collect();
+    types.sort_by(|a, b| b.1.iter().sum::<f64>().total_cmp(&a.1.iter().sum()));
+    let mut total = vec![0.0; buckets.len()];
+    for (_, spent) in &types {
+        for (total, month) in total.iter_mut().zip(spent) {
+            *total += month;
+        }
+    }
+
+    let (first, last) = (buckets.first(), buckets.last());
+    let mut document = Document::new(match (by, first, last) {
+        (By::Month, Some((first, _)), Some((last, _))) => format!(
+            "Spending from {} to {}",
+            first.start.format("%B %Y"),
+            last.start.format("%B %Y")
+        ),
+        (By::IsoWeek, Some((_, first)), Some((_, last))) => {
+            format!("Spending by ISO week from {} to {}", first, last)
+        }
+        _ => "Spending".to_string(),
+    });
+    let names: Vec<String> = buckets.iter().map(|(_, name)| name.clone()).collect();
+    let mut header = vec!["Type"];
+    header.extend(names.iter().map(String::as_str));
+    header.extend(["Share", "Trend", ""]);
+    let mut table = Table::new(&header)
+        .align(buckets.len() + 2, Align::Left)
+        .align(buckets.len() + 3, Align::Left);
+    let all: f64 = total.iter().sum();
+    let total = ("Total".to_string(), total);
+    for (name, spent) in types.iter().chain([&total]) {
+        let mut cells = vec![name.clone()];
+        cells.extend(spent.iter().map(|&month| match month {
+            0.0 => "-".to_string(),
+            month => config.short_money(month),
+        }));
+        cells.push(share(spent.iter().sum(), all));
+        cells.push(sparkline(spent));
+        cells.push(direction(spent).to_string());
+        table.push(cells);
+    }
+    if types.is_empty() {
+        document.text(match by {
+            By::Month => "Nothing was spent in these months.",
+            By::IsoWeek => "Nothing was spent in these weeks.",
+        });
+    } else {
+        document.table(table);
+        let mut bars = Bars::default();
+        for (name, &month) in names.iter().zip(&total.1) {
+            bars.push(name.clone(), config.money(month), month);
+        }
+        document.bars(bars);
+    }
+    document
+}
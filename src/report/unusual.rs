@@ -0,0 +1,57 @@
+//! `report unusual`: the entries whose amount is far from what their type usually comes to,
+//! likely mistakes like a missing decimal point.
+
+use budget_tracker::anomaly::{self, DEVIATIONS};
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::Expense;
+
+use super::document::{Align, Document, Table};
+
+/// The table of the entries of the `expenses` with an unusual amount in the `period`, or in all
+/// of them, compared with the other entries of their type whenever they are. None if there are
+/// none.
+pub fn table(config: &Config, expenses: &[&Expense], period: Option<DateRange>) -> Option<Table> {
+    let unusual: Vec<_> = anomaly::unusual(expenses)
+        .into_iter()
+        .filter(|unusual| {
+            period.is_none_or(|period| {
+                parse_iso(&expenses[unusual.index].date).is_some_and(|date| period.contains(date))
+            })
+        })
+        .collect();
+    if unusual.is_empty() {
+        return None;
+    }
+    let mut table = Table::new(&["Date", "Description", "Type", "Amount", "Usually", "Off by"])
+        .align(1, Align::Left)
+        .align(2, Align::Left);
+    for unusual in unusual {
+        let expense = expenses[unusual.index];
+        table.push(vec![
+            config.format_date(&expense.date),
+            expense.description.clone(),
+            expense.expense_type.clone(),
+            config.money(expense.amount),
+            config.money(unusual.usual),
+            format!("{:.1}σ", unusual.deviations),
+        ]);
+    }
+    Some(table)
+}
+
+/// The report of the unusual amounts among the `expenses` in the `period`, or in all of them.
+pub fn report(config: &Config, expenses: &[&Expense], period: Option<DateRange>) -> Document {
+    let mut document = Document::new("Unusual amounts");
+    match table(config, expenses, period) {
+        Some(table) => {
+            document.table(table);
+            document.text(format!(
+                "These are more than {} standard deviations from the average of the other entries of their type.",
+                DEVIATIONS
+            ));
+        }
+        None => document.text("Every amount is usual for its type."),
+    }
+    document
+}
@@ -0,0 +1,52 @@
+//! The savings rate, the share of the income that wasn't spent: (income - expenses) / income.
+//! Entries whose type or one of whose tags is in `savings-exclude`, like transfers between
+//! accounts or reimbursements, count as neither income nor expenses.
+
+use crate::config::Config;
+use crate::date::{parse_iso, DateRange};
+use crate::expense::Expense;
+
+/// What came in and went out over a range of days, for its savings rate.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Savings {
+    pub income: f64,
+    /// What was spent, as a positive amount
+    pub spent: f64,
+}
+
+impl Savings {
+    /// The savings of the `expenses` dated within `range`, without the excluded ones.
+    pub fn new(config: &Config, expenses: &[&Expense], range: DateRange) -> Self {
+        let mut savings = Self::default();
+        for expense in expenses {
+            if parse_iso(&expense.date).is_none_or(|date| !range.contains(date))
+                || config.excluded_from_savings(expense)
+            {
+                continue;
+            }
+            if expense.amount < 0.0 {
+                savings.spent -= expense.amount;
+            } else {
+                savings.income += expense.amount;
+            }
+        }
+        savings
+    }
+
+    pub fn saved(&self) -> f64 {
+        self.income - self.spent
+    }
+
+    /// The share of the income that was saved in percent, below zero when more was spent than
+    /// came in, or None without income.
+    pub fn rate(&self) -> Option<f64> {
+        (self.income > 0.0).then(|| self.saved() / self.income * 100.0)
+    }
+
+    /// The rate written like `23%`, or nothing without income.
+    pub fn written_rate(&self) -> String {
+        self.rate()
+            .map(|rate| format!("{:.0}%", rate))
+            .unwrap_or_default()
+    }
+}
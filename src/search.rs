@@ -0,0 +1,302 @@
+//! Implements the matching used by `--search`.
+//!
+//! A search can be scoped to a field with `desc:<text>` or `type:<text>`, otherwise the
+//! description, the type and the tags are searched, the fields the [SearchIndex] holds. The
+//! text is split into words, each of which has to match at a word boundary, so `bus` finds
+//! "Bus pass" but not "Butterscotch". Text wrapped in double quotes, like `"bus pass"`, is an
+//! exact phrase and has to match whole words. Only if nothing matches and the search has no
+//! phrases, it falls back to fuzzy matching on the same fields, ordering the results by the
+//! matcher's score.
+//!
+//! For patterns like ticket numbers or merchant codes, [RegexSearch] matches the description
+//! against a regular expression instead.
+
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+use crate::expense::Expense;
+use crate::index::SearchIndex;
+
+/// The fields a [Search] looks at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Any,
+    Description,
+    Type,
+}
+
+/// How well an expense matched, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rank {
+    /// The text matches whole words, e.g. `bus` in "Bus pass".
+    Word,
+    /// The text matches the start of a word, e.g. `bus` in "Business lunch".
+    WordPrefix,
+}
+
+/// A part of the search text, stored in lowercase.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// A bare word, matching at the start of a word.
+    Word(String),
+    /// A quoted phrase, matching only whole words.
+    Phrase(String),
+}
+
+impl Term {
+    fn rank(&self, field: &str) -> Option<Rank> {
+        match self {
+            Term::Word(word) => word_match(field, word),
+            Term::Phrase(phrase) => word_match(field, phrase).filter(|rank| *rank == Rank::Word),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Search {
+    pub field: Field,
+    pub terms: Vec<Term>,
+}
+
+impl Search {
+    pub fn parse(input: &str) -> Self {
+        let input = input.trim();
+        let (field, text) = match input.split_once(':') {
+            Some(("desc", text)) => (Field::Description, text),
+            Some(("type", text)) => (Field::Type, text),
+            _ => (Field::Any, input),
+        };
+        Self {
+            field,
+            terms: split_terms(&text.to_lowercase()),
+        }
+    }
+
+    fn fields<'a>(&self, expense: &'a Expense) -> Vec<&'a str> {
+        match self.field {
+            Field::Any => [&expense.description, &expense.expense_type]
+                .into_iter()
+                .chain(&expense.tags)
+                .map(String::as_str)
+                .collect(),
+            Field::Description => vec![&expense.description],
+            Field::Type => vec![&expense.expense_type],
+        }
+    }
+
+    /// Returns the [Rank] of the worst matching term, where each term takes its best match over
+    /// the searched fields. Returns [None] if any term doesn't match.
+    pub fn rank(&self, expense: &Expense) -> Option<Rank> {
+        let fields = self.fields(expense);
+        self.terms
+            .iter()
+            .map(|term| fields.iter().filter_map(|field| term.rank(field)).min())
+            .try_fold(Rank::Word, |worst, rank| rank.map(|rank| worst.max(rank)))
+    }
+
+    pub fn has_phrases(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|term| matches!(term, Term::Phrase(_)))
+    }
+
+    /// The search text used for fuzzy matching.
+    fn fuzzy_pattern(&self) -> String {
+        self.terms
+            .iter()
+            .map(|term| match term {
+                Term::Word(text) | Term::Phrase(text) => text.as_str(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the best fuzzy matcher score over the searched fields, if any field matches.
+    pub fn fuzzy_score(&self, matcher: &SkimMatcherV2, expense: &Expense) -> Option<i64> {
+        let text = self.fuzzy_pattern();
+        self.fields(expense)
+            .into_iter()
+            .filter_map(|field| matcher.fuzzy_match(field, &text))
+            .max()
+    }
+
+    /// Keeps the matching expenses, best matches first. Expenses of the same [Rank] (or fuzzy
+    /// score) keep their relative order.
+    pub fn filter(&self, expenses: &[Expense]) -> Vec<Expense> {
+        let rows: Vec<usize> = (0..expenses.len()).collect();
+        self.filter_rows(expenses, &rows, None)
+            .into_iter()
+            .map(|row| expenses[row].clone())
+            .collect()
+    }
+
+    /// Like [Search::filter], but works on `rows`, positions into `expenses` in the order they
+    /// are shown. With an `index` built over `expenses`, only the rows it suggests are ranked.
+    pub fn filter_rows(
+        &self,
+        expenses: &[Expense],
+        rows: &[usize],
+        index: Option<&SearchIndex>,
+    ) -> Vec<usize> {
+        let candidates = index.and_then(|index| index.candidates(self));
+        let mut ranked: Vec<(Rank, usize)> = rows
+            .iter()
+            .filter(|row| candidates.as_ref().is_none_or(|rows| rows.contains(row)))
+            .filter_map(|&row| self.rank(&expenses[row]).map(|rank| (rank, row)))
+            .collect();
+
+        if ranked.is_empty() && !self.has_phrases() {
+            let matcher = SkimMatcherV2::default();
+            let mut scored: Vec<(i64, usize)> = rows
+                .iter()
+                .filter_map(|&row| {
+                    self.fuzzy_score(&matcher, &expenses[row])
+                        .map(|score| (score, row))
+                })
+                .collect();
+            scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            return scored.into_iter().map(|(_, row)| row).collect();
+        }
+
+        ranked.sort_by_key(|(rank, _)| *rank);
+        ranked.into_iter().map(|(_, row)| row).collect()
+    }
+}
+
+/// Splits the text into [Term::Word]s on whitespace and [Term::Phrase]s on double quotes. An
+/// unterminated quote extends to the end of the text.
+fn split_terms(text: &str) -> Vec<Term> {
+    text.split('"')
+        .enumerate()
+        .flat_map(|(index, part)| {
+            if index % 2 == 1 {
+                let phrase = part.split_whitespace().collect::<Vec<_>>().join(" ");
+                vec![Term::Phrase(phrase)]
+            } else {
+                part.split_whitespace()
+                    .map(|word| Term::Word(word.to_string()))
+                    .collect()
+            }
+        })
+        .filter(|term| !matches!(term, Term::Phrase(phrase) if phrase.is_empty()))
+        .collect()
+}
+
+/// Matches the description against a case insensitive regular expression, used by `--search-regex`.
+#[derive(Debug, Clone)]
+pub struct RegexSearch {
+    pub regex: Regex,
+}
+
+impl RegexSearch {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        let regex = RegexBuilder::new(pattern).case_insensitive(true).build()?;
+        Ok(Self { regex })
+    }
+
+    pub fn matches(&self, expense: &Expense) -> bool {
+        self.regex.is_match(&expense.description)
+    }
+
+    pub fn filter(&self, expenses: &[Expense]) -> Vec<Expense> {
+        expenses
+            .iter()
+            .filter(|expense| self.matches(expense))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Finds `needle` (expected in lowercase) in `haystack` at a word boundary.
+fn word_match(haystack: &str, needle: &str) -> Option<Rank> {
+    if needle.is_empty() {
+        return Some(Rank::WordPrefix);
+    }
+
+    word_matches(haystack, needle)
+        .into_iter()
+        .map(|(_, rank)| rank)
+        .min()
+}
+
+/// Every occurrence of `needle` (expected in lowercase) starting at a word boundary in
+/// `haystack`, as byte ranges into the lowercased `haystack`.
+fn word_matches(haystack: &str, needle: &str) -> Vec<(Range<usize>, Rank)> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let haystack = haystack.to_lowercase();
+    haystack
+        .match_indices(needle)
+        .filter_map(|(index, _)| {
+            let end = index + needle.len();
+            let starts_word = haystack[..index]
+                .chars()
+                .next_back()
+                .is_none_or(|c| !c.is_alphanumeric());
+            let ends_word = haystack[end..]
+                .chars()
+                .next()
+                .is_none_or(|c| !c.is_alphanumeric());
+            match (starts_word, ends_word) {
+                (true, true) => Some((index..end, Rank::Word)),
+                (true, false) => Some((index..end, Rank::WordPrefix)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Byte ranges of text in the description or type to highlight, showing why a row matched.
+pub trait Highlight {
+    fn highlights(&self, field: Field, text: &str) -> Vec<Range<usize>>;
+}
+
+impl Highlight for Search {
+    fn highlights(&self, field: Field, text: &str) -> Vec<Range<usize>> {
+        if self.field != Field::Any && self.field != field {
+            return Vec::new();
+        }
+
+        let ranges: Vec<Range<usize>> = self
+            .terms
+            .iter()
+            .flat_map(|term| match term {
+                Term::Word(word) => word_matches(text, word),
+                Term::Phrase(phrase) => word_matches(text, phrase)
+                    .into_iter()
+                    .filter(|(_, rank)| *rank == Rank::Word)
+                    .collect(),
+            })
+            .map(|(range, _)| range)
+            .collect();
+        if !ranges.is_empty() || self.has_phrases() {
+            return ranges;
+        }
+
+        // The row could only have matched fuzzily, so highlight the matched characters
+        let pattern = self.fuzzy_pattern();
+        let char_offsets: Vec<(usize, char)> = text.char_indices().collect();
+        SkimMatcherV2::default()
+            .fuzzy_indices(text, &pattern)
+            .map(|(_, indices)| {
+                indices
+                    .into_iter()
+                    .filter_map(|index| char_offsets.get(index))
+                    .map(|(offset, c)| *offset..offset + c.len_utf8())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Highlight for RegexSearch {
+    fn highlights(&self, field: Field, text: &str) -> Vec<Range<usize>> {
+        if field != Field::Description {
+            return Vec::new();
+        }
+        self.regex.find_iter(text).map(|m| m.range()).collect()
+    }
+}
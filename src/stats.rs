@@ -0,0 +1,102 @@
+//! Statistics of the spending over a range of days: what a day and a month cost on average, how
+//! the days spread out, the busiest weekday and the average expense. Income isn't counted.
+
+use chrono::{Datelike, Days, Weekday};
+
+use crate::date::{parse_iso, DateRange};
+use crate::expense::Expense;
+
+/// The average number of days in a month.
+const DAYS_PER_MONTH: f64 = 365.25 / 12.0;
+
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub range: DateRange,
+    /// What was spent in all, as a positive amount
+    pub spent: f64,
+    /// How many expenses there were
+    pub count: usize,
+    /// What was spent each day of the range, least first
+    daily: Vec<f64>,
+    /// What was spent on each weekday from Monday, and how many of them the range has
+    weekdays: [(f64, usize); 7],
+}
+
+impl Stats {
+    /// The statistics of the `expenses` dated within `range`.
+    pub fn new(expenses: &[&Expense], range: DateRange) -> Self {
+        let days = (range.end - range.start).num_days().max(0) as usize + 1;
+        let mut daily = vec![0.0; days];
+        let (mut spent, mut count) = (0.0, 0);
+        for expense in expenses.iter().filter(|expense| expense.amount < 0.0) {
+            let Some(date) = parse_iso(&expense.date).filter(|date| range.contains(*date)) else {
+                continue;
+            };
+            daily[(date - range.start).num_days() as usize] -= expense.amount;
+            spent -= expense.amount;
+            count += 1;
+        }
+        let mut weekdays = [(0.0, 0); 7];
+        for (day, spent) in daily.iter().enumerate() {
+            let date = range.start + Days::new(day as u64);
+            let weekday = &mut weekdays[date.weekday().num_days_from_monday() as usize];
+            weekday.0 += spent;
+            weekday.1 += 1;
+        }
+        daily.sort_by(f64::total_cmp);
+        Self {
+            range,
+            spent,
+            count,
+            daily,
+            weekdays,
+        }
+    }
+
+    /// How many days the range has.
+    pub fn days(&self) -> usize {
+        self.daily.len()
+    }
+
+    /// What a day cost on average.
+    pub fn daily_mean(&self) -> f64 {
+        self.spent / self.days() as f64
+    }
+
+    /// What a month cost on average, from the days' average.
+    pub fn monthly_mean(&self) -> f64 {
+        self.daily_mean() * DAYS_PER_MONTH
+    }
+
+    /// The most spent on the days of the `percent` least spent on, e.g. the median for 50.
+    /// Days with nothing spent count as well.
+    pub fn daily_percentile(&self, percent: f64) -> f64 {
+        let rank =
+            (percent / 100.0 * (self.days() - 1) as f64).clamp(0.0, (self.days() - 1) as f64);
+        let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+        self.daily[low] + (self.daily[high] - self.daily[low]) * (rank - low as f64)
+    }
+
+    pub fn daily_median(&self) -> f64 {
+        self.daily_percentile(50.0)
+    }
+
+    /// The weekday the most is spent on on average, with that average, if anything was spent.
+    pub fn busiest_weekday(&self) -> Option<(Weekday, f64)> {
+        self.weekdays
+            .iter()
+            .enumerate()
+            .filter(|(_, (spent, days))| *spent > 0.0 && *days > 0)
+            .map(|(weekday, (spent, days))| (weekday, spent / *days as f64))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .and_then(|(weekday, average)| Some((Weekday::try_from(weekday as u8).ok()?, average)))
+    }
+
+    /// What an expense cost on average.
+    pub fn average_expense(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.spent / self.count as f64
+    }
+}
@@ -0,0 +1,213 @@
+//! The Budgets tab: how much of each monthly budget set in the config is spent this month, and
+//! how the spending went day by day against the pace that keeps within them.
+
+use chrono::{Datelike, Days, NaiveDate};
+use ratatui::{prelude::*, widgets::*};
+
+use budget_tracker::budget::{is_budgeted, Level, Progress};
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::Expense;
+
+use super::chart::ticks;
+
+/// The lines a budget's gauge takes.
+const GAUGE_HEIGHT: u16 = 3;
+
+/// Green while well within a budget, yellow close to it and red over it.
+pub fn color(progress: &Progress) -> Color {
+    match progress.level() {
+        Level::Over => Color::Red,
+        Level::Close => Color::Yellow,
+        Level::Within => Color::Green,
+    }
+}
+
+/// A gauge per budget, one under the other, over a chart of the spending this month.
+pub fn render(
+    frame: &mut Frame,
+    config: &Config,
+    expenses: &[Expense],
+    budgets: &[Progress],
+    today: NaiveDate,
+    area: Rect,
+) {
+    if budgets.is_empty() {
+        let paragraph = Paragraph::new(vec![
+            Line::from("No budgets are set up yet."),
+            Line::from(""),
+            Line::from("Add them to the [budgets] section of the config, e.g. Food = 300, or with"),
+            Line::from("--set budgets.food=300 or :set budgets.food=300."),
+        ])
+        .style(Style::default().dark_gray())
+        .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(paragraph, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((budgets.len() as u16 * GAUGE_HEIGHT).min(area.height / 2)),
+            Constraint::Min(0),
+        ])
+        .split(area);
+    render_pace(frame, config, expenses, budgets, today, chunks[1]);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            budgets
+                .iter()
+                .map(|_| Constraint::Length(GAUGE_HEIGHT))
+                .chain([Constraint::Min(0)]),
+        )
+        .split(chunks[0]);
+    for (progress, area) in budgets.iter().zip(rows.iter()) {
+        let left = progress.left();
+        let title = format!(
+            "{}: {} of {}, {}",
+            progress.expense_type,
+            config.money(progress.spent),
+            progress.written_budget(config),
+            if left >= 0.0 {
+                format!("{} left", config.money(left))
+            } else {
+                format!("{} over", config.money(-left))
+            }
+        );
+        let gauge = Gauge::default()
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .gauge_style(Style::default().fg(color(progress)))
+            .ratio(progress.ratio().min(1.0))
+            .label(format!("{:.0}%", progress.ratio() * 100.0));
+        frame.render_widget(gauge, *area);
+    }
+}
+
+/// What was spent on the budgeted types by the end of each day of the `month`, up to `last` at
+/// most, as points of the day of the month counting from 1 and the amount.
+fn spent_by_day(
+    config: &Config,
+    expenses: &[Expense],
+    month: DateRange,
+    last: NaiveDate,
+) -> Vec<(f64, f64)> {
+    let mut daily = vec![0.0; month.days() as usize];
+    for expense in expenses
+        .iter()
+        .filter(|expense| is_budgeted(config, &expense.expense_type))
+    {
+        if let Some(date) = parse_iso(&expense.date).filter(|date| month.contains(*date)) {
+            daily[(date - month.start).num_days() as usize] -= expense.amount;
+        }
+    }
+    let days = if month.contains(last) {
+        DateRange::new(month.start, last).days() as usize
+    } else {
+        daily.len()
+    };
+    let mut total = 0.0;
+    daily
+        .iter()
+        .take(days)
+        .enumerate()
+        .map(|(day, spent)| {
+            total += spent;
+            ((day + 1) as f64, total)
+        })
+        .collect()
+}
+
+/// This month's spending on the budgeted types added up day by day, against a straight line to
+/// the sum of what is available of the budgets at the end of the month and last month's
+/// spending.
+fn render_pace(
+    frame: &mut Frame,
+    config: &Config,
+    expenses: &[Expense],
+    budgets: &[Progress],
+    today: NaiveDate,
+    area: Rect,
+) {
+    let month = DateRange::month_of(today).unwrap_or(DateRange::day(today));
+    let last_month = DateRange::month_before(today).unwrap_or(month);
+    let days = month.days() as f64;
+    let total: f64 = budgets.iter().map(Progress::available).sum();
+
+    let this_month = spent_by_day(config, expenses, month, today);
+    let before = spent_by_day(config, expenses, last_month, today);
+    let pace = [(1.0, total / days), (days, total)];
+
+    let spent = this_month.last().map_or(0.0, |(_, spent)| *spent);
+    let expected = total * DateRange::new(month.start, today).days() as f64 / days;
+    let title = if spent > expected {
+        format!(
+            "Spent this month, {} ahead of the budgets' pace",
+            config.money(spent - expected)
+        )
+    } else {
+        format!(
+            "Spent this month, {} behind the budgets' pace",
+            config.money(expected - spent)
+        )
+    };
+
+    let highest = this_month
+        .iter()
+        .chain(&before)
+        .map(|(_, spent)| *spent)
+        .fold(total, f64::max);
+    let count = (area.height.saturating_sub(4) / 3).clamp(2, 6) as usize;
+    let ticks = ticks(0.0, highest, count, 0.01);
+    let datasets = vec![
+        Dataset::default()
+            .name("Last month")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().dark_gray())
+            .data(&before),
+        Dataset::default()
+            .name("Budget pace")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().yellow())
+            .data(&pace),
+        Dataset::default()
+            .name("This month")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().cyan())
+            .data(&this_month),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(
+            Axis::default()
+                .bounds([1.0, days.max(before.len() as f64)])
+                .labels(
+                    [0, month.days() / 2, month.days() - 1]
+                        .map(|day| {
+                            (month.start + Days::new(day as u64))
+                                .day()
+                                .to_string()
+                                .into()
+                        })
+                        .to_vec(),
+                )
+                .style(Style::default().dark_gray()),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([ticks[0], ticks[ticks.len() - 1]])
+                .labels(
+                    ticks
+                        .iter()
+                        .map(|tick| config.short_money(*tick).into())
+                        .collect(),
+                )
+                .style(Style::default().dark_gray()),
+        )
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    frame.render_widget(chart, area);
+}
@@ -0,0 +1,126 @@
+//! A heatmap of what was spent each day, a column per week as on a contribution graph, shown
+//! in the Reports tab. The arrow keys move between the days and Enter shows the selected one's
+//! expenses.
+
+use chrono::{Datelike, Days, NaiveDate};
+use ratatui::{prelude::*, widgets::*};
+use std::collections::HashMap;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::Expense;
+
+use super::accent_color;
+
+/// The lines the heatmap takes: the months, a line per weekday, the selected day and borders.
+pub const HEIGHT: u16 = 11;
+
+/// The width of the weekday names on the left.
+const LABELS: u16 = 4;
+
+/// The colors of days with nothing spent up to the most spent.
+const LEVELS: [Color; 5] = [
+    Color::DarkGray,
+    Color::Indexed(22),
+    Color::Indexed(28),
+    Color::Indexed(34),
+    Color::Indexed(40),
+];
+
+/// Draws the weeks up to today that fit in `area`, going further back if `selected` is older.
+/// Returns the area of each day, to select it with the mouse.
+pub fn render(
+    frame: &mut Frame,
+    config: &Config,
+    expenses: &[&Expense],
+    selected: NaiveDate,
+    today: NaiveDate,
+    area: Rect,
+) -> Vec<(Rect, NaiveDate)> {
+    let block = Block::default()
+        .title("Spending by day")
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < HEIGHT - 2 || inner.width <= LABELS + 2 {
+        return Vec::new();
+    }
+
+    let mut spent: HashMap<NaiveDate, (f64, usize)> = HashMap::new();
+    for expense in expenses.iter().filter(|expense| expense.amount < 0.0) {
+        if let Some(date) = parse_iso(&expense.date) {
+            let (total, count) = spent.entry(date).or_default();
+            *total -= expense.amount;
+            *count += 1;
+        }
+    }
+    let most = spent.values().map(|(total, _)| *total).fold(0.0, f64::max);
+
+    let weeks = ((inner.width - LABELS) / 2) as u64;
+    let mut first = DateRange::week(today.max(selected)).start - Days::new(7 * (weeks - 1));
+    if selected < first {
+        first = DateRange::week(selected).start;
+    }
+
+    // Every other day of the week named, from the one weeks start on
+    let buffer = frame.buffer_mut();
+    for row in [0, 2, 4] {
+        buffer.set_string(
+            inner.x,
+            inner.y + 1 + row,
+            (first + Days::new(row as u64)).format("%a").to_string(),
+            Style::default().dark_gray(),
+        );
+    }
+    let mut hits = Vec::new();
+    let mut label_end = inner.x;
+    for week in 0..weeks {
+        let x = inner.x + LABELS + week as u16 * 2;
+        let start = first + Days::new(7 * week);
+        // The month's name over the week it starts in
+        let end = start + Days::new(6);
+        if (week == 0 || end.day() <= 7) && x >= label_end {
+            let name = end.format("%b").to_string();
+            buffer.set_string(x, inner.y, &name, Style::default().dark_gray());
+            label_end = x + name.len() as u16 + 1;
+        }
+        for weekday in 0..7 {
+            let date = start + Days::new(weekday);
+            if date > today && date != selected {
+                continue;
+            }
+            let total = spent.get(&date).map_or(0.0, |(total, _)| *total);
+            let level = if total > 0.0 {
+                ((total / most * 4.0).ceil() as usize).clamp(1, 4)
+            } else {
+                0
+            };
+            let mut style = Style::default().fg(LEVELS[level]);
+            if date == selected {
+                style = style.bg(accent_color(config.theme));
+            }
+            let cell = Rect::new(x, inner.y + 1 + weekday as u16, 2, 1);
+            buffer.set_string(cell.x, cell.y, "■", style);
+            hits.push((cell, date));
+        }
+    }
+
+    let (total, count) = spent.get(&selected).copied().unwrap_or_default();
+    let date = config.format_date(&selected.format("%Y-%m-%d").to_string());
+    let summary = match count {
+        0 => format!("{} {}: nothing spent", selected.format("%a"), date),
+        count => format!(
+            "{} {}: spent {} in {} {}, Enter to show them",
+            selected.format("%a"),
+            date,
+            config.money(total),
+            count,
+            if count == 1 { "entry" } else { "entries" }
+        ),
+    };
+    frame.render_widget(
+        Paragraph::new(summary),
+        Rect::new(inner.x, inner.y + 8, inner.width, 1),
+    );
+    hits
+}
@@ -0,0 +1,893 @@
+//! The charts beside the table of the Transactions tab: the kind of chart is switched with `v`
+//! and what it measures with `V`. Bars that don't fit are scrolled through with Left and Right
+//! once the charts are focused with `C`.
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use ratatui::widgets::block::Title;
+use ratatui::widgets::canvas::{self, Canvas, Points};
+use ratatui::{prelude::*, widgets::*};
+use std::collections::BTreeMap;
+use std::f64::consts::TAU;
+
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::{capitalize, Expense};
+
+use super::palette::type_color;
+use super::{accent_color, text_color};
+
+/// The kind of chart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ChartKind {
+    /// A bar per type
+    #[default]
+    Bar,
+    /// A donut of each type's share of the total
+    Pie,
+    /// The totals added up day by day
+    Line,
+    /// A dot per entry at its date and amount
+    Scatter,
+    /// A bar per day, the days without entries included so spikes stand out
+    Days,
+    /// A bar per month
+    Months,
+}
+
+impl ChartKind {
+    pub fn next(self) -> Self {
+        match self {
+            ChartKind::Bar => ChartKind::Pie,
+            ChartKind::Pie => ChartKind::Line,
+            ChartKind::Line => ChartKind::Scatter,
+            ChartKind::Scatter => ChartKind::Days,
+            ChartKind::Days => ChartKind::Months,
+            ChartKind::Months => ChartKind::Bar,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChartKind::Bar => "totals by type",
+            ChartKind::Pie => "share of each type",
+            ChartKind::Line => "totals over time",
+            ChartKind::Scatter => "entries by date and amount",
+            ChartKind::Days => "totals by day",
+            ChartKind::Months => "totals by month",
+        }
+    }
+}
+
+/// What the charts measure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Metric {
+    /// The sum of each type or month, charted as spending when below zero and as income above
+    #[default]
+    Net,
+    /// What was spent and what was earned apart, so refunds aren't taken off the spending
+    Gross,
+    /// The number of expenses and of incomes
+    Count,
+}
+
+impl Metric {
+    pub fn next(self) -> Self {
+        match self {
+            Metric::Net => Metric::Gross,
+            Metric::Gross => Metric::Count,
+            Metric::Count => Metric::Net,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Net => "net amounts",
+            Metric::Gross => "gross amounts",
+            Metric::Count => "number of entries",
+        }
+    }
+
+    /// The smallest step between the ticks of an axis, counts being whole numbers.
+    pub fn min_step(self) -> f64 {
+        match self {
+            Metric::Count => 1.0,
+            _ => 0.01,
+        }
+    }
+
+    /// How a value is written on the charts, amounts being hidden while in privacy mode.
+    pub fn format(self, config: &Config, value: f64) -> String {
+        match self {
+            Metric::Count => format!("{}", value as u64),
+            _ => config.short_money(value),
+        }
+    }
+}
+
+/// What the charts show, changed while the TUI runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChartState {
+    pub kind: ChartKind,
+    pub metric: Metric,
+    /// How many bars are scrolled past on the left
+    pub offset: usize,
+    /// Whether Left and Right scroll the bars rather than move between the cells of the table
+    pub focused: bool,
+}
+
+impl ChartState {
+    pub fn next_kind(&mut self) {
+        self.kind = self.kind.next();
+        // The latest days and months are the ones of interest
+        self.offset = match self.kind {
+            ChartKind::Days | ChartKind::Months => usize::MAX,
+            _ => 0,
+        };
+    }
+
+    /// Scrolls the bars by `bars`, as far as `limit` at most.
+    pub fn scroll(&mut self, bars: isize, limit: usize) {
+        self.offset = self
+            .offset
+            .min(limit)
+            .saturating_add_signed(bars)
+            .min(limit);
+    }
+}
+
+/// What clicking a part of a chart does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// Filters the table with the query
+    Filter(String),
+    /// Selects the entry at the position in the expenses charted
+    Entry(usize),
+}
+
+/// The bars' heights are in hundredths, so cents aren't lost to the whole numbers they take.
+const BAR_SCALE: f64 = 100.0;
+
+/// The narrowest bars are drawn, with the ones that don't fit scrolled to.
+const MIN_BAR_WIDTH: u16 = 6;
+
+/// How much of a donut's radius is left empty in the middle.
+const HOLE: f64 = 0.55;
+
+/// Some expenses drawn as a bar or a slice.
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub label: String,
+    /// The query filtering the table by the group, when it is clicked
+    pub query: String,
+    pub value: f64,
+}
+
+/// The value of each type or month of the `expenses`, by its `key`, in the chart of spending
+/// and in the chart of income.
+fn totals(
+    expenses: &[&Expense],
+    metric: Metric,
+    key: impl Fn(&Expense) -> String,
+) -> [BTreeMap<String, f64>; 2] {
+    let [mut spent, mut earned]: [BTreeMap<String, f64>; 2] = Default::default();
+    for expense in expenses {
+        let side = if expense.amount < 0.0 {
+            &mut spent
+        } else {
+            &mut earned
+        };
+        match metric {
+            Metric::Net => *spent.entry(key(expense)).or_default() += expense.amount,
+            Metric::Gross => *side.entry(key(expense)).or_default() += expense.amount.abs(),
+            Metric::Count => *side.entry(key(expense)).or_default() += 1.0,
+        }
+    }
+    if metric == Metric::Net {
+        // Every sum is on the first side so far
+        earned = spent.clone();
+        spent.retain(|_, total| *total < 0.0);
+        spent.values_mut().for_each(|total| *total = -*total);
+        earned.retain(|_, total| *total >= 0.0);
+    }
+    [spent, earned]
+}
+
+/// A bar per day from the first to the last of the `expenses`, like `15 Oct`, on each side.
+/// Days without entries are kept as empty bars, so a big purchase stands out from the days
+/// around it rather than sitting beside the next day something was spent on.
+fn by_day(expenses: &[&Expense], metric: Metric) -> [Vec<Group>; 2] {
+    let dates = expenses
+        .iter()
+        .filter_map(|expense| parse_iso(&expense.date));
+    let (Some(first), Some(last)) = (dates.clone().min(), dates.max()) else {
+        return Default::default();
+    };
+    totals(expenses, metric, |expense| {
+        expense.date.get(..10).unwrap_or(&expense.date).to_string()
+    })
+    .map(|days| {
+        first
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .map(|date| {
+                let day = date.format("%Y-%m-%d").to_string();
+                Group {
+                    label: date.format("%d %b").to_string(),
+                    value: days.get(&day).copied().unwrap_or_default(),
+                    query: format!("date:{}", day),
+                }
+            })
+            .collect()
+    })
+}
+
+/// The groups of the `expenses` a chart of `kind` shows, by type, day or month, that were spent
+/// and that were earned.
+pub fn groups(kind: ChartKind, metric: Metric, expenses: &[&Expense]) -> [Vec<Group>; 2] {
+    match kind {
+        ChartKind::Days => by_day(expenses, metric),
+        // Months as `date:` takes them, with short labels like `Jun 24` after the month they
+        // start in, as many bars share the width
+        ChartKind::Months => totals(expenses, metric, |expense| {
+            match parse_iso(&expense.date).and_then(DateRange::month_of) {
+                Some(month) => month.start.format("%Y-%m").to_string(),
+                None => expense.date.clone(),
+            }
+        })
+        .map(|months| {
+            months
+                .into_iter()
+                .map(|(month, value)| Group {
+                    label: parse_iso(&format!("{}-01", month))
+                        .map_or(month.clone(), |date| date.format("%b %y").to_string()),
+                    query: format!("date:{}", month),
+                    value,
+                })
+                .collect()
+        }),
+        _ => totals(expenses, metric, |expense| {
+            capitalize(expense.expense_type.clone())
+        })
+        .map(|types| {
+            types
+                .into_iter()
+                .map(|(expense_type, value)| Group {
+                    query: format!("type:\"{}\"", expense_type),
+                    label: expense_type,
+                    value,
+                })
+                .collect()
+        }),
+    }
+}
+
+/// The titles of the chart of `kind` of what was spent and of what was earned.
+pub fn titles(kind: ChartKind, metric: Metric) -> [String; 2] {
+    let titles = match kind {
+        ChartKind::Days => ["Spent by day", "Earned by day"],
+        ChartKind::Months => ["Spent by month", "Earned by month"],
+        _ => ["Expenditure", "Income"],
+    };
+    let suffix = match metric {
+        Metric::Net => "",
+        Metric::Gross => " (gross)",
+        Metric::Count => " (entries)",
+    };
+    titles.map(|title| format!("{}{}", title, suffix))
+}
+
+/// Draws the spending and the income of the `expenses` in the upper and lower half of `area`,
+/// or its left and right half when it is much wider than high.
+/// Returns where each type, month or entry was drawn with what clicking it does, for the
+/// mouse, and how many bars can be scrolled past.
+pub fn render(
+    frame: &mut Frame,
+    state: ChartState,
+    config: &Config,
+    expenses: &[&Expense],
+    area: Rect,
+) -> (Vec<(Rect, Target)>, usize) {
+    if state.kind == ChartKind::Scatter {
+        return (render_scatter(frame, config, expenses, area), 0);
+    }
+    // Side by side in a wide and short area, like below the table in a narrow terminal
+    let direction = if area.width >= area.height * 4 {
+        Direction::Horizontal
+    } else {
+        Direction::Vertical
+    };
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    if state.kind == ChartKind::Line {
+        render_over_time(frame, config, state.metric, expenses, chunks[0], chunks[1]);
+        return (Vec::new(), 0);
+    }
+
+    let sides = groups(state.kind, state.metric, expenses);
+    let titles = titles(state.kind, state.metric);
+
+    let (mut hits, mut scroll_limit) = (Vec::new(), 0);
+    for (groups, (chunk, title, color)) in sides.iter().zip([
+        (chunks[0], &titles[0], Style::default().cyan()),
+        (chunks[1], &titles[1], Style::default().red()),
+    ]) {
+        if state.kind == ChartKind::Pie {
+            hits.extend(render_share(
+                frame,
+                config,
+                state.metric,
+                groups,
+                title,
+                chunk,
+            ));
+        } else {
+            let (bars, hidden) = render_bars(frame, config, state, groups, title, color, chunk);
+            hits.extend(bars);
+            scroll_limit = scroll_limit.max(hidden);
+        }
+    }
+    let hits = hits
+        .into_iter()
+        .map(|(area, query)| (area, Target::Filter(query)))
+        .collect();
+    (hits, scroll_limit)
+}
+
+/// A bar per group as high as its value, as many as fit from the offset of the `state`, with how
+/// many more there are on either side in the corner. Returns the area of each bar drawn with its
+/// query, and how many bars didn't fit.
+fn render_bars(
+    frame: &mut Frame,
+    config: &Config,
+    state: ChartState,
+    groups: &[Group],
+    title: &str,
+    color: Style,
+    chunk: Rect,
+) -> (Vec<(Rect, String)>, usize) {
+    // Calculate dynamic bar width, scrolling through the bars that don't fit
+    let inner = chunk.inner(&Margin::new(1, 1));
+    let bar_width = (chunk.width / (groups.len() as u16 + 5)).max(MIN_BAR_WIDTH);
+    let fit = ((inner.width + 1) / (bar_width + 1)).max(1) as usize;
+    let hidden = groups.len().saturating_sub(fit);
+    let offset = state.offset.min(hidden);
+    let groups = &groups[offset..groups.len().min(offset + fit)];
+    let after = hidden - offset;
+
+    // Find the maximum expense amount
+    let max_expense_amount = groups
+        .iter()
+        .map(|group| group.value)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    // Convert type expenses to bar chart data, the heights alone while the amounts are hidden
+    let bars: Vec<Bar> = groups
+        .iter()
+        .map(|group| {
+            let bar = Bar::default()
+                .label(group.label.as_str().into())
+                .value((group.value * BAR_SCALE).round() as u64)
+                .text_value(state.metric.format(config, group.value));
+            // Types in their own colors, days and months in the color of their side
+            match state.kind {
+                ChartKind::Bar => bar.style(Style::default().fg(type_color(config, &group.label))),
+                _ => bar,
+            }
+        })
+        .collect();
+
+    let mut more = String::new();
+    if offset > 0 {
+        more.push_str(&format!("◂ {} more", offset));
+    }
+    if after > 0 {
+        if !more.is_empty() {
+            more.push_str(", ");
+        }
+        more.push_str(&format!("{} more ▸", after));
+    }
+    let mut block = Block::default()
+        .title(title)
+        .title(Title::from(more).alignment(Alignment::Right))
+        .borders(Borders::ALL);
+    if state.focused {
+        block = block.border_style(Style::default().fg(accent_color(config.theme)));
+    }
+
+    let type_barchart = BarChart::default()
+        .block(block)
+        .bar_width(bar_width)
+        .bar_style(color)
+        .value_style(Style::default().fg(text_color(config.theme)).bold())
+        .label_style(Style::default().fg(text_color(config.theme)))
+        .data(BarGroup::default().bars(&bars))
+        .max((max_expense_amount * BAR_SCALE).ceil() as u64);
+
+    frame.render_widget(type_barchart, chunk);
+
+    // Bars are drawn one column apart inside the border
+    let mut hits = Vec::new();
+    for (index, group) in groups.iter().enumerate() {
+        let x = inner.x + index as u16 * (bar_width + 1);
+        if x + bar_width <= inner.right() {
+            hits.push((
+                Rect::new(x, inner.y, bar_width, inner.height),
+                group.query.clone(),
+            ));
+        }
+    }
+    (hits, hidden)
+}
+
+/// A donut with a slice per group, biggest first going clockwise from the top, and a legend with
+/// each group's share beside it. Returns the area of each line of the legend with its query.
+fn render_share(
+    frame: &mut Frame,
+    config: &Config,
+    metric: Metric,
+    groups: &[Group],
+    title: &str,
+    chunk: Rect,
+) -> Vec<(Rect, String)> {
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner = block.inner(chunk);
+    frame.render_widget(block, chunk);
+
+    let total: f64 = groups.iter().map(|group| group.value).sum();
+    if total <= 0.0 || inner.height == 0 {
+        frame.render_widget(
+            Paragraph::new("Nothing to show").style(Style::default().dark_gray()),
+            inner,
+        );
+        return Vec::new();
+    }
+    let mut slices = groups.to_vec();
+    slices.sort_by(|a, b| {
+        b.value
+            .total_cmp(&a.value)
+            .then_with(|| a.label.cmp(&b.label))
+    });
+
+    // A cell is about twice as tall as it is wide, so a round donut is twice as wide as tall
+    let size = inner.height.min(inner.width / 3);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(size * 2 + 1), Constraint::Min(0)])
+        .split(inner);
+    let donut_area = Rect::new(chunks[0].x, chunks[0].y, size * 2, size);
+
+    // Every dot of the donut, taking the color of the slice its angle falls in
+    let (columns, rows) = (
+        donut_area.width as usize * 2,
+        donut_area.height as usize * 4,
+    );
+    let mut dots: Vec<Vec<(f64, f64)>> = vec![Vec::new(); slices.len()];
+    for column in 0..columns {
+        for row in 0..rows {
+            let x = (column as f64 + 0.5) / columns as f64 * 2.0 - 1.0;
+            let y = (row as f64 + 0.5) / rows as f64 * 2.0 - 1.0;
+            let radius = x.hypot(y);
+            if !(HOLE..=1.0).contains(&radius) {
+                continue;
+            }
+            let turn = (x.atan2(y) / TAU).rem_euclid(1.0) * total;
+            let mut start = 0.0;
+            for (index, slice) in slices.iter().enumerate() {
+                start += slice.value;
+                if turn < start || index + 1 == slices.len() {
+                    dots[index].push((x, y));
+                    break;
+                }
+            }
+        }
+    }
+    let canvas = Canvas::default()
+        .marker(symbols::Marker::Braille)
+        .x_bounds([-1.0, 1.0])
+        .y_bounds([-1.0, 1.0])
+        .paint(|context| {
+            for (index, coords) in dots.iter().enumerate() {
+                context.draw(&Points {
+                    coords,
+                    color: type_color(config, &slices[index].label),
+                });
+            }
+        });
+    frame.render_widget(canvas, donut_area);
+
+    let legend = chunks[1];
+    let lines: Vec<Line> = slices
+        .iter()
+        .map(|slice| {
+            let value = metric.format(config, slice.value);
+            Line::from(vec![
+                Span::styled("■ ", Style::default().fg(type_color(config, &slice.label))),
+                Span::styled(
+                    format!("{:>5.1}% ", slice.value / total * 100.0),
+                    Style::default().fg(text_color(config.theme)).bold(),
+                ),
+                Span::raw(format!("{} ", slice.label)),
+                Span::styled(value, Style::default().dark_gray()),
+            ])
+        })
+        .collect();
+    let hits = slices
+        .iter()
+        .take(legend.height as usize)
+        .enumerate()
+        .map(|(index, slice)| {
+            (
+                Rect::new(legend.x, legend.y + index as u16, legend.width, 1),
+                slice.query.clone(),
+            )
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), legend);
+    hits
+}
+
+/// A dot per entry of the `expenses` at its date and amount, colored by its type as the legend
+/// above says, so the ones far from the rest stand out. Returns the cell of each dot with the
+/// position of its entry, the last drawn first.
+fn render_scatter(
+    frame: &mut Frame,
+    config: &Config,
+    expenses: &[&Expense],
+    area: Rect,
+) -> Vec<(Rect, Target)> {
+    let block = Block::default()
+        .title("Entries by date and amount")
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let entries: Vec<(usize, NaiveDate, &Expense)> = expenses
+        .iter()
+        .enumerate()
+        .filter_map(|(index, expense)| parse_iso(&expense.date).map(|date| (index, date, *expense)))
+        .collect();
+    let (Some(first), Some(last)) = (
+        entries.iter().map(|(_, date, _)| *date).min(),
+        entries.iter().map(|(_, date, _)| *date).max(),
+    ) else {
+        frame.render_widget(
+            Paragraph::new("Nothing to show").style(Style::default().dark_gray()),
+            inner,
+        );
+        return Vec::new();
+    };
+    let mut types: Vec<String> = entries
+        .iter()
+        .map(|(_, _, expense)| capitalize(expense.expense_type.clone()))
+        .collect();
+    types.sort();
+    types.dedup();
+    let color = |expense_type: &str| type_color(config, expense_type);
+
+    // The legend on the first line, the values on the left and the dates on the last line
+    let (low, high) = entries
+        .iter()
+        .fold((0.0_f64, 0.0_f64), |(low, high), (_, _, expense)| {
+            (low.min(expense.amount), high.max(expense.amount))
+        });
+    let count = (inner.height.saturating_sub(3) / 3).clamp(2, 6) as usize;
+    let ticks = ticks(low, high, count, 0.01);
+    let labels: Vec<String> = ticks.iter().map(|tick| config.short_money(*tick)).collect();
+    let label_width = labels.iter().map(|label| label.len()).max().unwrap_or(0) as u16 + 1;
+    if inner.height < 5 || inner.width <= label_width + 2 {
+        return Vec::new();
+    }
+    let plot = Rect::new(
+        inner.x + label_width,
+        inner.y + 1,
+        inner.width - label_width,
+        inner.height - 2,
+    );
+    let legend: Vec<Span> = types
+        .iter()
+        .flat_map(|expense_type| {
+            [
+                Span::styled("■ ", Style::default().fg(color(expense_type))),
+                Span::raw(format!("{}  ", expense_type)),
+            ]
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(Line::from(legend)),
+        Rect::new(inner.x, inner.y, inner.width, 1),
+    );
+    let buffer = frame.buffer_mut();
+    let style = Style::default().dark_gray();
+    for (index, label) in labels.iter().enumerate() {
+        let row = (ticks.len() - 1 - index) as u16 * (plot.height - 1) / (ticks.len() - 1) as u16;
+        buffer.set_string(inner.x, plot.y + row, label, style);
+    }
+    let dates = [first, last].map(|date| config.format_date(&date.format("%Y-%m-%d").to_string()));
+    buffer.set_string(plot.x, plot.bottom(), &dates[0], style);
+    let end = plot
+        .right()
+        .saturating_sub(dates[1].len() as u16)
+        .max(plot.x);
+    buffer.set_string(end, plot.bottom(), &dates[1], style);
+
+    let days = (last - first).num_days().max(1) as f64;
+    let ([bottom, top], x) = ([ticks[0], ticks[ticks.len() - 1]], |date: NaiveDate| {
+        (date - first).num_days() as f64
+    });
+    let canvas = Canvas::default()
+        .marker(symbols::Marker::Braille)
+        .x_bounds([0.0, days])
+        .y_bounds([bottom, top])
+        .paint(|context| {
+            context.draw(&canvas::Line {
+                x1: 0.0,
+                y1: 0.0,
+                x2: days,
+                y2: 0.0,
+                color: Color::DarkGray,
+            });
+            for expense_type in &types {
+                let coords: Vec<(f64, f64)> = entries
+                    .iter()
+                    .filter(|(_, _, expense)| {
+                        capitalize(expense.expense_type.clone()) == *expense_type
+                    })
+                    .map(|(_, date, expense)| (x(*date), expense.amount))
+                    .collect();
+                context.draw(&Points {
+                    coords: &coords,
+                    color: color(expense_type),
+                });
+            }
+        });
+    frame.render_widget(canvas, plot);
+
+    // The cell each dot falls in, as the canvas places them on its grid of Braille dots
+    let (columns, rows) = (
+        plot.width as f64 * 2.0 - 1.0,
+        plot.height as f64 * 4.0 - 1.0,
+    );
+    entries
+        .iter()
+        .rev()
+        .map(|(index, date, expense)| {
+            let column = (x(*date) / days * columns) as u16 / 2;
+            let row = ((top - expense.amount) / (top - bottom) * rows) as u16 / 4;
+            (
+                Rect::new(plot.x + column, plot.y + row, 1, 1),
+                Target::Entry(*index),
+            )
+        })
+        .collect()
+}
+
+/// The `expenses` over time as days since the first: the running total at the end of every
+/// day they were made on, being the balance for the net amounts, what was earned for the gross
+/// ones or the number of incomes, and what was spent or the number of expenses each day with
+/// its rolling averages over 7 and 30 days, which smooth out the noise of single days so real
+/// changes in the trend show. The balance starts from zero at the first expense shown, so over
+/// a period it is what was gained or lost since it began.
+pub struct OverTime {
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+    pub running: Vec<(f64, f64)>,
+    /// Every day from the first to the last, the ones without entries included so the averages
+    /// are over the days that went by
+    pub spent: Vec<(f64, f64)>,
+    pub averages: [Vec<(f64, f64)>; 2],
+}
+
+impl OverTime {
+    /// The lines of the `expenses` measured by `metric`, or `None` if none has a date.
+    pub fn new(metric: Metric, expenses: &[&Expense]) -> Option<Self> {
+        let mut days: Vec<(NaiveDate, f64)> = expenses
+            .iter()
+            .filter_map(|expense| parse_iso(&expense.date).map(|date| (date, expense.amount)))
+            .collect();
+        days.sort_by_key(|(date, _)| *date);
+        let first = days.first()?.0;
+        let last = days.last().map_or(first, |(date, _)| *date);
+
+        let mut running = Vec::new();
+        let mut total = 0.0;
+        let mut daily = vec![0.0; (last - first).num_days() as usize + 1];
+        for (index, (date, amount)) in days.iter().enumerate() {
+            let day = (*date - first).num_days() as usize;
+            match metric {
+                Metric::Net => total += amount,
+                _ if *amount < 0.0 => {}
+                Metric::Gross => total += amount,
+                Metric::Count => total += 1.0,
+            }
+            if *amount < 0.0 {
+                daily[day] += match metric {
+                    Metric::Count => 1.0,
+                    _ => -amount,
+                };
+            }
+            if days.get(index + 1).is_none_or(|(next, _)| next != date) {
+                running.push((day as f64, total));
+            }
+        }
+        Some(Self {
+            first,
+            last,
+            running,
+            spent: daily
+                .iter()
+                .enumerate()
+                .map(|(day, value)| (day as f64, *value))
+                .collect(),
+            averages: [7, 30].map(|window| rolling_average(&daily, window)),
+        })
+    }
+
+    /// The titles of the running total and of the spending per day.
+    pub fn titles(metric: Metric) -> [&'static str; 2] {
+        match metric {
+            Metric::Net => ["Balance", "Spent per day"],
+            Metric::Gross => ["Earned so far", "Spent per day"],
+            Metric::Count => ["Incomes so far", "Expenses per day"],
+        }
+    }
+
+    /// Whether the running total is shown above the spending, as the balance is.
+    pub fn running_first(metric: Metric) -> bool {
+        metric == Metric::Net
+    }
+}
+
+/// The `expenses` over time in `upper_area` and `lower_area`, as `OverTime` has them.
+fn render_over_time(
+    frame: &mut Frame,
+    config: &Config,
+    metric: Metric,
+    expenses: &[&Expense],
+    upper_area: Rect,
+    lower_area: Rect,
+) {
+    let [running_title, spent_title] = OverTime::titles(metric);
+    let mut titles = [running_title, spent_title];
+    if !OverTime::running_first(metric) {
+        titles.reverse();
+    }
+    let Some(over_time) = OverTime::new(metric, expenses) else {
+        for (area, title) in [upper_area, lower_area].into_iter().zip(titles) {
+            frame.render_widget(
+                Paragraph::new("Nothing to show")
+                    .style(Style::default().dark_gray())
+                    .block(Block::default().title(title).borders(Borders::ALL)),
+                area,
+            );
+        }
+        return;
+    };
+
+    let dates = date_ticks(config, over_time.first, over_time.last, upper_area.width);
+    let [week, month] = &over_time.averages;
+    let running_lines = [("", Color::Green, over_time.running.as_slice())];
+    let spent_lines = [
+        ("", Color::DarkGray, over_time.spent.as_slice()),
+        ("7 days", Color::Cyan, week.as_slice()),
+        ("30 days", Color::Yellow, month.as_slice()),
+    ];
+    let (upper, lower): (&[_], &[_]) = if OverTime::running_first(metric) {
+        (&running_lines, &spent_lines)
+    } else {
+        (&spent_lines, &running_lines)
+    };
+    render_line(frame, config, metric, upper, &dates, titles[0], upper_area);
+    render_line(frame, config, metric, lower, &dates, titles[1], lower_area);
+}
+
+/// A line drawn by `render_line`: its name, color and points.
+type Series<'a> = (&'a str, Color, &'a [(f64, f64)]);
+
+/// The average of the `values` over the `window` of days ending on each day, or over the days
+/// so far for the first ones, as a point per day.
+fn rolling_average(values: &[f64], window: usize) -> Vec<(f64, f64)> {
+    let mut sum = 0.0;
+    values
+        .iter()
+        .enumerate()
+        .map(|(day, value)| {
+            sum += value;
+            if day >= window {
+                sum -= values[day - window];
+            }
+            (day as f64, sum / (day + 1).min(window) as f64)
+        })
+        .collect()
+}
+
+/// The dates spread evenly from `first` to `last` to write below a chart `width` columns wide,
+/// as many as fit, with at least the first and the last.
+fn date_ticks(config: &Config, first: NaiveDate, last: NaiveDate, width: u16) -> Vec<String> {
+    let count = (width / 16).clamp(2, 5) as i64;
+    let days = (last - first).num_days().max(1);
+    (0..count)
+        .map(|tick| {
+            let date = first + chrono::Duration::days(days * tick / (count - 1));
+            config.format_date(&date.format("%Y-%m-%d").to_string())
+        })
+        .collect()
+}
+
+/// Round values from at most `low` to at least `high`, a round step apart like 0, 250, 500 and
+/// about `count` of them, to scale an axis to and label its ticks with. The step is at least
+/// `min_step`, for values that are whole numbers.
+pub fn ticks(low: f64, high: f64, count: usize, min_step: f64) -> Vec<f64> {
+    let high = if high > low { high } else { low + 1.0 };
+    let rough = (high - low) / count.saturating_sub(1).max(1) as f64;
+    let magnitude = 10_f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(rough)
+        .max(min_step);
+    let first = (low / step).floor() as i64;
+    let last = (high / step).ceil() as i64;
+    (first..=last).map(|tick| tick as f64 * step).collect()
+}
+
+/// The `lines` of points of days since the first of the `dates` and values, each named in a
+/// legend unless it is empty and drawn in Braille to be smooth even in a small pane. The axis
+/// of values is scaled to round ticks written as `metric` is, as many as fit its height, and
+/// the `dates` are spread below it.
+fn render_line(
+    frame: &mut Frame,
+    config: &Config,
+    metric: Metric,
+    lines: &[Series],
+    dates: &[String],
+    title: &str,
+    area: Rect,
+) {
+    let points = lines.iter().flat_map(|(_, _, points)| points.iter());
+    let (low, high) = points
+        .clone()
+        .fold((0.0_f64, 0.0_f64), |(low, high), (_, y)| {
+            (low.min(*y), high.max(*y))
+        });
+    let days = points.fold(1.0_f64, |days, (day, _)| days.max(*day));
+    let count = (area.height.saturating_sub(4) / 3).clamp(2, 6) as usize;
+    let ticks = ticks(low, high, count, metric.min_step());
+    let datasets = lines
+        .iter()
+        .map(|(name, color, points)| {
+            let dataset = Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(points);
+            match *name {
+                "" => dataset,
+                name => dataset.name(name),
+            }
+        })
+        .collect();
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, days])
+                .labels(dates.iter().map(|date| date.as_str().into()).collect())
+                .style(Style::default().dark_gray()),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([ticks[0], ticks[ticks.len() - 1]])
+                .labels(
+                    ticks
+                        .iter()
+                        .map(|tick| metric.format(config, *tick).into())
+                        .collect(),
+                )
+                .style(Style::default().dark_gray()),
+        );
+    frame.render_widget(chart, area);
+}
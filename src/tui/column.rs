@@ -0,0 +1,155 @@
+//! The columns of the expense table and sorting by them.
+
+use std::cmp::Ordering;
+
+use budget_tracker::config::Config;
+use budget_tracker::expense::Expense;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    Date,
+    Description,
+    Type,
+    Amount,
+    Tags,
+}
+
+impl Column {
+    pub const ALL: [Column; 5] = [
+        Column::Date,
+        Column::Description,
+        Column::Type,
+        Column::Amount,
+        Column::Tags,
+    ];
+
+    /// The columns shown when the config doesn't list them.
+    pub const DEFAULT: [Column; 4] = [
+        Column::Date,
+        Column::Description,
+        Column::Type,
+        Column::Amount,
+    ];
+
+    /// The columns of the table in the order set in the config, or the default ones.
+    pub fn shown(config: &Config) -> Vec<Column> {
+        let columns: Vec<Column> = config
+            .columns
+            .iter()
+            .filter_map(|name| Self::from_name(name))
+            .collect();
+        if columns.is_empty() {
+            Self::DEFAULT.to_vec()
+        } else {
+            columns
+        }
+    }
+
+    /// The column with the name given in the config, e.g. `date`, whatever its case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|column| column.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The name of the column in the config.
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Date => "date",
+            Column::Description => "description",
+            Column::Type => "type",
+            Column::Amount => "amount",
+            Column::Tags => "tags",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Date => "Date",
+            Column::Description => "Description",
+            Column::Type => "Type",
+            Column::Amount => "Amount",
+            Column::Tags => "Tags",
+        }
+    }
+
+    /// The column after this one among the `columns` shown, going round to the first.
+    pub fn next(self, columns: &[Column]) -> Self {
+        let position = columns.iter().position(|&column| column == self);
+        let next = position.map_or(0, |position| (position + 1) % columns.len());
+        columns.get(next).copied().unwrap_or(self)
+    }
+
+    /// The column before this one among the `columns` shown, going round to the last.
+    pub fn previous(self, columns: &[Column]) -> Self {
+        let position = columns.iter().position(|&column| column == self);
+        let previous =
+            position.map_or(0, |position| (position + columns.len() - 1) % columns.len());
+        columns.get(previous).copied().unwrap_or(self)
+    }
+
+    /// Orders two expenses by this column, ascending. Text is compared ignoring case.
+    pub fn compare(self, a: &Expense, b: &Expense) -> Ordering {
+        match self {
+            // Dates are stored as YYYY-MM-DD, so they sort as text
+            Column::Date => a.date.cmp(&b.date),
+            Column::Description => a
+                .description
+                .to_lowercase()
+                .cmp(&b.description.to_lowercase()),
+            Column::Type => a
+                .expense_type
+                .to_lowercase()
+                .cmp(&b.expense_type.to_lowercase()),
+            Column::Amount => a.amount.total_cmp(&b.amount),
+            Column::Tags => a
+                .tags
+                .join(" ")
+                .to_lowercase()
+                .cmp(&b.tags.join(" ").to_lowercase()),
+        }
+    }
+}
+
+/// How the table is sorted, newest first by default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self::by(Column::Date)
+    }
+}
+
+impl Sort {
+    /// Sorts by the column, dates newest first and everything else ascending.
+    pub fn by(column: Column) -> Self {
+        Self {
+            column,
+            descending: column == Column::Date,
+        }
+    }
+
+    /// Orders two expenses; ties are broken by date, newest first.
+    pub fn compare(&self, a: &Expense, b: &Expense) -> Ordering {
+        let ordering = self.column.compare(a, b);
+        let ordering = if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then_with(|| b.date.cmp(&a.date))
+    }
+
+    /// The column title with an arrow if the table is sorted by it.
+    pub fn header(&self, column: Column) -> String {
+        match (column == self.column, self.descending) {
+            (false, _) => column.title().to_string(),
+            (true, true) => format!("{} ▼", column.title()),
+            (true, false) => format!("{} ▲", column.title()),
+        }
+    }
+}
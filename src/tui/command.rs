@@ -0,0 +1,73 @@
+//! The commands typed after `:`, like `:filter type:Food` or `:goto 2024-03`.
+
+use std::path::PathBuf;
+
+/// The commands with how they are written, listed when an unknown one is typed.
+pub const COMMANDS: &[&str] = &[
+    "export <file>",
+    "filter <query or saved filter>",
+    "goto <date or month>",
+    "import <file>",
+    "set <setting> <value>",
+    "quit",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Write the marked rows, or else the rows in the table, to a CSV, JSON or Markdown file
+    Export(PathBuf),
+    Filter(String),
+    /// Select the row nearest to a date, month or year
+    Goto(String),
+    /// Open the import wizard on a CSV file
+    Import(PathBuf),
+    /// Change a setting in the config file
+    Set(String, String),
+    Quit,
+}
+
+impl Command {
+    pub fn parse(input: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let input = input.trim();
+        let (name, argument) = match input.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (input, ""),
+        };
+
+        match (name, argument) {
+            ("q" | "quit", "") => Ok(Command::Quit),
+            ("export" | "filter" | "goto" | "import" | "set", "") => {
+                Err(format!("Missing argument, usage: {}", usage(name)).into())
+            }
+            ("export", file) => Ok(Command::Export(PathBuf::from(file))),
+            ("filter", query) => Ok(Command::Filter(query.to_string())),
+            ("goto", date) => Ok(Command::Goto(date.to_string())),
+            ("import", file) => Ok(Command::Import(PathBuf::from(file))),
+            // Both `set key value` and `set key=value`
+            ("set", setting) => {
+                let (key, value) = setting
+                    .split_once('=')
+                    .or_else(|| setting.split_once(char::is_whitespace))
+                    .ok_or_else(|| format!("Missing value, usage: {}", usage(name)))?;
+                Ok(Command::Set(
+                    key.trim().to_string(),
+                    value.trim().to_string(),
+                ))
+            }
+            _ => Err(format!(
+                "Unknown command '{}', commands are: {}",
+                name,
+                COMMANDS.join(", ")
+            )
+            .into()),
+        }
+    }
+}
+
+fn usage(name: &str) -> &'static str {
+    COMMANDS
+        .iter()
+        .find(|command| command.split(' ').next() == Some(name))
+        .copied()
+        .unwrap_or_default()
+}
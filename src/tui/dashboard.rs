@@ -0,0 +1,254 @@
+//! The Dashboard tab: an overview of this month's spending and where it is heading, what a day
+//! costs, the savings rate and the latest expenses.
+
+use chrono::{Days, NaiveDate};
+use ratatui::{prelude::*, widgets::*};
+use std::collections::HashMap;
+
+use budget_tracker::budget::Progress;
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, weekday_name, DateRange};
+use budget_tracker::expense::{capitalize, Expense};
+use budget_tracker::forecast::Forecast;
+use budget_tracker::savings::Savings;
+use budget_tracker::stats::Stats;
+
+use super::budgets::color;
+use super::palette::type_color;
+
+/// How many of the latest expenses are listed below the cards.
+const RECENT: usize = 10;
+
+/// How many days up to today the daily spending is worked out over.
+const STATS_DAYS: u64 = 90;
+
+pub fn render(
+    frame: &mut Frame,
+    config: &Config,
+    expenses: &[&Expense],
+    budgets: &[Progress],
+    today: NaiveDate,
+    area: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(0)])
+        .split(area);
+    let cards = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 5); 5])
+        .split(chunks[0]);
+
+    let this_month = DateRange::month_of(today).unwrap_or(DateRange::day(today));
+    let last_month = DateRange::month_before(today).unwrap_or(this_month);
+    let in_month = |expense: &Expense, month: DateRange| {
+        parse_iso(&expense.date).is_some_and(|date| month.contains(date))
+    };
+    let spent_in = |month: DateRange| -> f64 {
+        expenses
+            .iter()
+            .filter(|expense| in_month(expense, month) && expense.amount < 0.0)
+            .map(|expense| expense.amount)
+            .sum()
+    };
+    let (spent, spent_before) = (spent_in(this_month), spent_in(last_month));
+
+    let comparison = if spent_before < 0.0 {
+        let change = (spent / spent_before - 1.0) * 100.0;
+        let style = if change > 0.0 {
+            Style::default().red()
+        } else {
+            Style::default().green()
+        };
+        Line::from(vec![
+            Span::raw(format!("{} last month, ", config.money(spent_before))),
+            Span::styled(format!("{:+.0}%", change), style),
+        ])
+    } else {
+        Line::from("Nothing spent last month")
+    };
+    let mut spent_lines = vec![
+        Line::styled(config.money(spent), Style::default().bold()),
+        comparison,
+    ];
+    let savings = Savings::new(config, expenses, this_month);
+    if let Some(rate) = savings.rate() {
+        let style = if rate < 0.0 {
+            Style::default().red()
+        } else {
+            Style::default().green()
+        };
+        spent_lines.push(Line::from(vec![
+            Span::raw("Saving "),
+            Span::styled(savings.written_rate(), style),
+            Span::raw(" of the income"),
+        ]));
+    }
+    render_card(frame, "Spent this month", spent_lines, cards[0]);
+
+    let mut types: HashMap<&str, f64> = HashMap::new();
+    for expense in expenses
+        .iter()
+        .filter(|expense| in_month(expense, this_month) && expense.amount < 0.0)
+    {
+        *types.entry(&expense.expense_type).or_insert(0.0) += expense.amount;
+    }
+    let top = types
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+    let top_lines = match top {
+        Some((expense_type, amount)) => vec![
+            Line::styled(
+                capitalize(expense_type.to_string()),
+                Style::default().fg(type_color(config, expense_type)).bold(),
+            ),
+            Line::from(format!(
+                "{}, {:.0}% of the month",
+                config.money(amount),
+                amount / spent * 100.0
+            )),
+        ],
+        None => vec![Line::from("Nothing spent this month")],
+    };
+    render_card(frame, "Top category", top_lines, cards[1]);
+
+    // Regular expenses still to come are the bills, and the budgets are met or not
+    let forecast_lines = match Forecast::new(expenses, today) {
+        Some(forecast) => {
+            let mut lines = vec![
+                Line::styled(
+                    format!(
+                        "{} by {}",
+                        config.money(-forecast.projected()),
+                        forecast.month.end.format("%-d %b")
+                    ),
+                    Style::default().bold(),
+                ),
+                Line::from(match forecast.upcoming().count() {
+                    0 => "No bills to come".to_string(),
+                    count => format!(
+                        "{} in {} {} to come",
+                        config.short_money(forecast.upcoming_total()),
+                        count,
+                        if count == 1 { "bill" } else { "bills" }
+                    ),
+                }),
+            ];
+            if let Some((budgeted, budgets)) = Forecast::of_budgets(config, expenses, today) {
+                let difference = budgeted.projected() - budgets;
+                lines.push(if difference > 0.0 {
+                    Line::styled(
+                        format!("{} over budget", config.short_money(difference)),
+                        Style::default().red(),
+                    )
+                } else {
+                    Line::styled(
+                        format!("{} within budget", config.short_money(-difference)),
+                        Style::default().green(),
+                    )
+                });
+            }
+            lines
+        }
+        None => vec![Line::from("Nothing to forecast")],
+    };
+    render_card(frame, "Month-end forecast", forecast_lines, cards[2]);
+    render_budgets(frame, budgets, cards[3]);
+
+    let stats = Stats::new(
+        expenses,
+        DateRange::new(today - Days::new(STATS_DAYS - 1), today),
+    );
+    let mut stats_lines = vec![
+        Line::styled(
+            format!("{} a day", config.money(stats.daily_mean())),
+            Style::default().bold(),
+        ),
+        Line::from(format!(
+            "Median {}, 90% {}",
+            config.short_money(stats.daily_median()),
+            config.short_money(stats.daily_percentile(90.0))
+        )),
+    ];
+    if let Some((weekday, _)) = stats.busiest_weekday() {
+        stats_lines.push(Line::from(format!(
+            "{}s most, {} each",
+            weekday_name(weekday),
+            config.short_money(stats.average_expense())
+        )));
+    }
+    render_card(
+        frame,
+        &format!("Last {} days", STATS_DAYS),
+        stats_lines,
+        cards[4],
+    );
+
+    // Newest first, whatever order the table is in
+    let mut recent = expenses.to_vec();
+    recent.sort_by(|a, b| b.date.cmp(&a.date));
+    let rows = recent.iter().take(RECENT).map(|expense| {
+        Row::new(vec![
+            config.format_date(&expense.date),
+            expense.description.clone(),
+            expense.expense_type.clone(),
+            config.money(expense.amount),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Min(20),
+            Constraint::Length(20),
+            Constraint::Length(12),
+        ],
+    )
+    .header(Row::new(["Date", "Description", "Type", "Amount"]).style(Style::default().bold()))
+    .block(
+        Block::default()
+            .title("Latest expenses")
+            .borders(Borders::ALL),
+    );
+    frame.render_widget(table, chunks[1]);
+}
+
+fn render_card(frame: &mut Frame, title: &str, lines: Vec<Line>, area: Rect) {
+    let card = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().title(title).borders(Borders::ALL));
+    frame.render_widget(card, area);
+}
+
+/// The budgets closest to being spent, as many as fit.
+fn render_budgets(frame: &mut Frame, budgets: &[Progress], area: Rect) {
+    if budgets.is_empty() {
+        render_card(
+            frame,
+            "Budgets",
+            vec![Line::from("No budgets are set up yet.")],
+            area,
+        );
+        return;
+    }
+    let block = Block::default().title("Budgets").borders(Borders::ALL);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut budgets: Vec<&Progress> = budgets.iter().collect();
+    budgets.sort_by(|a, b| b.ratio().total_cmp(&a.ratio()));
+    for (line, progress) in budgets.iter().take(inner.height as usize).enumerate() {
+        let gauge = LineGauge::default()
+            .ratio(progress.ratio().min(1.0))
+            .label(format!(
+                "{:<10.10} {:>4.0}%",
+                progress.expense_type,
+                progress.ratio() * 100.0
+            ))
+            .gauge_style(Style::default().fg(color(progress)));
+        frame.render_widget(
+            gauge,
+            Rect::new(inner.x, inner.y + line as u16, inner.width, 1),
+        );
+    }
+}
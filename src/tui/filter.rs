@@ -0,0 +1,101 @@
+//! The searches and filters narrowing down the table.
+
+use budget_tracker::expense::Expense;
+use budget_tracker::index::SearchIndex;
+use budget_tracker::query::Query;
+use budget_tracker::search::{Highlight, RegexSearch, Search};
+
+/// How the text typed into the search bar is interpreted, switched with Tab.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchKind {
+    /// Word matching as with `--search`
+    Text,
+    /// The query language of `--query`
+    Query,
+    /// A regular expression as with `--search-regex`
+    Regex,
+}
+
+impl SearchKind {
+    pub fn next(self) -> Self {
+        match self {
+            SearchKind::Text => SearchKind::Query,
+            SearchKind::Query => SearchKind::Regex,
+            SearchKind::Regex => SearchKind::Text,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            SearchKind::Text => "Search",
+            SearchKind::Query => "Query",
+            SearchKind::Regex => "Regex",
+        }
+    }
+
+    pub fn parse(self, input: &str) -> Result<Filter, String> {
+        match self {
+            SearchKind::Text => Ok(Filter::Text(Search::parse(input))),
+            SearchKind::Query => Query::parse(input)
+                .map(Filter::Query)
+                .map_err(|err| err.to_string()),
+            SearchKind::Regex => RegexSearch::new(input)
+                .map(Filter::Regex)
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+/// Any of the ways to narrow down the table.
+pub enum Filter {
+    Text(Search),
+    Query(Query),
+    Regex(RegexSearch),
+}
+
+impl Filter {
+    /// Keeps the matching rows, positions into `expenses`. Text searches may reorder them by
+    /// relevance.
+    pub fn apply(&self, expenses: &[Expense], rows: &[usize], index: &SearchIndex) -> Vec<usize> {
+        match self {
+            Filter::Text(search) => search.filter_rows(expenses, rows, Some(index)),
+            Filter::Query(query) => rows
+                .iter()
+                .copied()
+                .filter(|&row| query.matches(&expenses[row]))
+                .collect(),
+            Filter::Regex(regex_search) => rows
+                .iter()
+                .copied()
+                .filter(|&row| regex_search.matches(&expenses[row]))
+                .collect(),
+        }
+    }
+
+    pub fn highlighter(&self) -> &dyn Highlight {
+        match self {
+            Filter::Text(search) => search,
+            Filter::Query(query) => query,
+            Filter::Regex(regex_search) => regex_search,
+        }
+    }
+}
+
+/// A search kept with Enter; later searches refine its results.
+pub struct ActiveFilter {
+    pub kind: SearchKind,
+    pub input: String,
+    pub filter: Filter,
+    /// Rows left after applying this and all earlier filters.
+    pub rows: Vec<usize>,
+}
+
+impl ActiveFilter {
+    /// How the filter is shown in the breadcrumb.
+    pub fn label(&self) -> String {
+        match self.kind {
+            SearchKind::Text => self.input.clone(),
+            kind => format!("{}: {}", kind.title(), self.input),
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! The form for entering an expense inside the TUI.
+
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use budget_tracker::date::{parse_input_date, DateOrder};
+use budget_tracker::expense::Expense;
+
+use super::centered_rect;
+use super::column::Column;
+use super::widgets::{Calendar, CalendarAction, Picker, PickerAction, TextInput};
+
+const LABELS: [&str; 5] = ["Date", "Description", "Type", "Amount", "Tags"];
+const DATE: usize = 0;
+const DESCRIPTION: usize = 1;
+const TYPE: usize = 2;
+const AMOUNT: usize = 3;
+const TAGS: usize = 4;
+
+/// What the form wants to happen after a key press.
+pub enum FormAction {
+    Continue,
+    Submit(Expense),
+    Cancel,
+}
+
+/// A modal form with one text field per column of the database.
+pub struct ExpenseForm {
+    pub title: String,
+    /// The row being edited, a position into the expenses, or `None` when adding.
+    pub row: Option<usize>,
+    /// The fields, each with why it can't be saved as it is
+    pub values: [TextInput; 5],
+    /// The values the form was opened with, to tell whether anything was typed
+    original: [String; 5],
+    pub focus: usize,
+    /// Edits only the focused field, drawn over its cell in the table
+    pub inline: bool,
+    /// Picking the date, opened with Ctrl-d on the date field
+    calendar: Option<Calendar>,
+    /// The types to pick from, the most used first
+    pub types: Vec<String>,
+    /// Picking the type, opened with Ctrl-d on the type field
+    picker: Option<Picker>,
+}
+
+impl ExpenseForm {
+    /// An empty form, with the date set to today and the type to the default one.
+    pub fn new(title: &str, default_type: Option<&str>) -> Self {
+        let mut values: [String; 5] = Default::default();
+        values[DATE] = Local::now().format("%Y-%m-%d").to_string();
+        values[TYPE] = default_type.unwrap_or_default().to_string();
+        Self {
+            title: title.to_string(),
+            row: None,
+            values: values.clone().map(TextInput::with_value),
+            original: values,
+            focus: DESCRIPTION,
+            inline: false,
+            calendar: None,
+            types: Vec::new(),
+            picker: None,
+        }
+    }
+
+    /// A form to add a copy of the expense, dated today.
+    pub fn duplicate(title: &str, expense: &Expense) -> Self {
+        let mut form = Self::edit(title, 0, expense);
+        form.row = None;
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        form.values[DATE] = TextInput::with_value(today.as_str());
+        form.original[DATE] = today;
+        form.focus = AMOUNT;
+        form
+    }
+
+    /// A form filled in with the expense at `row`, to edit it.
+    pub fn edit(title: &str, row: usize, expense: &Expense) -> Self {
+        let values = [
+            expense.date.clone(),
+            expense.description.clone(),
+            expense.expense_type.clone(),
+            expense.amount.to_string(),
+            expense.tags.join(" "),
+        ];
+        Self {
+            title: title.to_string(),
+            row: Some(row),
+            values: values.clone().map(TextInput::with_value),
+            original: values,
+            focus: DESCRIPTION,
+            inline: false,
+            calendar: None,
+            types: Vec::new(),
+            picker: None,
+        }
+    }
+
+    /// Edits a single cell of the expense at `row`, the other fields are kept as they are.
+    pub fn cell(row: usize, expense: &Expense, column: Column) -> Self {
+        let mut form = Self::edit("", row, expense);
+        form.focus = match column {
+            Column::Date => DATE,
+            Column::Description => DESCRIPTION,
+            Column::Type => TYPE,
+            Column::Amount => AMOUNT,
+            Column::Tags => TAGS,
+        };
+        form.inline = true;
+        form
+    }
+
+    /// Whether any field was changed since the form was opened.
+    pub fn is_changed(&self) -> bool {
+        self.values
+            .iter()
+            .zip(&self.original)
+            .any(|(input, original)| input.value() != original)
+    }
+
+    /// Shows why the form couldn't be saved next to the focused field.
+    pub fn set_error(&mut self, err: String) {
+        self.values[self.focus].error = Some(err);
+    }
+
+    pub fn handle_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        date_order: DateOrder,
+    ) -> FormAction {
+        if let Some(calendar) = self.calendar.as_mut() {
+            match calendar.handle_key(code) {
+                CalendarAction::Continue => {}
+                CalendarAction::Pick(date) => {
+                    self.values[DATE] = TextInput::with_value(date.format("%Y-%m-%d").to_string());
+                    self.calendar = None;
+                }
+                CalendarAction::Cancel => self.calendar = None,
+            }
+            return FormAction::Continue;
+        }
+        if let Some(picker) = self.picker.as_mut() {
+            match picker.handle_key(code, modifiers) {
+                PickerAction::Continue => {}
+                PickerAction::Pick(expense_type) => {
+                    self.values[TYPE] = TextInput::with_value(expense_type);
+                    self.picker = None;
+                }
+                PickerAction::Cancel => self.picker = None,
+            }
+            return FormAction::Continue;
+        }
+
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Esc => return FormAction::Cancel,
+            KeyCode::Char('d') if ctrl && self.focus == DATE => {
+                // Starting from the date typed so far
+                let today = Local::now().date_naive();
+                let date =
+                    parse_input_date(self.values[DATE].value(), today, date_order).unwrap_or(today);
+                self.calendar = Some(Calendar::new(date, today));
+            }
+            KeyCode::Char('d') if ctrl && self.focus == TYPE => {
+                let choices = self
+                    .types
+                    .iter()
+                    .map(|expense_type| (expense_type.clone(), expense_type.clone()))
+                    .collect();
+                self.picker = Some(Picker::new("Type", choices, self.values[TYPE].value()));
+            }
+            KeyCode::Enter => match self.validate(date_order) {
+                Ok(expense) => return FormAction::Submit(expense),
+                Err((field, err)) => {
+                    self.focus = field;
+                    self.set_error(err);
+                }
+            },
+            KeyCode::Tab | KeyCode::Down if !self.inline => {
+                self.focus = (self.focus + 1) % LABELS.len()
+            }
+            KeyCode::BackTab | KeyCode::Up if !self.inline => {
+                self.focus = (self.focus + LABELS.len() - 1) % LABELS.len()
+            }
+            _ => {
+                self.values[self.focus].handle_key(code, modifiers);
+            }
+        }
+        FormAction::Continue
+    }
+
+    /// Builds the expense, or returns the field to fix and why.
+    fn validate(&self, date_order: DateOrder) -> Result<Expense, (usize, String)> {
+        let today = Local::now().date_naive();
+        let date = parse_input_date(self.values[DATE].value(), today, date_order).ok_or((
+            DATE,
+            "Invalid date, use e.g. 2024-06-12, yesterday, mon, -3d or 12/6".to_string(),
+        ))?;
+
+        let description = self.values[DESCRIPTION].value().trim();
+        if description.is_empty() {
+            return Err((DESCRIPTION, "The description can't be empty".to_string()));
+        }
+
+        let expense_type = self.values[TYPE].value().trim();
+        if expense_type.is_empty() {
+            return Err((TYPE, "The type can't be empty".to_string()));
+        }
+
+        // Commas would break the columns of the database
+        for field in [DESCRIPTION, TYPE, TAGS] {
+            if self.values[field].value().contains(',') {
+                return Err((field, format!("The {} can't contain commas", LABELS[field])));
+            }
+        }
+
+        let amount = self.values[AMOUNT]
+            .value()
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| (AMOUNT, "The amount has to be a number".to_string()))?;
+
+        let mut expense = Expense::new(
+            date.format("%Y-%m-%d").to_string(),
+            description.to_string(),
+            expense_type.to_string(),
+            amount,
+        );
+        expense.tags = Expense::parse_tags(self.values[TAGS].value());
+        Ok(expense)
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = centered_rect(60, LABELS.len() as u16 + 5, frame.size());
+        frame.render_widget(Clear, area);
+
+        let mut lines: Vec<Line> = LABELS
+            .iter()
+            .zip(&self.values)
+            .enumerate()
+            .map(|(index, (label, value))| {
+                let label_style = if value.error.is_some() {
+                    Style::default().red().bold()
+                } else if index == self.focus {
+                    Style::default().yellow().bold()
+                } else {
+                    Style::default().bold()
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<12}", label), label_style),
+                    Span::raw(value.value()),
+                ])
+            })
+            .collect();
+        lines.push(Line::raw(""));
+        lines.push(
+            match self.values.iter().find_map(|value| value.error.as_ref()) {
+                Some(err) => Line::styled(err.as_str(), Style::default().red()),
+                None if self.focus == DATE => Line::styled(
+                    "Ctrl-d: calendar, Tab: next field, Enter: save, Esc: cancel",
+                    Style::default().dark_gray(),
+                ),
+                None if self.focus == TYPE => Line::styled(
+                    "Ctrl-d: pick a type, Tab: next field, Enter: save, Esc: cancel",
+                    Style::default().dark_gray(),
+                ),
+                None => Line::styled(
+                    "Tab: next field, Enter: save, Esc: cancel",
+                    Style::default().dark_gray(),
+                ),
+            },
+        );
+
+        let form = Paragraph::new(lines).block(
+            Block::default()
+                .title(self.title.as_str())
+                .borders(Borders::ALL),
+        );
+        frame.render_widget(form, area);
+
+        frame.set_cursor(
+            area.x + 13 + self.values[self.focus].cursor_offset(),
+            area.y + 1 + self.focus as u16,
+        );
+        if let Some(calendar) = &self.calendar {
+            calendar.render(frame, area.x + 13, area.y + 2);
+        }
+        if let Some(picker) = &self.picker {
+            picker.render(frame, area.x + 12, area.y + 2 + TYPE as u16);
+        }
+    }
+
+    /// Draws the field being edited inline over its cell, with the error below it if any.
+    pub fn render_cell(&self, frame: &mut Frame, area: Rect) {
+        let input = &self.values[self.focus];
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(input.value()).style(Style::default().yellow().underlined()),
+            area,
+        );
+
+        if let Some(err) = &input.error {
+            let screen = frame.size();
+            if area.bottom() < screen.bottom() {
+                let width = (err.chars().count() as u16).min(screen.right() - area.x);
+                let below = Rect::new(area.x, area.bottom(), width, 1);
+                frame.render_widget(Clear, below);
+                frame.render_widget(Paragraph::new(err.as_str()).red(), below);
+            }
+        }
+
+        let cursor = input.cursor_offset().min(area.width.saturating_sub(1));
+        frame.set_cursor(area.x + cursor, area.y);
+        if let Some(calendar) = &self.calendar {
+            calendar.render(frame, area.x, area.bottom());
+        }
+        if let Some(picker) = &self.picker {
+            picker.render(frame, area.x, area.bottom());
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Grouping the rows of the table under a header line per month or type, with their total.
+
+use chrono::NaiveDate;
+use std::collections::{BTreeSet, HashMap};
+
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::Expense;
+
+/// How many weeks the sparkline beside each type covers.
+const SPARKLINE_WEEKS: i64 = 12;
+
+/// The bars of a sparkline, from the least to the most spent in a week.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How the rows of the table are grouped, switched with `m`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Grouping {
+    #[default]
+    None,
+    Month,
+    /// By type, biggest spending first and folded, to compare the totals
+    Type,
+}
+
+impl Grouping {
+    pub fn next(self) -> Self {
+        match self {
+            Grouping::None => Grouping::Month,
+            Grouping::Month => Grouping::Type,
+            Grouping::Type => Grouping::None,
+        }
+    }
+
+    /// The group of an expense, e.g. `June 2024` by month. Months are the configured ones,
+    /// named after the month they start in.
+    fn key(self, expense: &Expense) -> String {
+        match self {
+            Grouping::None => String::new(),
+            Grouping::Month => match Self::month(expense) {
+                Some(month) => month.month_name(),
+                None => expense.date.clone(),
+            },
+            Grouping::Type => expense.expense_type.clone(),
+        }
+    }
+
+    /// What the groups are ordered by, the first day of the month by month.
+    fn order(self, expense: &Expense) -> String {
+        match self {
+            Grouping::Month => match Self::month(expense) {
+                Some(month) => month.start.to_string(),
+                None => expense.date.clone(),
+            },
+            _ => self.key(expense),
+        }
+    }
+
+    fn month(expense: &Expense) -> Option<DateRange> {
+        DateRange::month_of(parse_iso(&expense.date)?)
+    }
+
+    /// Whether groups start out folded, showing only their header.
+    fn folded(self) -> bool {
+        self == Grouping::Type
+    }
+}
+
+/// A line of the table: a group's header or an expense.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableLine {
+    Group {
+        key: String,
+        count: usize,
+        total: f64,
+        collapsed: bool,
+    },
+    /// A position into the expenses
+    Expense(usize),
+}
+
+/// The lines of the table for the rows in display order. Months are ordered newest first unless
+/// `ascending`, and types by their total. Groups keep the order of their rows; the ones in
+/// `toggled` are folded if groups start unfolded and the other way around.
+pub fn lines(
+    grouping: Grouping,
+    rows: &[usize],
+    expenses: &[Expense],
+    ascending: bool,
+    toggled: &BTreeSet<String>,
+) -> Vec<TableLine> {
+    if grouping == Grouping::None {
+        return rows.iter().map(|&row| TableLine::Expense(row)).collect();
+    }
+
+    let mut rows = rows.to_vec();
+    rows.sort_by(|&a, &b| {
+        let ordering = grouping
+            .order(&expenses[a])
+            .cmp(&grouping.order(&expenses[b]));
+        if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+
+    let mut groups: Vec<(&[usize], f64)> = rows
+        .chunk_by(|&a, &b| grouping.key(&expenses[a]) == grouping.key(&expenses[b]))
+        .map(|group| (group, group.iter().map(|&row| expenses[row].amount).sum()))
+        .collect();
+    if grouping == Grouping::Type {
+        groups.sort_by(|a, b| a.1.total_cmp(&b.1));
+    }
+
+    let mut lines = Vec::new();
+    for (group, total) in groups {
+        let key = grouping.key(&expenses[group[0]]);
+        let collapsed = grouping.folded() != toggled.contains(&key);
+        lines.push(TableLine::Group {
+            count: group.len(),
+            total,
+            key,
+            collapsed,
+        });
+        if !collapsed {
+            lines.extend(group.iter().map(|&row| TableLine::Expense(row)));
+        }
+    }
+    lines
+}
+
+/// A bar per week of the last 12 up to the one of `today` with what was spent on each type of the
+/// `rows`, oldest first, shown beside the types when grouping by type. Weeks with nothing spent
+/// are left blank.
+pub fn sparklines(
+    rows: &[usize],
+    expenses: &[Expense],
+    today: NaiveDate,
+) -> HashMap<String, String> {
+    let mut weeks: HashMap<&str, [f64; SPARKLINE_WEEKS as usize]> = HashMap::new();
+    for &row in rows {
+        let expense = &expenses[row];
+        let Some(date) = parse_iso(&expense.date) else {
+            continue;
+        };
+        let weeks_ago = (DateRange::week(today).start - DateRange::week(date).start).num_days() / 7;
+        if expense.amount < 0.0 && date <= today && (0..SPARKLINE_WEEKS).contains(&weeks_ago) {
+            let week = (SPARKLINE_WEEKS - 1 - weeks_ago) as usize;
+            weeks.entry(&expense.expense_type).or_default()[week] -= expense.amount;
+        }
+    }
+    weeks
+        .into_iter()
+        .map(|(expense_type, spent)| (expense_type.to_string(), sparkline(&spent)))
+        .collect()
+}
+
+/// A bar per value as high as it is next to the largest, e.g. `▁▃█`. Values of zero or less are
+/// left blank.
+pub fn sparkline(values: &[f64]) -> String {
+    let most = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|&value| match value {
+            value if value <= 0.0 => ' ',
+            value => SPARKS[(value / most * (SPARKS.len() - 1) as f64).round() as usize],
+        })
+        .collect()
+}
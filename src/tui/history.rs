@@ -0,0 +1,140 @@
+//! Undo and redo of the changes made to the expenses during a session.
+
+use budget_tracker::expense::Expense;
+
+/// A change to the database, with everything needed to reverse it. Rows are positions into the
+/// expenses in ascending order, as they are before a removal and after an insertion.
+#[derive(Debug, Clone)]
+pub enum Change {
+    Insert(Vec<(usize, Expense)>),
+    Remove(Vec<(usize, Expense)>),
+    /// The rows with their expense before and after the change
+    Replace(Vec<(usize, Expense, Expense)>),
+}
+
+impl Change {
+    pub fn inverse(&self) -> Change {
+        match self {
+            Change::Insert(rows) => Change::Remove(rows.clone()),
+            Change::Remove(rows) => Change::Insert(rows.clone()),
+            Change::Replace(rows) => Change::Replace(
+                rows.iter()
+                    .map(|(row, before, after)| (*row, after.clone(), before.clone()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Writes the change to the database file, then makes it to `expenses`. Removed rows are
+    /// kept in the trash with `trash`, for the deletions made by the user, but not when undoing
+    /// an addition or redoing a deletion, whose rows are in the trash already.
+    pub fn apply(
+        &self,
+        file_name: &str,
+        expenses: &mut Vec<Expense>,
+        trash: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Change::Insert(rows) => {
+                let inserted: Vec<(usize, &Expense)> =
+                    rows.iter().map(|(row, expense)| (*row, expense)).collect();
+                Expense::insert_rows_in_csv(file_name, &inserted)?;
+                for (row, expense) in rows {
+                    expenses.insert(*row, expense.clone());
+                }
+            }
+            Change::Remove(rows) => {
+                if trash {
+                    let removed: Vec<(usize, &Expense)> =
+                        rows.iter().map(|(row, expense)| (*row, expense)).collect();
+                    Expense::delete_from_csv(file_name, &removed)?;
+                } else {
+                    let changes = rows.iter().map(|(row, _)| (*row, None)).collect();
+                    Expense::replace_rows_in_csv(file_name, &changes)?;
+                }
+                for (row, _) in rows.iter().rev() {
+                    expenses.remove(*row);
+                }
+            }
+            Change::Replace(rows) => {
+                let changes = rows
+                    .iter()
+                    .map(|(row, _, after)| (*row, Some(after)))
+                    .collect();
+                Expense::replace_rows_in_csv(file_name, &changes)?;
+                for (row, _, after) in rows {
+                    expenses[*row] = after.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The rows worth selecting after the change is made, positions into the expenses.
+    pub fn rows(&self) -> Vec<usize> {
+        match self {
+            Change::Insert(rows) | Change::Remove(rows) => {
+                rows.iter().map(|(row, _)| *row).collect()
+            }
+            Change::Replace(rows) => rows.iter().map(|(row, _, _)| *row).collect(),
+        }
+    }
+
+    /// What the change did, e.g. "deleted 2 expenses".
+    pub fn describe(&self) -> String {
+        let (verb, count) = match self {
+            Change::Insert(rows) => ("added", rows.len()),
+            Change::Remove(rows) => ("deleted", rows.len()),
+            Change::Replace(rows) => ("edited", rows.len()),
+        };
+        match count {
+            1 => format!("{} 1 expense", verb),
+            count => format!("{} {} expenses", verb, count),
+        }
+    }
+}
+
+/// The changes made so far, and the ones undone since.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl History {
+    /// How many changes can be undone.
+    pub fn changes(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Remembers a change that was just made. Undone changes can't be redone after it.
+    pub fn record(&mut self, change: Change) {
+        self.undo.push(change);
+        self.redo.clear();
+    }
+
+    /// The last change, to reverse. It is then remembered for redo.
+    pub fn undo(&mut self) -> Option<Change> {
+        let change = self.undo.pop()?;
+        self.redo.push(change.clone());
+        Some(change)
+    }
+
+    /// The last undone change, to make again.
+    pub fn redo(&mut self) -> Option<Change> {
+        let change = self.redo.pop()?;
+        self.undo.push(change.clone());
+        Some(change)
+    }
+
+    /// Puts back a change that couldn't be undone or redone, so it can be tried again.
+    pub fn restore(&mut self, undo: bool) {
+        if undo {
+            if let Some(change) = self.redo.pop() {
+                self.undo.push(change);
+            }
+        } else if let Some(change) = self.undo.pop() {
+            self.redo.push(change);
+        }
+    }
+}
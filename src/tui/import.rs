@@ -0,0 +1,442 @@
+//! The import wizard: picking a CSV file, saying which column holds what, then going over the
+//! rows before they are added.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+use std::path::PathBuf;
+
+use budget_tracker::date::DateOrder;
+use budget_tracker::expense::Expense;
+use budget_tracker::import::{is_duplicate, Field, Statement};
+
+use super::centered_rect;
+use super::widgets::{Picker, PickerAction, TextInput};
+
+/// What the wizard wants to happen after a key press.
+pub enum ImportAction {
+    Continue,
+    /// Ask whether to add the kept rows
+    Confirm,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    /// Typing the path of the file
+    File,
+    /// Choosing what each column holds
+    Columns,
+    /// Choosing the rows to add and their types
+    Review,
+}
+
+/// A row of the file as it would be added.
+struct ImportRow {
+    /// The expense, or why the row can't be read
+    expense: Result<Expense, String>,
+    /// Whether an expense like it is already in the database
+    duplicate: bool,
+    keep: bool,
+}
+
+pub struct ImportWizard {
+    step: Step,
+    path: TextInput,
+    statement: Option<Statement>,
+    /// What each column of the file holds
+    fields: Vec<Field>,
+    /// The column selected while choosing the fields
+    column: usize,
+    /// Why the columns can't be used as chosen
+    error: Option<String>,
+    rows: Vec<ImportRow>,
+    table_state: TableState,
+    /// The types to pick from, the most used first
+    types: Vec<String>,
+    /// Picking a type, for every kept row without one if the flag is set
+    picker: Option<(Picker, bool)>,
+    date_order: DateOrder,
+}
+
+impl ImportWizard {
+    /// The wizard asking for the file to import.
+    pub fn new(types: Vec<String>, date_order: DateOrder) -> Self {
+        Self {
+            step: Step::File,
+            path: TextInput::default(),
+            statement: None,
+            fields: Vec::new(),
+            column: 0,
+            error: None,
+            rows: Vec::new(),
+            table_state: TableState::default(),
+            types,
+            picker: None,
+            date_order,
+        }
+    }
+
+    /// Reads the file, going on to choose its columns, or shows why it can't be read.
+    pub fn open(&mut self, path: &str) {
+        self.path = TextInput::with_value(path);
+        match Statement::read(&PathBuf::from(path.trim())) {
+            Ok(statement) if statement.rows.is_empty() => {
+                self.path.error = Some("The file has no rows below its titles".to_string())
+            }
+            Ok(statement) => {
+                self.fields = statement.guess_fields();
+                self.statement = Some(statement);
+                self.column = 0;
+                self.error = None;
+                self.step = Step::Columns;
+            }
+            Err(err) => self.path.error = Some(format!("Couldn't read the file: {}", err)),
+        }
+    }
+
+    /// The kept rows, in the order of the file.
+    pub fn kept(&self) -> Vec<Expense> {
+        self.rows
+            .iter()
+            .filter(|row| row.keep)
+            .filter_map(|row| row.expense.as_ref().ok().cloned())
+            .collect()
+    }
+
+    /// How many rows are left out for being duplicates, and for not being readable.
+    pub fn skipped(&self) -> (usize, usize) {
+        let skipped = self.rows.iter().filter(|row| !row.keep);
+        let duplicates = skipped.clone().filter(|row| row.duplicate).count();
+        let unreadable = skipped.filter(|row| row.expense.is_err()).count();
+        (duplicates, unreadable)
+    }
+
+    /// Reads the rows with the chosen columns, leaving out the ones already in `expenses`.
+    fn review(&mut self, expenses: &[Expense]) {
+        let Some(statement) = &self.statement else {
+            return;
+        };
+        let read = statement.expenses(&self.fields, self.date_order);
+        // Nothing read at all is most likely a wrong column
+        if let Some(Err(err)) = read.first().filter(|_| read.iter().all(Result::is_err)) {
+            self.error = Some(err.clone());
+            return;
+        }
+        self.rows = read
+            .into_iter()
+            .map(|expense| {
+                let duplicate = expense
+                    .as_ref()
+                    .is_ok_and(|expense| is_duplicate(expense, expenses));
+                ImportRow {
+                    keep: expense.is_ok() && !duplicate,
+                    duplicate,
+                    expense,
+                }
+            })
+            .collect();
+        self.table_state.select(Some(0));
+        self.error = None;
+        self.step = Step::Review;
+    }
+
+    /// Sets the type of the selected row, or with `all` of every kept row without one.
+    fn set_type(&mut self, expense_type: &str, all: bool) {
+        let selected = self.table_state.selected().unwrap_or(0);
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            let Ok(expense) = row.expense.as_mut() else {
+                continue;
+            };
+            let wanted = if all {
+                row.keep && expense.expense_type.is_empty()
+            } else {
+                index == selected
+            };
+            if wanted {
+                expense.expense_type = expense_type.to_string();
+                if !all {
+                    row.keep = true;
+                }
+            }
+        }
+    }
+
+    fn open_picker(&mut self, all: bool) {
+        let title = if all {
+            "Type of the rows without one"
+        } else {
+            "Type of the row"
+        };
+        let choices = self
+            .types
+            .iter()
+            .map(|expense_type| (expense_type.clone(), expense_type.clone()))
+            .collect();
+        self.picker = Some((Picker::new(title, choices, ""), all));
+    }
+
+    pub fn handle_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        expenses: &[Expense],
+    ) -> ImportAction {
+        if let Some((picker, all)) = self.picker.as_mut() {
+            match picker.handle_key(code, modifiers) {
+                PickerAction::Continue => {}
+                PickerAction::Pick(expense_type) => {
+                    let all = *all;
+                    self.picker = None;
+                    self.set_type(&expense_type, all);
+                }
+                PickerAction::Cancel => self.picker = None,
+            }
+            return ImportAction::Continue;
+        }
+
+        match self.step {
+            Step::File => match code {
+                KeyCode::Enter => {
+                    let path = self.path.take();
+                    self.open(&path);
+                }
+                KeyCode::Esc => return ImportAction::Cancel,
+                _ => {
+                    self.path.handle_key(code, modifiers);
+                }
+            },
+            Step::Columns => {
+                let count = self.fields.len().max(1);
+                match code {
+                    KeyCode::Enter => self.review(expenses),
+                    KeyCode::Esc => self.step = Step::File,
+                    KeyCode::Right | KeyCode::Tab | KeyCode::Char('l') => {
+                        self.column = (self.column + 1) % count
+                    }
+                    KeyCode::Left | KeyCode::BackTab | KeyCode::Char('h') => {
+                        self.column = (self.column + count - 1) % count
+                    }
+                    KeyCode::Down | KeyCode::Char(' ') | KeyCode::Char('j') => {
+                        self.cycle_field(true)
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => self.cycle_field(false),
+                    _ => {}
+                }
+            }
+            Step::Review => {
+                let len = self.rows.len();
+                let selected = self.table_state.selected().unwrap_or(0);
+                match code {
+                    KeyCode::Enter if !self.kept().is_empty() => return ImportAction::Confirm,
+                    KeyCode::Esc => self.step = Step::Columns,
+                    KeyCode::Down | KeyCode::Char('j') if len > 0 => {
+                        self.table_state.select(Some((selected + 1).min(len - 1)))
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.table_state.select(Some(selected.saturating_sub(1)))
+                    }
+                    KeyCode::Char(' ') => {
+                        if let Some(row) = self.rows.get_mut(selected) {
+                            row.keep = !row.keep && row.expense.is_ok();
+                        }
+                    }
+                    KeyCode::Char('t') => self.open_picker(false),
+                    KeyCode::Char('T') => self.open_picker(true),
+                    _ => {}
+                }
+            }
+        }
+        ImportAction::Continue
+    }
+
+    /// Changes what the selected column holds. A field held by another column is moved here.
+    fn cycle_field(&mut self, forward: bool) {
+        let Some(&field) = self.fields.get(self.column) else {
+            return;
+        };
+        let field = if forward {
+            field.next()
+        } else {
+            field.previous()
+        };
+        if field != Field::Ignore {
+            for other in self.fields.iter_mut().filter(|other| **other == field) {
+                *other = Field::Ignore;
+            }
+        }
+        self.fields[self.column] = field;
+        self.error = None;
+    }
+
+    /// Inserts pasted text into the path of the file.
+    pub fn paste(&mut self, text: &str) {
+        if self.step == Step::File && self.picker.is_none() {
+            self.path.paste(text);
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let screen = frame.size();
+        let area = match self.step {
+            Step::File => centered_rect(70, 5, screen),
+            _ => centered_rect(
+                screen.width.saturating_sub(8).min(110),
+                screen.height.saturating_sub(4),
+                screen,
+            ),
+        };
+        frame.render_widget(Clear, area);
+        let title = match self.step {
+            Step::File => "Import: file".to_string(),
+            Step::Columns => "Import: what each column holds".to_string(),
+            Step::Review => {
+                let (duplicates, unreadable) = self.skipped();
+                format!(
+                    "Import: {} rows to add, {} duplicates, {} unreadable",
+                    self.kept().len(),
+                    duplicates,
+                    unreadable
+                )
+            }
+        };
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        if inner.height < 2 {
+            return;
+        }
+        let hint_area = Rect::new(inner.x, inner.bottom() - 1, inner.width, 1);
+        let content = Rect::new(inner.x, inner.y, inner.width, inner.height - 1);
+
+        let (error, hint) = match self.step {
+            Step::File => (
+                self.path.error.as_deref(),
+                "Enter: read the file, Esc: cancel",
+            ),
+            Step::Columns => (
+                self.error.as_deref(),
+                "Left/Right: column, Up/Down: what it holds, Enter: next, Esc: back",
+            ),
+            Step::Review => (
+                None,
+                "Space: keep or skip, t: type, T: type of rows without one, Enter: import, Esc: back",
+            ),
+        };
+        let hint = match error {
+            Some(err) => Line::styled(err, Style::default().red()),
+            None => Line::styled(hint, Style::default().dark_gray()),
+        };
+        frame.render_widget(Paragraph::new(hint), hint_area);
+
+        match self.step {
+            Step::File => {
+                frame.render_widget(
+                    Paragraph::new(Line::from(vec![
+                        Span::styled("Path  ", Style::default().bold()),
+                        Span::raw(self.path.value()),
+                    ])),
+                    content,
+                );
+                if self.picker.is_none() {
+                    frame.set_cursor(content.x + 6 + self.path.cursor_offset(), content.y);
+                }
+            }
+            Step::Columns => self.render_columns(frame, content),
+            Step::Review => self.render_review(frame, content),
+        }
+
+        if let Some((picker, _)) = &self.picker {
+            let area = centered_rect(Picker::WIDTH, picker.height(), screen);
+            picker.render(frame, area.x, area.y);
+        }
+    }
+
+    /// The first rows of the file under the titles, with what each column holds above them.
+    fn render_columns(&self, frame: &mut Frame, area: Rect) {
+        let Some(statement) = &self.statement else {
+            return;
+        };
+        let widths: Vec<Constraint> = (0..statement.titles.len())
+            .map(|column| {
+                let values = std::iter::once(&statement.titles)
+                    .chain(&statement.rows)
+                    .filter_map(|row| row.get(column));
+                let width = values.map(|value| value.chars().count()).max().unwrap_or(0);
+                Constraint::Length(width.clamp(11, 30) as u16)
+            })
+            .collect();
+        let header = Row::new(self.fields.iter().enumerate().map(|(column, field)| {
+            let mut style = match field {
+                Field::Ignore => Style::default().dark_gray(),
+                _ => Style::default().yellow().bold(),
+            };
+            if column == self.column {
+                style = style.reversed();
+            }
+            Cell::from(field.label()).style(style)
+        }));
+        let rows = std::iter::once(
+            Row::new(statement.titles.iter().map(String::as_str)).style(Style::default().bold()),
+        )
+        .chain(
+            statement
+                .rows
+                .iter()
+                .map(|row| Row::new(row.iter().map(String::as_str))),
+        );
+        frame.render_widget(Table::new(rows, widths).header(header), area);
+    }
+
+    /// The rows as they would be added, with the ones left out dimmed.
+    fn render_review(&self, frame: &mut Frame, area: Rect) {
+        let rows = self.rows.iter().map(|row| {
+            let keep = if row.keep { "[x]" } else { "[ ]" };
+            let style = if row.keep {
+                Style::default()
+            } else {
+                Style::default().dark_gray()
+            };
+            match &row.expense {
+                Ok(expense) => {
+                    let note = if row.duplicate { "duplicate" } else { "" };
+                    let expense_type = if expense.expense_type.is_empty() {
+                        Cell::from("?").style(Style::default().yellow())
+                    } else {
+                        Cell::from(expense.expense_type.as_str())
+                    };
+                    Row::new(vec![
+                        Cell::from(keep),
+                        Cell::from(expense.date.as_str()),
+                        Cell::from(expense.description.as_str()),
+                        expense_type,
+                        Cell::from(format!("{:.2}", expense.amount)),
+                        Cell::from(note),
+                    ])
+                    .style(style)
+                }
+                Err(err) => Row::new(vec![
+                    Cell::from(keep),
+                    Cell::from(""),
+                    Cell::from(Line::styled(err.as_str(), Style::default().red())),
+                ])
+                .style(style),
+            }
+        });
+        let widths = [
+            Constraint::Length(3),
+            Constraint::Length(10),
+            Constraint::Min(20),
+            Constraint::Length(14),
+            Constraint::Length(10),
+            Constraint::Length(9),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["", "Date", "Description", "Type", "Amount", ""])
+                    .style(Style::default().bold()),
+            )
+            .highlight_style(Style::default().reversed());
+        let mut table_state = self.table_state.clone();
+        frame.render_stateful_widget(table, area, &mut table_state);
+    }
+}
@@ -0,0 +1,503 @@
+//! The keys of the table view and what they do, also listed by the help overlay. The defaults
+//! can be replaced per action in the `[keys]` section of the config.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::collections::BTreeMap;
+
+/// Everything a key can do in the table view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    GotoDate,
+    Details,
+    Search,
+    FilterMenu,
+    PopFilter,
+    CategoryFilter,
+    SortNext,
+    SortReverse,
+    ToggleColumn,
+    Group,
+    NextPeriod,
+    PreviousPeriod,
+    Add,
+    Edit,
+    EditCell,
+    PreviousColumn,
+    NextColumn,
+    Duplicate,
+    Delete,
+    Undo,
+    Redo,
+    Mark,
+    ClearMarks,
+    Retype,
+    Retag,
+    Triage,
+    Export,
+    ExportView,
+    Import,
+    HideAmounts,
+    SwitchChart,
+    ChartMetric,
+    FocusCharts,
+    CopyText,
+    CopyCsv,
+    NextTab,
+    PreviousTab,
+    Command,
+    Help,
+    Quit,
+}
+
+impl Action {
+    /// Whether the action changes the database, which a read only instance can't.
+    pub fn writes(self) -> bool {
+        matches!(
+            self,
+            Action::Add
+                | Action::Edit
+                | Action::EditCell
+                | Action::Duplicate
+                | Action::Delete
+                | Action::Undo
+                | Action::Redo
+                | Action::Retype
+                | Action::Retag
+                | Action::Triage
+                | Action::Import
+        )
+    }
+
+    /// The name of the action in the config, e.g. `page-down`.
+    pub fn name(self) -> String {
+        let mut name = String::new();
+        for c in format!("{:?}", self).chars() {
+            if c.is_uppercase() && !name.is_empty() {
+                name.push('-');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+        name
+    }
+}
+
+/// A key, optionally pressed after another one as in `gg`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Key {
+    pub prefix: Option<char>,
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+const fn key(code: KeyCode) -> Key {
+    Key {
+        prefix: None,
+        code,
+        ctrl: false,
+    }
+}
+
+const fn char(c: char) -> Key {
+    key(KeyCode::Char(c))
+}
+
+const fn ctrl(c: char) -> Key {
+    Key {
+        prefix: None,
+        code: KeyCode::Char(c),
+        ctrl: true,
+    }
+}
+
+const fn after(prefix: char, c: char) -> Key {
+    Key {
+        prefix: Some(prefix),
+        code: KeyCode::Char(c),
+        ctrl: false,
+    }
+}
+
+impl Key {
+    /// How the key is written in the help, e.g. `Ctrl-r` or `gg`.
+    pub fn label(&self) -> String {
+        let code = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Down => "Down".to_string(),
+            KeyCode::Up => "Up".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Delete".to_string(),
+            KeyCode::BackTab => "Shift-Tab".to_string(),
+            code => format!("{:?}", code),
+        };
+        match (self.prefix, self.ctrl) {
+            (Some(prefix), _) => format!("{}{}", prefix, code),
+            (None, true) => format!("Ctrl-{}", code),
+            (None, false) => code,
+        }
+    }
+}
+
+/// Reads a key written as in the help, e.g. `x`, `Ctrl-r`, `gg`, `PgDn` or `Space`.
+pub fn parse_key(label: &str) -> Option<Key> {
+    let code = match label {
+        "Space" => KeyCode::Char(' '),
+        "Down" => KeyCode::Down,
+        "Up" => KeyCode::Up,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "PgDn" => KeyCode::PageDown,
+        "PgUp" => KeyCode::PageUp,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "Shift-Tab" => KeyCode::BackTab,
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        _ => {
+            let chars: Vec<char> = label.chars().collect();
+            return match chars.as_slice() {
+                [c] => Some(char(*c)),
+                [prefix, c] => Some(after(*prefix, *c)),
+                ['C', 't', 'r', 'l', '-', c] => Some(ctrl(*c)),
+                _ => None,
+            };
+        }
+    };
+    Some(key(code))
+}
+
+pub struct Binding {
+    pub keys: &'static [Key],
+    pub action: Action,
+    pub description: &'static str,
+}
+
+/// The keys of the table view. Motions can be preceded by a count, as in `5j`.
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        keys: &[key(KeyCode::Down), char('j'), char('s')],
+        action: Action::Down,
+        description: "Move down, or a count of rows",
+    },
+    Binding {
+        keys: &[key(KeyCode::Up), char('k'), char('w')],
+        action: Action::Up,
+        description: "Move up, or a count of rows",
+    },
+    Binding {
+        keys: &[key(KeyCode::PageDown)],
+        action: Action::PageDown,
+        description: "Move a page down",
+    },
+    Binding {
+        keys: &[key(KeyCode::PageUp)],
+        action: Action::PageUp,
+        description: "Move a page up",
+    },
+    Binding {
+        keys: &[key(KeyCode::Home)],
+        action: Action::First,
+        description: "Go to the first row, or the row of the count",
+    },
+    Binding {
+        keys: &[char('G'), key(KeyCode::End)],
+        action: Action::Last,
+        description: "Go to the last row, or the row of the count",
+    },
+    Binding {
+        keys: &[char('g')],
+        action: Action::GotoDate,
+        description: "Go to a date or month, or with gg to the first row",
+    },
+    Binding {
+        keys: &[key(KeyCode::Enter)],
+        action: Action::Details,
+        description: "Show every field of the selected row",
+    },
+    Binding {
+        keys: &[char('/')],
+        action: Action::Search,
+        description: "Search",
+    },
+    Binding {
+        keys: &[char('f')],
+        action: Action::FilterMenu,
+        description: "Filter by type, month or sign",
+    },
+    Binding {
+        keys: &[char('c')],
+        action: Action::CategoryFilter,
+        description: "Filter by a type, or remove the type filtered by",
+    },
+    Binding {
+        keys: &[key(KeyCode::Backspace)],
+        action: Action::PopFilter,
+        description: "Remove the last filter",
+    },
+    Binding {
+        keys: &[char('o')],
+        action: Action::SortNext,
+        description: "Sort by the next column",
+    },
+    Binding {
+        keys: &[char('O')],
+        action: Action::SortReverse,
+        description: "Reverse the sort order",
+    },
+    Binding {
+        keys: &[char('|')],
+        action: Action::ToggleColumn,
+        description: "Show or hide a column of the table",
+    },
+    Binding {
+        keys: &[char('m')],
+        action: Action::Group,
+        description: "Group the rows by month, by type or not at all",
+    },
+    Binding {
+        keys: &[char('p')],
+        action: Action::NextPeriod,
+        description: "Show only the last month, 3 months, this year, the last year or all",
+    },
+    Binding {
+        keys: &[char('P')],
+        action: Action::PreviousPeriod,
+        description: "Go back to the previous period",
+    },
+    Binding {
+        keys: &[char('a')],
+        action: Action::Add,
+        description: "Add an expense",
+    },
+    Binding {
+        keys: &[char('e')],
+        action: Action::Edit,
+        description: "Edit the selected expense",
+    },
+    Binding {
+        keys: &[char('i')],
+        action: Action::EditCell,
+        description: "Edit only the selected cell, in place",
+    },
+    Binding {
+        keys: &[key(KeyCode::Left), char('h')],
+        action: Action::PreviousColumn,
+        description: "Select the cell to the left",
+    },
+    Binding {
+        keys: &[key(KeyCode::Right), char('l')],
+        action: Action::NextColumn,
+        description: "Select the cell to the right",
+    },
+    Binding {
+        keys: &[char('D'), after('y', 'p')],
+        action: Action::Duplicate,
+        description: "Add a copy of the selected expense dated today",
+    },
+    Binding {
+        keys: &[char('d'), key(KeyCode::Delete)],
+        action: Action::Delete,
+        description: "Delete the selected or marked expenses",
+    },
+    Binding {
+        keys: &[char('u')],
+        action: Action::Undo,
+        description: "Undo the last change",
+    },
+    Binding {
+        keys: &[ctrl('r')],
+        action: Action::Redo,
+        description: "Redo the last undone change",
+    },
+    Binding {
+        keys: &[char(' ')],
+        action: Action::Mark,
+        description: "Mark or unmark the selected row",
+    },
+    Binding {
+        keys: &[key(KeyCode::Esc)],
+        action: Action::ClearMarks,
+        description: "Unmark all rows",
+    },
+    Binding {
+        keys: &[char('r')],
+        action: Action::Retype,
+        description: "Set the type of the marked or filtered rows",
+    },
+    Binding {
+        keys: &[char('t')],
+        action: Action::Retag,
+        description: "Set the tags of the marked or filtered rows",
+    },
+    Binding {
+        keys: &[char('T')],
+        action: Action::Triage,
+        description: "Set the type of row after row with the number keys",
+    },
+    Binding {
+        keys: &[char('x')],
+        action: Action::Export,
+        description: "Export the marked rows to a CSV file",
+    },
+    Binding {
+        keys: &[char('E')],
+        action: Action::ExportView,
+        description: "Export the rows in the table to a CSV, JSON or Markdown file",
+    },
+    Binding {
+        keys: &[char('I')],
+        action: Action::Import,
+        description: "Import expenses from a bank statement or other CSV file",
+    },
+    Binding {
+        keys: &[char('H')],
+        action: Action::HideAmounts,
+        description: "Hide or show the amounts, for a shared screen",
+    },
+    Binding {
+        keys: &[char('v')],
+        action: Action::SwitchChart,
+        description: "Switch the charts between bars by type, shares, lines, entries, bars by day and by month",
+    },
+    Binding {
+        keys: &[char('V')],
+        action: Action::ChartMetric,
+        description: "Chart the net or gross amounts, or the number of entries",
+    },
+    Binding {
+        keys: &[char('C')],
+        action: Action::FocusCharts,
+        description: "Focus the charts, to scroll the bars that don't fit with Left and Right",
+    },
+    Binding {
+        keys: &[after('y', 'y')],
+        action: Action::CopyText,
+        description: "Copy the selected or marked rows as text",
+    },
+    Binding {
+        keys: &[char('Y')],
+        action: Action::CopyCsv,
+        description: "Copy the selected or marked rows as CSV",
+    },
+    Binding {
+        keys: &[key(KeyCode::Tab)],
+        action: Action::NextTab,
+        description: "Show the next tab",
+    },
+    Binding {
+        keys: &[key(KeyCode::BackTab)],
+        action: Action::PreviousTab,
+        description: "Show the previous tab",
+    },
+    Binding {
+        keys: &[char(':')],
+        action: Action::Command,
+        description: "Type a command: export, filter, goto, import, set or quit",
+    },
+    Binding {
+        keys: &[char('?')],
+        action: Action::Help,
+        description: "Show this help",
+    },
+    Binding {
+        keys: &[char('q')],
+        action: Action::Quit,
+        description: "Quit",
+    },
+];
+
+/// What a key press means, given the key pressed before it.
+pub enum Lookup {
+    Action(Action),
+    /// The first key of a pair like `gg`, waiting for the second one
+    Prefix(char),
+    None,
+}
+
+/// The keys of every action, the defaults with the ones from the config in their place.
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, &'static Binding)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: BINDINGS
+                .iter()
+                .map(|binding| (binding.keys.to_vec(), binding))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// The default keys with the actions in `keys` bound to the space separated keys given
+    /// instead, e.g. `delete = "x Delete"`. Keys taken this way are removed from other actions.
+    pub fn new(keys: &BTreeMap<String, String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut keymap = Self::default();
+        for (name, labels) in keys {
+            let index = keymap
+                .bindings
+                .iter()
+                .position(|(_, binding)| binding.action.name() == *name)
+                .ok_or_else(|| format!("Unknown action '{}' in the keys", name))?;
+            let new_keys = labels
+                .split_whitespace()
+                .map(|label| {
+                    parse_key(label).ok_or_else(|| format!("Unknown key '{}' for {}", label, name))
+                })
+                .collect::<Result<Vec<Key>, String>>()?;
+
+            for (keys, _) in keymap.bindings.iter_mut() {
+                keys.retain(|key| !new_keys.contains(key));
+            }
+            keymap.bindings[index].0 = new_keys;
+        }
+        Ok(keymap)
+    }
+
+    /// Every action with its keys and description, in the order of [BINDINGS].
+    pub fn bindings(&self) -> impl Iterator<Item = (&[Key], &'static Binding)> {
+        self.bindings
+            .iter()
+            .map(|(keys, binding)| (keys.as_slice(), *binding))
+    }
+
+    pub fn lookup(
+        &self,
+        pending_key: Option<char>,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> Lookup {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let keys = || self.bindings.iter().flat_map(|(keys, _)| keys);
+
+        if let Some((_, binding)) = self.bindings.iter().find(|(keys, _)| {
+            keys.iter()
+                .any(|key| key.prefix == pending_key && key.code == code && key.ctrl == ctrl)
+        }) {
+            return Lookup::Action(binding.action);
+        }
+        match code {
+            KeyCode::Char(c) if pending_key.is_none() && !ctrl => {
+                if keys().any(|key| key.prefix == Some(c)) {
+                    Lookup::Prefix(c)
+                } else {
+                    Lookup::None
+                }
+            }
+            _ => Lookup::None,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! The menu for filtering the table by a column's value.
+
+use crossterm::event::KeyCode;
+use ratatui::{prelude::*, widgets::*};
+use std::collections::{BTreeMap, BTreeSet};
+
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::Expense;
+
+use super::centered_rect;
+
+const CATEGORIES: [&str; 4] = ["Type", "Month", "Sign", "Saved"];
+
+/// What the menu wants to happen after a key press.
+pub enum MenuAction {
+    Continue,
+    /// Filter with the query
+    Pick(String),
+    Close,
+}
+
+/// A menu listing what to filter by, and then the values to pick from.
+pub struct FilterMenu {
+    /// The chosen category and its values as labels and queries, or `None` while choosing one.
+    choices: Option<(&'static str, Vec<(String, String)>)>,
+    selected: usize,
+}
+
+impl FilterMenu {
+    pub fn new() -> Self {
+        Self {
+            choices: None,
+            selected: 0,
+        }
+    }
+
+    /// `saved` are the filters saved in the configuration, by name.
+    pub fn handle_key(
+        &mut self,
+        code: KeyCode,
+        expenses: &[Expense],
+        saved: &BTreeMap<String, String>,
+    ) -> MenuAction {
+        let len = match &self.choices {
+            Some((_, choices)) => choices.len(),
+            None => CATEGORIES.len(),
+        };
+        match code {
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left if self.choices.is_some() => {
+                self.choices = None;
+                self.selected = 0;
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('f') => return MenuAction::Close,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Char('s') if len > 0 => {
+                self.selected = (self.selected + 1) % len
+            }
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('w') if len > 0 => {
+                self.selected = (self.selected + len - 1) % len
+            }
+            KeyCode::Enter | KeyCode::Right => match &self.choices {
+                Some((_, choices)) => {
+                    if let Some((_, query)) = choices.get(self.selected) {
+                        return MenuAction::Pick(query.clone());
+                    }
+                }
+                None => {
+                    let category = CATEGORIES[self.selected];
+                    self.choices = Some((category, Self::choices(category, expenses, saved)));
+                    self.selected = 0;
+                }
+            },
+            _ => {}
+        }
+        MenuAction::Continue
+    }
+
+    /// The values of a category found in the expenses, or the saved filters, with the query
+    /// matching each.
+    fn choices(
+        category: &str,
+        expenses: &[Expense],
+        saved: &BTreeMap<String, String>,
+    ) -> Vec<(String, String)> {
+        match category {
+            "Type" => expenses
+                .iter()
+                .map(|expense| expense.expense_type.as_str())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(|expense_type| {
+                    (
+                        expense_type.to_string(),
+                        format!("type:\"{}\"", expense_type),
+                    )
+                })
+                .collect(),
+            // Newest month first
+            // Months as `date:` takes them, named after the month they start in
+            "Month" => expenses
+                .iter()
+                .filter_map(|expense| DateRange::month_of(parse_iso(&expense.date)?))
+                .map(|month| month.start)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .rev()
+                .filter_map(DateRange::month_of)
+                .map(|month| {
+                    (
+                        month.month_name(),
+                        format!("date:{}", month.start.format("%Y-%m")),
+                    )
+                })
+                .collect(),
+            "Saved" => saved
+                .iter()
+                .map(|(name, query)| (name.clone(), query.clone()))
+                .collect(),
+            _ => vec![
+                ("Expenses".to_string(), "amount<0".to_string()),
+                ("Income".to_string(), "amount>=0".to_string()),
+            ],
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let (title, items): (String, Vec<&str>) = match &self.choices {
+            Some((category, choices)) => (
+                match *category {
+                    "Saved" => "Saved filters".to_string(),
+                    category => format!("Filter by {}", category.to_lowercase()),
+                },
+                choices.iter().map(|(label, _)| label.as_str()).collect(),
+            ),
+            None => ("Filter by".to_string(), CATEGORIES.to_vec()),
+        };
+        let height = (items.len() as u16 + 2).min(frame.size().height.saturating_sub(4));
+        let area = centered_rect(30, height.max(3), frame.size());
+        frame.render_widget(Clear, area);
+
+        let list = List::new(items)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">>");
+        let mut list_state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, area, &mut list_state);
+    }
+}
@@ -0,0 +1,3130 @@
+//! Implements the TUI interface
+
+pub mod budgets;
+mod calendar;
+pub mod chart;
+mod column;
+mod command;
+mod dashboard;
+mod filter;
+mod form;
+pub mod group;
+mod history;
+mod import;
+mod keymap;
+mod menu;
+pub mod palette;
+mod period;
+mod recurring;
+mod reports;
+mod settings;
+pub mod setup;
+mod tab;
+mod watch;
+mod widgets;
+
+use chrono::{Local, NaiveDate};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+use log::{debug, error, trace};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::{prelude::*, widgets::*};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::{io, ops::Range, path::Path, path::PathBuf};
+
+use budget_tracker::anomaly;
+use budget_tracker::budget;
+use budget_tracker::config::{Config, Theme, MASK};
+use budget_tracker::date::{parse_input_date, parse_iso, parse_range, DateRange};
+use budget_tracker::expense::*;
+use budget_tracker::index::SearchIndex;
+use budget_tracker::search::{Field, Highlight};
+
+use chart::{ChartState, Target};
+use column::{Column, Sort};
+use command::Command;
+pub use filter::{ActiveFilter, Filter, SearchKind};
+use form::{ExpenseForm, FormAction};
+use group::{Grouping, TableLine};
+use history::{Change, History};
+use import::{ImportAction, ImportWizard};
+use keymap::{Action, Key, Keymap, Lookup};
+use menu::{FilterMenu, MenuAction};
+use period::Period;
+use tab::Tab;
+use watch::FileWatcher;
+use widgets::{Button, Choice, Modal, Picker, PickerAction, TextInput, Toast};
+
+/// How wide the Transactions tab has to be for the charts to go beside the table.
+const SIDE_BY_SIDE_WIDTH: u16 = 120;
+
+/// How high the Transactions tab has to be for the charts to go below the table when it is too
+/// narrow to have them beside it.
+const STACKED_HEIGHT: u16 = 30;
+
+/// Why nothing can be changed while another instance has the database open.
+const READ_ONLY: &str = "Read only, another budget-tracker has the expenses open";
+
+/// What the keyboard input currently goes to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Normal,
+    Search,
+    Form,
+    /// Answering the dialog in [App::modal]
+    Dialog(Dialog),
+    FilterMenu,
+    /// Picking the type to filter by, from [App::picker]
+    PickCategory,
+    /// Picking the column of the table to show or hide, from [App::picker]
+    PickColumn,
+    /// Typing the new value of a field for all marked rows
+    BulkEdit(BulkField),
+    /// Listing the keys of the table view
+    Help,
+    /// Typing a new value for the selected setting
+    EditSetting,
+    /// Typing the date to go to
+    GotoDate,
+    /// Typing the file to export the rows in the table to
+    ExportView,
+    /// Setting the type of one row after the other with the number keys
+    Triage,
+    /// Typing a command after `:`
+    Command,
+    /// Going through the steps of [App::import]
+    Import,
+}
+
+/// What a dialog is about, to act on the answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dialog {
+    /// Asking whether to delete the selected or marked rows
+    Delete,
+    /// Showing every field of the selected row
+    Details,
+    /// Asking whether to close the form without saving what was typed
+    DiscardForm,
+    /// Asking whether to add the rows kept in the import wizard
+    Import,
+    /// Asking whether to throw away the form to show the database changed by another program
+    FileChanged,
+    Error,
+}
+
+/// A field set on all marked rows at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BulkField {
+    Type,
+    Tags,
+}
+
+/// The state of the TUI.
+pub struct App {
+    pub config: Config,
+    /// Every expense in the database, in the order they are stored.
+    pub expenses: Vec<Expense>,
+    index: SearchIndex,
+    /// Filters given on the command line.
+    cli_filters: Vec<Filter>,
+    /// The recent period everything is restricted to
+    pub period: Period,
+    /// Rows left after the command line filters and the period, positions into `expenses` in
+    /// display order.
+    base_rows: Vec<usize>,
+    /// Rows currently shown in the table.
+    pub rows: Vec<usize>,
+    /// The lines of the table: the rows, under a header per group when grouping. The selection
+    /// is an index into these.
+    pub lines: Vec<TableLine>,
+    pub grouping: Grouping,
+    /// The groups folded or unfolded from how they start out, by key
+    toggled_groups: BTreeSet<String>,
+    /// Searches kept with Enter, each narrowing the results of the previous one.
+    pub filters: Vec<ActiveFilter>,
+    /// The query of the type picked with `c`, cleared by pressing it again
+    category_filter: Option<String>,
+    /// The search typed into the search bar.
+    search: Option<Filter>,
+    pub mode: Mode,
+    pub search_kind: SearchKind,
+    /// The search being typed, with why it can't be used in its error
+    pub search_input: TextInput,
+    pub form: Option<ExpenseForm>,
+    pub menu: Option<FilterMenu>,
+    /// Importing a CSV file, opened with `I` or `:import`
+    pub import: Option<ImportWizard>,
+    /// The types to filter by with `c`
+    pub picker: Option<Picker>,
+    /// Rows marked with Space for bulk actions, positions into `expenses`.
+    pub marked: BTreeSet<usize>,
+    /// Rows whose amount is unusual for their type, positions into `expenses`
+    unusual: HashSet<usize>,
+    pub bulk_input: TextInput,
+    /// The command being typed after `:`
+    pub command_input: TextInput,
+    /// The date or month being typed to go to
+    pub goto_input: TextInput,
+    /// The file being typed to export the table to, its extension giving the format
+    pub export_input: TextInput,
+    /// The types set with the keys 1 to 9 while triaging, the most common ones first
+    pub triage_types: Vec<String>,
+    /// The keys of the table view, from the config
+    pub keymap: Keymap,
+    /// The line selected in the Settings tab
+    pub settings_selected: usize,
+    /// The new value being typed for the selected setting
+    pub setting_input: TextInput,
+    /// The outcome of the last action, shown for a few seconds
+    pub toast: Option<Toast>,
+    /// The dialog being shown, with [Mode::Dialog] saying what it is about
+    pub modal: Option<Modal>,
+    /// Opened on the first copy and kept, as on X11 the copied text is gone once it is dropped.
+    clipboard: Option<arboard::Clipboard>,
+    history: History,
+    /// Whether another instance holds the lock of the database, so this one doesn't write to it
+    pub read_only: bool,
+    /// Tells about changes made to the database by other programs
+    watcher: Option<FileWatcher>,
+    /// Whether the database was changed by another program and is waiting to be reloaded, as it
+    /// isn't while a form or dialog is open
+    disk_changed: bool,
+    /// Whether to keep editing the form despite the database having changed, asked once
+    keep_editing: bool,
+    pub sort: Sort,
+    /// The column of the selected cell, edited in place with `i`
+    pub column: Column,
+    pub table_state: TableState,
+    /// The view shown
+    pub tab: Tab,
+    /// What the charts beside the table show
+    pub chart: ChartState,
+    /// The day selected in the heatmap of the Reports tab
+    pub calendar_day: NaiveDate,
+    /// Where the table and charts were last drawn, to find what the mouse clicks.
+    pub areas: HitAreas,
+    /// A count typed before a motion, like the 5 in `5j`.
+    pub count: Option<usize>,
+    /// A key waiting for a second one, like the first `g` of `gg`.
+    pub pending_key: Option<char>,
+}
+
+impl App {
+    pub fn new(
+        config: Config,
+        expenses: Vec<Expense>,
+        index: SearchIndex,
+        cli_filters: Vec<Filter>,
+    ) -> Self {
+        // Searching from the command line goes straight to the results
+        let tab = if cli_filters.is_empty() {
+            Tab::Dashboard
+        } else {
+            Tab::Transactions
+        };
+        // A mistake in the keys shouldn't keep the TUI from starting
+        let (keymap, modal) = match Keymap::new(&config.keys) {
+            Ok(keymap) => (keymap, None),
+            Err(err) => {
+                error!("Couldn't read the keys: {}", err);
+                (
+                    Keymap::default(),
+                    Some(Modal::error(
+                        "Couldn't read the keys",
+                        format!("{}\n\nUsing the default keys instead.", err),
+                    )),
+                )
+            }
+        };
+        // The TUI works without noticing changes from other programs too
+        let watcher = Expense::get_database_file_path("expenses.csv")
+            .and_then(FileWatcher::new)
+            .map_err(|err| error!("Couldn't watch the database: {}", err))
+            .ok();
+        let mut app = Self {
+            config,
+            expenses,
+            index,
+            cli_filters,
+            period: Period::default(),
+            base_rows: Vec::new(),
+            rows: Vec::new(),
+            lines: Vec::new(),
+            grouping: Grouping::default(),
+            toggled_groups: BTreeSet::new(),
+            filters: Vec::new(),
+            category_filter: None,
+            search: None,
+            mode: match modal {
+                Some(_) => Mode::Dialog(Dialog::Error),
+                None => Mode::Normal,
+            },
+            search_kind: SearchKind::Text,
+            search_input: TextInput::default(),
+            form: None,
+            column: Column::Amount,
+            menu: None,
+            import: None,
+            picker: None,
+            marked: BTreeSet::new(),
+            unusual: HashSet::new(),
+            bulk_input: TextInput::default(),
+            command_input: TextInput::default(),
+            goto_input: TextInput::default(),
+            export_input: TextInput::default(),
+            triage_types: Vec::new(),
+            keymap,
+            settings_selected: 0,
+            setting_input: TextInput::default(),
+            toast: None,
+            modal,
+            clipboard: None,
+            history: History::default(),
+            read_only: false,
+            watcher,
+            disk_changed: false,
+            keep_editing: false,
+            sort: Sort::default(),
+            table_state: TableState::default(),
+            tab,
+            chart: ChartState::default(),
+            calendar_day: Local::now().date_naive(),
+            areas: HitAreas::default(),
+            count: None,
+            pending_key: None,
+        };
+        app.refresh();
+        app
+    }
+
+    /// The expenses currently shown in the table.
+    pub fn visible(&self) -> Vec<&Expense> {
+        self.rows.iter().map(|&row| &self.expenses[row]).collect()
+    }
+
+    /// Whether the table shows only some of the expenses, because of a search or filter.
+    pub fn is_filtered(&self) -> bool {
+        !self.cli_filters.is_empty()
+            || self.period != Period::All
+            || !self.filters.is_empty()
+            || self.search.is_some()
+    }
+
+    /// Recomputes the shown rows from all expenses, e.g. after they changed.
+    fn refresh(&mut self) {
+        let expenses: Vec<&Expense> = self.expenses.iter().collect();
+        self.unusual = anomaly::unusual(&expenses)
+            .into_iter()
+            .map(|unusual| unusual.index)
+            .collect();
+        let mut rows: Vec<usize> = (0..self.expenses.len()).collect();
+        rows.sort_by(|&a, &b| self.sort.compare(&self.expenses[a], &self.expenses[b]));
+        for filter in &self.cli_filters {
+            rows = self.apply_filter(filter, &rows);
+        }
+        if let Some(range) = self.period.range(Local::now().date_naive()) {
+            rows.retain(|&row| {
+                parse_iso(&self.expenses[row].date).is_some_and(|date| range.contains(date))
+            });
+        }
+        self.base_rows = rows;
+
+        for position in 0..self.filters.len() {
+            let rows =
+                self.apply_filter(&self.filters[position].filter, self.filtered_rows(position));
+            self.filters[position].rows = rows;
+        }
+        self.apply_search();
+    }
+
+    /// Restricts everything to a period, keeping the selected row selected if it is in it.
+    fn set_period(&mut self, period: Period) {
+        trace!("Showing the period {:?}", period);
+        let selected = self.selected_row();
+        self.period = period;
+        self.refresh();
+        if let Some(row) = selected {
+            self.select_row(row);
+        }
+    }
+
+    /// Sorts the table differently, keeping the selected row selected.
+    fn set_sort(&mut self, sort: Sort) {
+        trace!("Sorting by {:?}", sort);
+        let selected = self.selected_row();
+        self.sort = sort;
+        self.refresh();
+        if let Some(row) = selected {
+            self.select_row(row);
+        }
+    }
+
+    /// The `rows` the filter keeps. Text searches order them by relevance under the default sort,
+    /// but a column picked to sort by keeps its order.
+    fn apply_filter(&self, filter: &Filter, rows: &[usize]) -> Vec<usize> {
+        let mut rows = filter.apply(&self.expenses, rows, &self.index);
+        if matches!(filter, Filter::Text(_)) && self.sort != Sort::default() {
+            rows.sort_by(|&a, &b| self.sort.compare(&self.expenses[a], &self.expenses[b]));
+        }
+        rows
+    }
+
+    /// Rows left after the first `count` kept filters.
+    fn filtered_rows(&self, count: usize) -> &[usize] {
+        match count {
+            0 => &self.base_rows,
+            count => &self.filters[count - 1].rows,
+        }
+    }
+
+    /// Filters the table with the text in the search bar.
+    fn apply_search(&mut self) {
+        trace!(
+            "Applying {:?} search: {}",
+            self.search_kind,
+            self.search_input.value()
+        );
+        self.search_input.error = None;
+        self.search = None;
+        self.rows = self.filtered_rows(self.filters.len()).to_vec();
+
+        if !self.search_input.value().trim().is_empty() {
+            match self.search_kind.parse(self.search_input.value()) {
+                Ok(filter) => {
+                    self.rows = self.apply_filter(&filter, &self.rows);
+                    self.search = Some(filter);
+                }
+                Err(err) => self.search_input.error = Some(err),
+            }
+        }
+
+        self.update_lines();
+        self.table_state
+            .select(if self.lines.is_empty() { None } else { Some(0) });
+    }
+
+    /// Lays out the shown rows into the lines of the table, after the rows or grouping changed.
+    fn update_lines(&mut self) {
+        // Groups follow the date order when sorting by date, and are newest first otherwise
+        let ascending = self.sort.column == Column::Date && !self.sort.descending;
+        self.lines = group::lines(
+            self.grouping,
+            &self.rows,
+            &self.expenses,
+            ascending,
+            &self.toggled_groups,
+        );
+    }
+
+    /// Switches to the next grouping, keeping the selected row selected.
+    fn next_grouping(&mut self) {
+        let selected = self.selected_row();
+        self.grouping = self.grouping.next();
+        self.toggled_groups.clear();
+        self.update_lines();
+        self.select_index(0);
+        if let Some(row) = selected {
+            self.select_row(row);
+        }
+    }
+
+    /// Collapses the group whose header is selected, or expands it.
+    fn toggle_group(&mut self, key: &str) {
+        if !self.toggled_groups.remove(key) {
+            self.toggled_groups.insert(key.to_string());
+        }
+        self.update_lines();
+        if let Some(index) = self
+            .lines
+            .iter()
+            .position(|line| matches!(line, TableLine::Group { key: k, .. } if k == key))
+        {
+            self.table_state.select(Some(index));
+        }
+    }
+
+    /// Keeps the current search as a filter, so the next search refines its results.
+    fn keep_search(&mut self) {
+        if let Some(filter) = self.search.take() {
+            trace!(
+                "Keeping {:?} search: {}",
+                self.search_kind,
+                self.search_input.value()
+            );
+            self.filters.push(ActiveFilter {
+                kind: self.search_kind,
+                input: self.search_input.take(),
+                filter,
+                rows: self.rows.clone(),
+            });
+        }
+    }
+
+    /// Keeps a filter picked from the filter menu, below any search being typed.
+    fn push_filter(
+        &mut self,
+        kind: SearchKind,
+        input: String,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let filter = kind.parse(&input)?;
+        trace!("Adding {:?} filter: {}", kind, input);
+        let rows = self.apply_filter(&filter, self.filtered_rows(self.filters.len()));
+        self.filters.push(ActiveFilter {
+            kind,
+            input,
+            filter,
+            rows,
+        });
+        self.apply_search();
+
+        Ok(())
+    }
+
+    /// Opens the menu of types to filter by, or removes the type picked from it before.
+    fn toggle_category_filter(&mut self) {
+        let picked = self.category_filter.take().and_then(|query| {
+            self.filters
+                .iter()
+                .position(|filter| filter.kind == SearchKind::Query && filter.input == query)
+        });
+        match picked {
+            Some(position) => {
+                let filter = self.filters.remove(position);
+                trace!("Removed filter: {}", filter.label());
+                self.apply_search();
+            }
+            None => {
+                // The types in the table with how many there are of each, the most common first
+                let mut counts: HashMap<&str, usize> = HashMap::new();
+                for expense in self.visible() {
+                    *counts.entry(&expense.expense_type).or_default() += 1;
+                }
+                let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+                counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+                let choices = counts
+                    .into_iter()
+                    .map(|(expense_type, count)| {
+                        (
+                            format!("{} ({})", expense_type, count),
+                            format!("type:\"{}\"", expense_type),
+                        )
+                    })
+                    .collect();
+                self.picker = Some(Picker::new("Filter by type", choices, ""));
+                self.mode = Mode::PickCategory;
+            }
+        }
+    }
+
+    /// Lists every column of the table to show or hide one, the shown ones ticked.
+    fn pick_column(&mut self) {
+        let shown = Column::shown(&self.config);
+        let choices = Column::ALL
+            .into_iter()
+            .map(|column| {
+                let tick = if shown.contains(&column) { "✓" } else { " " };
+                (
+                    format!("{} {}", tick, column.title()),
+                    column.name().to_string(),
+                )
+            })
+            .collect();
+        self.picker = Some(Picker::new("Show or hide a column", choices, ""));
+        self.mode = Mode::PickColumn;
+    }
+
+    /// Hides the column named `name` if it is shown, or else shows it after the others, and
+    /// saves the columns to the config.
+    fn toggle_column(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let column = Column::from_name(name).ok_or("Unknown column")?;
+        let mut columns = Column::shown(&self.config);
+        match columns.iter().position(|&shown| shown == column) {
+            Some(_) if columns.len() == 1 => return Err("The table needs a column".into()),
+            Some(position) => {
+                columns.remove(position);
+            }
+            None => columns.push(column),
+        }
+        let names: Vec<&str> = columns.iter().map(|column| column.name()).collect();
+        let mut config = self.config.clone();
+        config.set("columns", &names.join(","))?;
+        config.save()?;
+
+        self.config = config;
+        if !columns.contains(&self.column) {
+            self.column = columns[0];
+        }
+        self.toast = Some(Toast::info(format!(
+            "Saved columns as {}",
+            names.join(", ")
+        )));
+        Ok(())
+    }
+
+    /// Every type to pick from: the ones in use, the most common first, then the ones from the
+    /// config that aren't used yet.
+    fn known_types(&self) -> Vec<String> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for expense in &self.expenses {
+            if !expense.expense_type.is_empty() {
+                *counts.entry(&expense.expense_type).or_default() += 1;
+            }
+        }
+        let mut types: Vec<(&str, usize)> = counts.into_iter().collect();
+        types.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let mut types: Vec<String> = types
+            .into_iter()
+            .map(|(expense_type, _)| expense_type.to_string())
+            .collect();
+        for category in &self.config.categories {
+            if !types.iter().any(|used| used.eq_ignore_ascii_case(category)) {
+                types.push(category.clone());
+            }
+        }
+        types
+    }
+
+    /// Removes the last kept filter.
+    fn pop_filter(&mut self) {
+        if let Some(filter) = self.filters.pop() {
+            trace!("Removed filter: {}", filter.label());
+            self.apply_search();
+        }
+    }
+
+    /// Selects the given row, a position into `expenses`, if it is shown.
+    fn select_row(&mut self, row: usize) {
+        if let Some(position) = self
+            .lines
+            .iter()
+            .position(|line| *line == TableLine::Expense(row))
+        {
+            self.table_state.select(Some(position));
+        }
+    }
+
+    /// Saves a change to the database, shows it in the table and remembers it for undo.
+    fn make_change(&mut self, change: Change) -> Result<(), Box<dyn std::error::Error>> {
+        if self.read_only {
+            return Err(READ_ONLY.into());
+        }
+        self.apply_change(&change, true)?;
+        self.history.record(change);
+        Ok(())
+    }
+
+    /// Saves a change to the database and shows it in the table, putting removed rows in the
+    /// trash with `trash`.
+    fn apply_change(
+        &mut self,
+        change: &Change,
+        trash: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        change.apply("expenses.csv", &mut self.expenses, trash)?;
+        trace!("Made change: {:?}", change);
+        // Inserting or removing rows moves the ones after them
+        if !matches!(change, Change::Replace(_)) {
+            self.marked.clear();
+        }
+        self.index = SearchIndex::load_or_build("expenses.csv", &self.expenses)?;
+        self.refresh();
+        Ok(())
+    }
+
+    /// Notes whether another program changed the database, and reloads it unless a form or
+    /// dialog is open. Unsaved changes in the form are only thrown away when confirmed.
+    fn check_disk(&mut self) {
+        if self.watcher.as_ref().is_some_and(FileWatcher::changed) {
+            // Our own writes are noticed as well, but leave the file as it is in memory
+            match Expense::read_csv("expenses.csv") {
+                Ok(expenses) => self.disk_changed |= !same_records(&expenses, &self.expenses),
+                // Likely half written, the rest of the write comes as another change
+                Err(err) => debug!("Couldn't read the changed database: {}", err),
+            }
+        }
+        if !self.disk_changed || matches!(self.mode, Mode::Dialog(_)) {
+            return;
+        }
+        match &self.form {
+            Some(form) if form.is_changed() && !self.keep_editing => {
+                self.modal = Some(Modal::new(
+                    "The expenses changed on disk",
+                    vec![
+                        Line::raw("Another program changed expenses.csv while the form is open."),
+                        Line::raw(""),
+                        Line::raw("Reload to throw away what was typed and show the new expenses, or keep editing to save the form into the new file."),
+                    ],
+                    vec![
+                        Button::new("Reload", Some('r'), Choice::Confirm),
+                        Button::new("Keep editing", Some('k'), Choice::Dismiss),
+                    ],
+                ));
+                self.mode = Mode::Dialog(Dialog::FileChanged);
+            }
+            // Saving the form reloads first
+            Some(_) => {}
+            None => {
+                let reloaded = self.reload();
+                self.report("reload the expenses", reloaded);
+            }
+        }
+    }
+
+    /// Reads the database again after another program changed it. Undo can't follow changes
+    /// it didn't make, so the history starts over.
+    fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.disk_changed = false;
+        self.keep_editing = false;
+        let expenses = Expense::read_csv("expenses.csv")?;
+        if same_records(&expenses, &self.expenses) {
+            return Ok(());
+        }
+        trace!("Reloading {} expenses changed on disk", expenses.len());
+        self.expenses = expenses;
+        self.marked.clear();
+        self.history = History::default();
+        self.index = SearchIndex::load_or_build("expenses.csv", &self.expenses)?;
+        self.refresh();
+        self.toast = Some(Toast::info("Reloaded the expenses changed on disk"));
+        Ok(())
+    }
+
+    /// Reverses the last change, or makes the last undone change again with `redo`.
+    fn undo(&mut self, redo: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let change = if redo {
+            self.history.redo()
+        } else {
+            self.history.undo()
+        };
+        let Some(change) = change else {
+            self.toast = Some(Toast::info(format!(
+                "Nothing to {}",
+                if redo { "redo" } else { "undo" }
+            )));
+            return Ok(());
+        };
+
+        let applied = if redo {
+            change.clone()
+        } else {
+            change.inverse()
+        };
+        if let Err(err) = self.apply_change(&applied, false) {
+            self.history.restore(!redo);
+            return Err(err);
+        }
+        if !matches!(applied, Change::Remove(_)) {
+            if let Some(&row) = applied.rows().first() {
+                self.select_row(row);
+            }
+        }
+        self.toast = Some(Toast::info(format!(
+            "{}: {}",
+            if redo { "Redone" } else { "Undone" },
+            change.describe()
+        )));
+        Ok(())
+    }
+
+    /// Saves a new expense to the database and shows it in the table.
+    fn add_expense(&mut self, expense: Expense) -> Result<(), Box<dyn std::error::Error>> {
+        let row = self.expenses.len();
+        self.make_change(Change::Insert(vec![(row, expense)]))?;
+        self.select_row(row);
+        Ok(())
+    }
+
+    /// Saves the changes to the expense at `row` and keeps it selected.
+    fn edit_expense(
+        &mut self,
+        row: usize,
+        expense: Expense,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let before = self.expenses[row].clone();
+        self.make_change(Change::Replace(vec![(row, before, expense)]))?;
+        self.select_row(row);
+        Ok(())
+    }
+
+    /// The rows delete and the other bulk actions work on: the marked ones, or else the selected
+    /// one.
+    fn action_rows(&self) -> Vec<usize> {
+        if self.marked.is_empty() {
+            self.selected_row().into_iter().collect()
+        } else {
+            self.marked.iter().copied().collect()
+        }
+    }
+
+    /// The rows the type and tags are set on: the marked ones, or else all rows left by the
+    /// filters, so a search result can be recategorized at once.
+    fn bulk_rows(&self) -> Vec<usize> {
+        if !self.marked.is_empty() {
+            self.marked.iter().copied().collect()
+        } else if self.is_filtered() {
+            self.rows.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Marks the selected row for bulk actions, or unmarks it, and moves to the next one.
+    fn toggle_mark(&mut self) {
+        if let Some(row) = self.selected_row() {
+            if !self.marked.remove(&row) {
+                self.marked.insert(row);
+            }
+            self.move_selection(1);
+        }
+    }
+
+    /// Moves the expenses at `rows` to the trash and selects the row that took the place of
+    /// the selected one.
+    fn delete_rows(&mut self, rows: &[usize]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut rows = rows.to_vec();
+        rows.sort_unstable();
+        let removed = rows
+            .iter()
+            .map(|&row| (row, self.expenses[row].clone()))
+            .collect();
+
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.make_change(Change::Remove(removed))?;
+        self.select_index(selected);
+        self.toast = Some(Toast::info(match rows.len() {
+            1 => "Moved 1 expense to the trash".to_string(),
+            count => format!("Moved {} expenses to the trash", count),
+        }));
+        Ok(())
+    }
+
+    /// Sets the type or the tags of all marked rows, or all rows left by the filters, in a
+    /// single write.
+    fn bulk_edit(
+        &mut self,
+        field: BulkField,
+        value: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let value = value.trim();
+        if field == BulkField::Type && (value.is_empty() || value.contains(',')) {
+            return Err("The type can't be empty or contain commas".into());
+        }
+        if value.contains(',') {
+            return Err("Tags can't contain commas".into());
+        }
+
+        let mut rows = self.bulk_rows();
+        rows.sort_unstable();
+        let edited = rows
+            .iter()
+            .map(|&row| {
+                let before = self.expenses[row].clone();
+                let mut after = before.clone();
+                match field {
+                    BulkField::Type => after.expense_type = capitalize(value.to_string()),
+                    BulkField::Tags => after.tags = edit_tags(&after.tags, value),
+                }
+                (row, before, after)
+            })
+            .collect();
+
+        let selected = self.selected_row();
+        trace!("Setting {:?} of rows {:?} to {}", field, rows, value);
+        self.make_change(Change::Replace(edited))?;
+        if let Some(row) = selected {
+            self.select_row(row);
+        }
+        self.toast = Some(Toast::info(format!("Updated {} expenses", rows.len())));
+        Ok(())
+    }
+
+    /// Starts triaging with the nine most common types on the number keys. They stay the same
+    /// until triaging is started again, so the keys don't move while typing.
+    fn start_triage(&mut self) {
+        self.triage_types = self.known_types().into_iter().take(9).collect();
+        self.mode = Mode::Triage;
+    }
+
+    /// Sets the type of the selected row to the one on the number key and moves on to the next
+    /// row. If the row is filtered out by its new type, the one taking its place is selected.
+    fn triage(&mut self, number: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let (Some(row), Some(expense_type)) = (
+            self.selected_row(),
+            number
+                .checked_sub(1)
+                .and_then(|index| self.triage_types.get(index))
+                .cloned(),
+        ) else {
+            return Ok(());
+        };
+        let selected = self.table_state.selected().unwrap_or(0);
+
+        let before = self.expenses[row].clone();
+        let mut after = before.clone();
+        after.expense_type = expense_type;
+        self.make_change(Change::Replace(vec![(row, before, after)]))?;
+
+        if self.lines.contains(&TableLine::Expense(row)) {
+            self.select_row(row);
+            self.move_selection(1);
+        } else {
+            self.select_index(selected);
+        }
+        Ok(())
+    }
+
+    /// Copies the marked rows, or else the selected one, to the clipboard as database records
+    /// or as readable text.
+    fn copy_to_clipboard(&mut self, as_csv: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let rows = self.action_rows();
+        let lines: Vec<String> = rows
+            .iter()
+            .map(|&row| {
+                let expense = &self.expenses[row];
+                if as_csv {
+                    expense.to_csv_record()
+                } else if expense.tags.is_empty() {
+                    format!(
+                        "{}  {}  {}  {:.2}",
+                        expense.date, expense.description, expense.expense_type, expense.amount
+                    )
+                } else {
+                    format!(
+                        "{}  {}  {}  {:.2}  ({})",
+                        expense.date,
+                        expense.description,
+                        expense.expense_type,
+                        expense.amount,
+                        expense.tags.join(", ")
+                    )
+                }
+            })
+            .collect();
+
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => self.clipboard.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(lines.join("\n"))?;
+        trace!("Copied rows {:?} to the clipboard", rows);
+        self.toast = Some(Toast::info(match rows.len() {
+            1 => "Copied 1 expense to the clipboard".to_string(),
+            count => format!("Copied {} expenses to the clipboard", count),
+        }));
+        Ok(())
+    }
+
+    /// Writes the marked rows to a CSV file in the current directory.
+    fn export_marked(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::current_dir()?.join(format!(
+            "selection-{}.csv",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        self.export(&path)
+    }
+
+    /// Writes the marked rows, or without marks every row in the table, to a file in the format
+    /// of its extension.
+    fn export(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let rows: Vec<usize> = self
+            .rows
+            .iter()
+            .copied()
+            .filter(|row| self.marked.is_empty() || self.marked.contains(row))
+            .collect();
+        self.export_rows(path, &rows)
+    }
+
+    /// Starts typing the file to export the table to, suggesting a CSV file named after today.
+    fn open_export_view(&mut self) {
+        self.export_input =
+            TextInput::with_value(format!("expenses-{}.csv", Local::now().format("%Y%m%d")));
+        self.mode = Mode::ExportView;
+    }
+
+    /// Changes the extension of the file being typed to the next format.
+    fn next_export_format(&mut self) {
+        let path = PathBuf::from(self.export_input.value());
+        let format = ExportFormat::of(&path).next();
+        self.export_input = TextInput::with_value(
+            path.with_extension(format.extension())
+                .to_string_lossy()
+                .into_owned(),
+        );
+    }
+
+    /// Writes the rows to a file in the format of its extension.
+    fn export_rows(
+        &mut self,
+        path: &Path,
+        rows: &[usize],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let expenses: Vec<&Expense> = rows.iter().map(|&row| &self.expenses[row]).collect();
+        Expense::export(path, &expenses)?;
+        self.toast = Some(Toast::info(format!(
+            "Exported {} expenses to {}",
+            expenses.len(),
+            path.display()
+        )));
+        Ok(())
+    }
+
+    /// Runs a command typed after `:`.
+    fn run_command(&mut self, command: Command) -> Result<(), Box<dyn std::error::Error>> {
+        trace!("Running command: {:?}", command);
+        match command {
+            Command::Export(path) => self.export(&path)?,
+            // A saved filter's name stands for its query
+            Command::Filter(query) => {
+                let query = match self.config.filters.get(&query) {
+                    Some(saved) => saved.clone(),
+                    None => query,
+                };
+                self.push_filter(SearchKind::Query, query)?;
+            }
+            Command::Goto(date) => self.goto(&date)?,
+            Command::Import(_) if self.read_only => return Err(READ_ONLY.into()),
+            Command::Import(path) => {
+                self.open_import();
+                if let Some(import) = self.import.as_mut() {
+                    import.open(&path.to_string_lossy());
+                }
+            }
+            // Nothing changes unless the value is valid and saved, as in the Settings tab
+            Command::Set(key, value) => {
+                let mut config = self.config.clone();
+                config.set(&key, &value)?;
+                let keymap = Keymap::new(&config.keys)?;
+                config.save()?;
+
+                self.config = config;
+                self.keymap = keymap;
+                self.toast = Some(Toast::info(format!("Set {} to {}", key, value)));
+            }
+            Command::Quit => {}
+        }
+        Ok(())
+    }
+
+    /// Selects the first row in the range of days, e.g. `2024-03`, or else the row dated nearest
+    /// to its start.
+    fn goto(&mut self, input: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let today = Local::now().date_naive();
+        let range = parse_range(input, today)
+            .or_else(|| parse_input_date(input, today, self.config.date_order).map(DateRange::day))
+            .ok_or_else(|| format!("Unknown date '{}'", input))?;
+        let dates: Vec<(usize, NaiveDate)> = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| match line {
+                TableLine::Expense(row) => {
+                    parse_iso(&self.expenses[*row].date).map(|date| (index, date))
+                }
+                TableLine::Group { .. } => None,
+            })
+            .collect();
+        let index = dates
+            .iter()
+            .find(|(_, date)| range.contains(*date))
+            .or_else(|| {
+                dates
+                    .iter()
+                    .min_by_key(|(_, date)| (*date - range.start).num_days().abs())
+            })
+            .map(|(index, _)| *index)
+            .ok_or("No dated rows in the table")?;
+        self.table_state.select(Some(index));
+
+        Ok(())
+    }
+
+    /// Moves the selection in the Settings tab, stopping at the first and last lines.
+    fn move_setting(&mut self, offset: isize) {
+        let last = settings::settings(&self.config, &self.keymap).len() - 1;
+        self.settings_selected = self
+            .settings_selected
+            .saturating_add_signed(offset)
+            .min(last);
+    }
+
+    /// Moves the day selected in the heatmap by `days`, no further than today.
+    fn move_calendar_day(&mut self, days: i64) {
+        let day = self.calendar_day + chrono::Duration::days(days);
+        self.calendar_day = day.min(Local::now().date_naive());
+    }
+
+    /// Shows the expenses of a day in the table.
+    fn show_day(&mut self, day: NaiveDate) {
+        self.calendar_day = day;
+        let filtered = self.push_filter(SearchKind::Query, format!("date:{}", day));
+        self.report("filter", filtered);
+        self.tab = Tab::Transactions;
+    }
+
+    /// Starts typing a new value for the selected setting, beginning from the current one.
+    fn open_setting(&mut self) {
+        let settings = settings::settings(&self.config, &self.keymap);
+        if let Some(setting) = settings.get(self.settings_selected) {
+            self.setting_input = TextInput::with_value(setting.value.as_str());
+            self.mode = Mode::EditSetting;
+        }
+    }
+
+    /// Sets the selected setting to the typed value and saves the config. Nothing changes if the
+    /// value is invalid.
+    fn save_setting(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = settings::settings(&self.config, &self.keymap);
+        let Some(setting) = settings.get(self.settings_selected) else {
+            return Ok(());
+        };
+        let mut config = self.config.clone();
+        config.set(&setting.key, self.setting_input.value())?;
+        let keymap = Keymap::new(&config.keys)?;
+        config.save()?;
+
+        self.config = config;
+        self.keymap = keymap;
+        self.toast = Some(Toast::info(format!("Saved {}", setting.key)));
+        Ok(())
+    }
+
+    /// Shows every field of the selected row.
+    fn open_details(&mut self) {
+        if let Some(row) = self.selected_row() {
+            self.modal = Some(details_modal(
+                row,
+                &self.expenses[row],
+                self.config.hide_amounts,
+            ));
+            self.mode = Mode::Dialog(Dialog::Details);
+        }
+    }
+
+    /// Asks whether to delete the marked rows, or else the selected one.
+    fn confirm_delete(&mut self) {
+        let expenses: Vec<&Expense> = self
+            .action_rows()
+            .into_iter()
+            .map(|row| &self.expenses[row])
+            .collect();
+        if !expenses.is_empty() {
+            self.modal = Some(delete_modal(&expenses, self.config.hide_amounts));
+            self.mode = Mode::Dialog(Dialog::Delete);
+        }
+    }
+
+    /// Acts on the button pressed in a dialog and closes it.
+    fn answer(&mut self, dialog: Dialog, choice: Choice) {
+        self.modal = None;
+        self.mode = Mode::Normal;
+        match (dialog, choice) {
+            (Dialog::Delete, Choice::Confirm) => {
+                let deleted = self.delete_rows(&self.action_rows());
+                self.report("delete", deleted);
+            }
+            (Dialog::Details, Choice::Edit) => self.open_edit_form(),
+            (Dialog::DiscardForm, Choice::Confirm) => self.form = None,
+            (Dialog::DiscardForm, _) => self.mode = Mode::Form,
+            (Dialog::Import, Choice::Confirm) => {
+                let imported = self.import();
+                self.report("import", imported);
+            }
+            (Dialog::Import, _) => self.mode = Mode::Import,
+            (Dialog::FileChanged, Choice::Confirm) => {
+                self.form = None;
+                let reloaded = self.reload();
+                self.report("reload the expenses", reloaded);
+            }
+            (Dialog::FileChanged, _) => {
+                self.keep_editing = true;
+                self.mode = Mode::Form;
+            }
+            _ => {}
+        }
+    }
+
+    /// Opens the import wizard, asking for the file.
+    fn open_import(&mut self) {
+        self.import = Some(ImportWizard::new(
+            self.known_types(),
+            self.config.date_order,
+        ));
+        self.mode = Mode::Import;
+    }
+
+    /// Asks whether to add the rows kept in the import wizard.
+    fn confirm_import(&mut self) {
+        let Some(import) = &self.import else {
+            return;
+        };
+        let kept = import.kept();
+        let (duplicates, unreadable) = import.skipped();
+        let untyped = kept
+            .iter()
+            .filter(|expense| expense.expense_type.is_empty())
+            .count();
+        let mut body = vec![Line::raw(format!(
+            "{} rows will be added, {} duplicates and {} rows that couldn't be read are skipped.",
+            kept.len(),
+            duplicates,
+            unreadable
+        ))];
+        if untyped > 0 {
+            body.push(Line::raw(format!(
+                "{} rows without a type will get {}.",
+                untyped,
+                self.import_type()
+            )));
+        }
+        self.modal = Some(Modal::new(
+            "Import the rows?",
+            body,
+            vec![
+                Button::new("Import", Some('i'), Choice::Confirm),
+                Button::new("Back", Some('b'), Choice::Dismiss),
+            ],
+        ));
+        self.mode = Mode::Dialog(Dialog::Import);
+    }
+
+    /// The type given to imported rows without one.
+    fn import_type(&self) -> String {
+        self.config
+            .default_type
+            .clone()
+            .unwrap_or_else(|| "Other".to_string())
+    }
+
+    /// Adds the rows kept in the import wizard after the others, as a single change to undo.
+    fn import(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(import) = &self.import else {
+            return Ok(());
+        };
+        let (duplicates, _) = import.skipped();
+        let default_type = self.import_type();
+        let first = self.expenses.len();
+        let rows: Vec<(usize, Expense)> = import
+            .kept()
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut expense)| {
+                if expense.expense_type.is_empty() {
+                    expense.expense_type = capitalize(default_type.clone());
+                }
+                (first + index, expense)
+            })
+            .collect();
+        let count = rows.len();
+        if let Err(err) = self.make_change(Change::Insert(rows)) {
+            self.mode = Mode::Import;
+            return Err(err);
+        }
+        self.import = None;
+        self.select_row(first);
+        self.toast = Some(Toast::info(format!(
+            "Imported {} expenses, skipped {} duplicates",
+            count, duplicates
+        )));
+        Ok(())
+    }
+
+    fn handle_import_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let Some(import) = self.import.as_mut() else {
+            return;
+        };
+        match import.handle_key(code, modifiers, &self.expenses) {
+            ImportAction::Continue => {}
+            ImportAction::Confirm => self.confirm_import(),
+            ImportAction::Cancel => {
+                self.import = None;
+                self.mode = Mode::Normal;
+            }
+        }
+    }
+
+    /// Shows the form, with the types to pick from.
+    fn open_form(&mut self, mut form: ExpenseForm) {
+        if self.read_only {
+            self.toast = Some(Toast::error(READ_ONLY));
+            return;
+        }
+        form.types = self.known_types();
+        self.form = Some(form);
+        self.mode = Mode::Form;
+    }
+
+    /// Opens the form to edit the selected row.
+    fn open_edit_form(&mut self) {
+        if let Some(row) = self.selected_row() {
+            self.open_form(ExpenseForm::edit("Edit expense", row, &self.expenses[row]));
+        }
+    }
+
+    /// Opens an input over the selected cell to change only that field.
+    fn open_cell_editor(&mut self) {
+        if let Some(row) = self.selected_row() {
+            self.open_form(ExpenseForm::cell(row, &self.expenses[row], self.column));
+        }
+    }
+
+    /// Opens the form to add a copy of the selected row, dated today.
+    fn open_duplicate_form(&mut self) {
+        if let Some(row) = self.selected_row() {
+            self.open_form(ExpenseForm::duplicate(
+                "Duplicate expense",
+                &self.expenses[row],
+            ));
+        }
+    }
+
+    /// Does what a key of the table view is bound to, moving `count` rows or to that row if
+    /// given. Returns whether to quit.
+    fn perform(&mut self, action: Action, count: Option<usize>) -> bool {
+        let page = self.areas.rows.height.max(1) as isize;
+        let times = count.unwrap_or(1) as isize;
+        let settings = self.tab == Tab::Settings;
+        let reports = self.tab == Tab::Reports;
+        let charts = self.chart.focused && self.tab == Tab::Transactions;
+        if self.read_only && action.writes() {
+            self.toast = Some(Toast::error(READ_ONLY));
+            return false;
+        }
+        match action {
+            Action::Down if settings => self.move_setting(count.unwrap_or(1) as isize),
+            Action::Up if settings => self.move_setting(-(count.unwrap_or(1) as isize)),
+            Action::First if settings => self.settings_selected = 0,
+            Action::Last if settings => self.move_setting(isize::MAX),
+            Action::Details if settings => self.open_setting(),
+            Action::Down if reports => self.move_calendar_day(count.unwrap_or(1) as i64),
+            Action::Up if reports => self.move_calendar_day(-(count.unwrap_or(1) as i64)),
+            Action::NextColumn if reports => self.move_calendar_day(7 * times as i64),
+            Action::PreviousColumn if reports => self.move_calendar_day(-7 * times as i64),
+            Action::Details if reports => self.show_day(self.calendar_day),
+            Action::NextColumn if charts => self.chart.scroll(times, self.areas.chart_scroll),
+            Action::PreviousColumn if charts => self.chart.scroll(-times, self.areas.chart_scroll),
+            Action::ClearMarks if charts => self.chart.focused = false,
+            Action::GotoDate => self.mode = Mode::GotoDate,
+            Action::Details if matches!(self.selected_line(), Some(TableLine::Group { .. })) => {
+                if let Some(TableLine::Group { key, .. }) = self.selected_line().cloned() {
+                    self.toggle_group(&key);
+                }
+            }
+            Action::Group => self.next_grouping(),
+            Action::Triage => self.start_triage(),
+            Action::NextPeriod => self.set_period(self.period.next()),
+            Action::PreviousPeriod => self.set_period(self.period.previous()),
+            Action::Down => match count {
+                Some(count) => self.move_selection(count as isize),
+                None => self.select_next(),
+            },
+            Action::Up => match count {
+                Some(count) => self.move_selection(-(count as isize)),
+                None => self.select_previous(),
+            },
+            Action::PageDown => self.move_selection(page * times),
+            Action::PageUp => self.move_selection(-page * times),
+            // A count jumps to that row, counting from 1 as vim does with lines
+            Action::First => self.select_index(count.unwrap_or(1) - 1),
+            Action::Last => self.select_index(count.map_or(usize::MAX, |count| count - 1)),
+            Action::Details => self.open_details(),
+            Action::Search => self.mode = Mode::Search,
+            Action::FilterMenu => {
+                self.menu = Some(FilterMenu::new());
+                self.mode = Mode::FilterMenu;
+            }
+            Action::PopFilter => self.pop_filter(),
+            Action::CategoryFilter => self.toggle_category_filter(),
+            Action::SortNext => {
+                let columns = Column::shown(&self.config);
+                self.set_sort(Sort::by(self.sort.column.next(&columns)))
+            }
+            Action::SortReverse => self.set_sort(Sort {
+                descending: !self.sort.descending,
+                ..self.sort
+            }),
+            Action::Add => {
+                self.open_form(ExpenseForm::new(
+                    "Add expense",
+                    self.config.default_type.as_deref(),
+                ));
+            }
+            Action::Edit => self.open_edit_form(),
+            Action::EditCell => self.open_cell_editor(),
+            Action::PreviousColumn => {
+                self.column = self.column.previous(&Column::shown(&self.config))
+            }
+            Action::NextColumn => self.column = self.column.next(&Column::shown(&self.config)),
+            Action::ToggleColumn => self.pick_column(),
+            Action::Duplicate => self.open_duplicate_form(),
+            Action::Delete => self.confirm_delete(),
+            Action::Undo => {
+                let undone = self.undo(false);
+                self.report("undo", undone);
+            }
+            Action::Redo => {
+                let redone = self.undo(true);
+                self.report("redo", redone);
+            }
+            Action::Mark => self.toggle_mark(),
+            Action::ClearMarks => self.marked.clear(),
+            Action::Retype if !self.bulk_rows().is_empty() => {
+                self.mode = Mode::BulkEdit(BulkField::Type)
+            }
+            Action::Retag if !self.bulk_rows().is_empty() => {
+                self.mode = Mode::BulkEdit(BulkField::Tags)
+            }
+            Action::Export if !self.marked.is_empty() => {
+                let exported = self.export_marked();
+                self.report("export", exported);
+            }
+            Action::ExportView => self.open_export_view(),
+            Action::HideAmounts => {
+                self.config.hide_amounts = !self.config.hide_amounts;
+                self.toast = Some(Toast::info(if self.config.hide_amounts {
+                    "Amounts hidden"
+                } else {
+                    "Amounts shown"
+                }));
+            }
+            Action::CopyText | Action::CopyCsv => {
+                let copied = self.copy_to_clipboard(action == Action::CopyCsv);
+                self.report("copy", copied);
+            }
+            Action::SwitchChart => {
+                self.chart.next_kind();
+                self.toast = Some(Toast::info(format!(
+                    "Charts show the {}",
+                    self.chart.kind.label()
+                )));
+            }
+            Action::FocusCharts => {
+                self.chart.focused = !self.chart.focused;
+                if self.chart.focused {
+                    self.tab = Tab::Transactions;
+                    self.toast = Some(Toast::info(
+                        "Left and Right scroll the charts, Esc goes back to the table",
+                    ));
+                }
+            }
+            Action::ChartMetric => {
+                self.chart.metric = self.chart.metric.next();
+                self.toast = Some(Toast::info(format!(
+                    "Charts show the {}",
+                    self.chart.metric.label()
+                )));
+            }
+            Action::NextTab => self.tab = self.tab.next(),
+            Action::PreviousTab => self.tab = self.tab.previous(),
+            Action::Import => self.open_import(),
+            Action::Command => self.mode = Mode::Command,
+            Action::Help => self.mode = Mode::Help,
+            Action::Quit => return true,
+            Action::Retype | Action::Retag | Action::Export => {}
+        }
+        false
+    }
+
+    /// Logs and shows why an action failed.
+    fn report(&mut self, action: &str, result: Result<(), Box<dyn std::error::Error>>) {
+        if let Err(err) = result {
+            error!("Couldn't {}: {}", action, err);
+            self.toast = Some(Toast::error(format!("Couldn't {}: {}", action, err)));
+        }
+    }
+
+    /// The position into `expenses` of the selected row, `None` on a group's header.
+    fn selected_row(&self) -> Option<usize> {
+        match self
+            .table_state
+            .selected()
+            .and_then(|selected| self.lines.get(selected))
+        {
+            Some(TableLine::Expense(row)) => Some(*row),
+            _ => None,
+        }
+    }
+
+    /// The line selected in the table.
+    fn selected_line(&self) -> Option<&TableLine> {
+        self.table_state
+            .selected()
+            .and_then(|selected| self.lines.get(selected))
+    }
+
+    fn handle_form_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let Some(form) = self.form.as_mut() else {
+            return;
+        };
+        let row = form.row;
+        match form.handle_key(code, modifiers, self.config.date_order) {
+            FormAction::Continue => {}
+            // Typing into the whole form again is more work than a cell, so ask first
+            FormAction::Cancel if form.is_changed() && !form.inline => {
+                self.modal = Some(
+                    Modal::new(
+                        "Discard the changes?",
+                        vec![Line::raw("What was typed into the form hasn't been saved.")],
+                        vec![
+                            Button::new("Discard", Some('d'), Choice::Confirm),
+                            Button::new("Keep editing", Some('k'), Choice::Dismiss),
+                        ],
+                    )
+                    .selected(1),
+                );
+                self.mode = Mode::Dialog(Dialog::DiscardForm);
+            }
+            FormAction::Cancel => {
+                self.form = None;
+                self.mode = Mode::Normal;
+            }
+            FormAction::Submit(expense) => {
+                let added = row.is_none().then(|| expense.clone());
+                let saved = self.save_form(row, expense);
+                match saved {
+                    Ok(()) => {
+                        self.form = None;
+                        self.mode = Mode::Normal;
+                        self.toast = Some(Toast::info(match row {
+                            Some(_) => "Saved the expense",
+                            None => "Added the expense",
+                        }));
+                        // Adding to a budgeted type that is close to spent or overspent
+                        let warning = added.and_then(|expense| {
+                            budget::of_expense(&self.config, &self.expenses, &expense)?
+                                .warning(&self.config)
+                        });
+                        if let Some(warning) = warning {
+                            self.toast = Some(Toast::warning(warning));
+                        }
+                    }
+                    Err(err) => {
+                        error!("Error saving expense: {}", err);
+                        if let Some(form) = self.form.as_mut() {
+                            form.set_error(format!("Couldn't save: {}", err));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Saves the expense of the form, to `row` if editing. If the database changed on disk in the
+    /// meantime it is reloaded first, and the expense that was edited looked for in it; if it is
+    /// gone the expense is added instead.
+    fn save_form(
+        &mut self,
+        row: Option<usize>,
+        expense: Expense,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut row = row;
+        if self.disk_changed {
+            let edited = row.map(|row| self.expenses[row].to_csv_record());
+            self.reload()?;
+            row = edited.and_then(|edited| {
+                self.expenses
+                    .iter()
+                    .position(|expense| expense.to_csv_record() == edited)
+            });
+        }
+        match row {
+            Some(row) => self.edit_expense(row, expense),
+            None => self.add_expense(expense),
+        }
+    }
+
+    /// Inserts pasted text into whatever is being typed.
+    fn paste(&mut self, text: &str) {
+        match self.mode {
+            Mode::Search => {
+                self.search_input.paste(text);
+                self.apply_search();
+            }
+            Mode::Form => {
+                if let Some(form) = self.form.as_mut() {
+                    form.values[form.focus].paste(text);
+                }
+            }
+            Mode::BulkEdit(_) => self.bulk_input.paste(text),
+            Mode::GotoDate => self.goto_input.paste(text),
+            Mode::ExportView => self.export_input.paste(text),
+            Mode::EditSetting => self.setting_input.paste(text),
+            Mode::Command => self.command_input.paste(text),
+            Mode::Import => {
+                if let Some(import) = self.import.as_mut() {
+                    import.paste(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Selects the row at `index` in the table, or the last one if there are fewer.
+    fn select_index(&mut self, index: usize) {
+        if !self.lines.is_empty() {
+            self.table_state
+                .select(Some(index.min(self.lines.len() - 1)));
+        }
+    }
+
+    /// Moves the selection by `delta` rows, stopping at the first and last row.
+    fn move_selection(&mut self, delta: isize) {
+        if let Some(selected) = self.table_state.selected() {
+            self.select_index(selected.saturating_add_signed(delta));
+        }
+    }
+
+    fn select_next(&mut self) {
+        if let Some(selected) = self.table_state.selected() {
+            let next_index = if selected + 1 >= self.lines.len() {
+                0
+            } else {
+                selected + 1
+            };
+            self.table_state.select(Some(next_index));
+        }
+    }
+
+    fn select_previous(&mut self) {
+        if let Some(selected) = self.table_state.selected() {
+            let next_index = if selected == 0 {
+                self.lines.len().saturating_sub(1)
+            } else {
+                selected - 1
+            };
+            self.table_state.select(Some(next_index));
+        }
+    }
+}
+
+/// Draws the TUI until the user quits.
+pub fn run<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
+    let mut should_quit = false;
+    while !should_quit {
+        if app.toast.as_ref().is_some_and(Toast::expired) {
+            app.toast = None;
+        }
+        app.check_disk();
+        terminal.draw(|f| ui(f, app))?;
+        should_quit = handle_events(app)?;
+    }
+    Ok(())
+}
+
+fn handle_events(app: &mut App) -> io::Result<bool> {
+    if event::poll(std::time::Duration::from_millis(50))? {
+        let event = event::read()?;
+        if let Event::Mouse(mouse) = event {
+            if app.mode == Mode::Normal {
+                handle_mouse(app, mouse);
+            }
+        } else if let Event::Paste(text) = event {
+            app.paste(&text);
+        } else if let Event::Resize(width, height) = event {
+            // The next draw lays everything out for the new size
+            trace!("Resized to {}x{}", width, height);
+        } else if let Event::Key(KeyEvent {
+            kind: KeyEventKind::Press,
+            code,
+            modifiers,
+            ..
+        }) = event
+        {
+            debug!("Read in key: {:?}", code);
+            match app.mode {
+                Mode::Normal => {
+                    let count = app.count.take();
+                    let pending_key = app.pending_key.take();
+                    match (code, app.keymap.lookup(pending_key, code, modifiers)) {
+                        // A count for the next motion, as in `5j`
+                        (KeyCode::Char(digit @ '0'..='9'), _)
+                            if pending_key.is_none() && (digit != '0' || count.is_some()) =>
+                        {
+                            let digit = digit.to_digit(10).unwrap_or(0) as usize;
+                            app.count =
+                                Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                        }
+                        (_, Lookup::Action(action)) => return Ok(app.perform(action, count)),
+                        (_, Lookup::Prefix(key)) => {
+                            app.pending_key = Some(key);
+                            app.count = count;
+                        }
+                        (_, Lookup::None) => {}
+                    }
+                }
+                Mode::Search => match code {
+                    KeyCode::Enter => {
+                        app.keep_search();
+                        app.mode = Mode::Normal;
+                    }
+                    KeyCode::Esc => {
+                        app.search_input.clear();
+                        app.apply_search();
+                        app.mode = Mode::Normal;
+                    }
+                    KeyCode::Tab => {
+                        app.search_kind = app.search_kind.next();
+                        app.apply_search();
+                    }
+                    KeyCode::Down => app.select_next(),
+                    KeyCode::Up => app.select_previous(),
+                    _ => {
+                        if app.search_input.handle_key(code, modifiers) {
+                            app.apply_search();
+                        }
+                    }
+                },
+                Mode::Form => app.handle_form_key(code, modifiers),
+                Mode::Import => app.handle_import_key(code, modifiers),
+                // Any key closes the help
+                Mode::Help => app.mode = Mode::Normal,
+                Mode::Dialog(dialog) => {
+                    if let Some(choice) =
+                        app.modal.as_mut().and_then(|modal| modal.handle_key(code))
+                    {
+                        app.answer(dialog, choice);
+                    }
+                }
+                Mode::FilterMenu => {
+                    if let Some(menu) = app.menu.as_mut() {
+                        match menu.handle_key(code, &app.expenses, &app.config.filters) {
+                            MenuAction::Continue => {}
+                            MenuAction::Pick(query) => {
+                                let filtered = app.push_filter(SearchKind::Query, query);
+                                app.report("filter", filtered);
+                                app.menu = None;
+                                app.mode = Mode::Normal;
+                            }
+                            MenuAction::Close => {
+                                app.menu = None;
+                                app.mode = Mode::Normal;
+                            }
+                        }
+                    }
+                }
+                Mode::PickCategory => {
+                    if let Some(picker) = app.picker.as_mut() {
+                        match picker.handle_key(code, modifiers) {
+                            PickerAction::Continue => {}
+                            PickerAction::Pick(query) => {
+                                app.category_filter = Some(query.clone());
+                                let filtered = app.push_filter(SearchKind::Query, query);
+                                app.report("filter", filtered);
+                                app.picker = None;
+                                app.mode = Mode::Normal;
+                            }
+                            PickerAction::Cancel => {
+                                app.picker = None;
+                                app.mode = Mode::Normal;
+                            }
+                        }
+                    }
+                }
+                Mode::PickColumn => {
+                    if let Some(picker) = app.picker.as_mut() {
+                        match picker.handle_key(code, modifiers) {
+                            PickerAction::Continue => {}
+                            PickerAction::Pick(name) => {
+                                let toggled = app.toggle_column(&name);
+                                app.report("change the columns", toggled);
+                                app.picker = None;
+                                app.mode = Mode::Normal;
+                            }
+                            PickerAction::Cancel => {
+                                app.picker = None;
+                                app.mode = Mode::Normal;
+                            }
+                        }
+                    }
+                }
+                Mode::BulkEdit(field) => match code {
+                    KeyCode::Enter => {
+                        let value = app.bulk_input.take();
+                        let edited = app.bulk_edit(field, &value);
+                        app.report("update", edited);
+                        app.mode = Mode::Normal;
+                    }
+                    KeyCode::Esc => {
+                        app.bulk_input.clear();
+                        app.mode = Mode::Normal;
+                    }
+                    _ => {
+                        app.bulk_input.handle_key(code, modifiers);
+                    }
+                },
+                Mode::Triage => match code {
+                    KeyCode::Char(number @ '1'..='9') => {
+                        let number = number.to_digit(10).unwrap_or(0) as usize;
+                        let triaged = app.triage(number);
+                        app.report("set the type", triaged);
+                    }
+                    KeyCode::Esc => app.mode = Mode::Normal,
+                    // Moving around, undoing and leaving with the key that started it
+                    _ => match app.keymap.lookup(None, code, modifiers) {
+                        Lookup::Action(Action::Triage | Action::Quit) => app.mode = Mode::Normal,
+                        Lookup::Action(
+                            action @ (Action::Down
+                            | Action::Up
+                            | Action::PageDown
+                            | Action::PageUp
+                            | Action::Last
+                            | Action::Undo
+                            | Action::Redo),
+                        ) => {
+                            app.perform(action, None);
+                        }
+                        _ => {}
+                    },
+                },
+                Mode::GotoDate => match code {
+                    // A `g` right away goes to the top, as `gg` does in vim
+                    KeyCode::Char('g') if app.goto_input.is_empty() => {
+                        app.select_index(0);
+                        app.mode = Mode::Normal;
+                    }
+                    // A date that can't be read stays to be corrected
+                    KeyCode::Enter => match app.goto(app.goto_input.value().to_string().as_str()) {
+                        Ok(()) => {
+                            app.goto_input.clear();
+                            app.mode = Mode::Normal;
+                        }
+                        Err(err) => app.goto_input.error = Some(err.to_string()),
+                    },
+                    KeyCode::Esc => {
+                        app.goto_input.clear();
+                        app.mode = Mode::Normal;
+                    }
+                    _ => {
+                        app.goto_input.handle_key(code, modifiers);
+                    }
+                },
+                Mode::ExportView => match code {
+                    // A file that can't be written stays to be corrected
+                    KeyCode::Enter => {
+                        let path = PathBuf::from(app.export_input.value().trim());
+                        let rows = app.rows.clone();
+                        match app.export_rows(&path, &rows) {
+                            Ok(()) => {
+                                app.export_input.clear();
+                                app.mode = Mode::Normal;
+                            }
+                            Err(err) => app.export_input.error = Some(err.to_string()),
+                        }
+                    }
+                    KeyCode::Esc => {
+                        app.export_input.clear();
+                        app.mode = Mode::Normal;
+                    }
+                    KeyCode::Tab => app.next_export_format(),
+                    _ => {
+                        app.export_input.handle_key(code, modifiers);
+                    }
+                },
+                Mode::EditSetting => match code {
+                    // An invalid value stays to be corrected
+                    KeyCode::Enter => match app.save_setting() {
+                        Ok(()) => {
+                            app.setting_input.clear();
+                            app.mode = Mode::Normal;
+                        }
+                        Err(err) => app.setting_input.error = Some(err.to_string()),
+                    },
+                    KeyCode::Esc => {
+                        app.setting_input.clear();
+                        app.mode = Mode::Normal;
+                    }
+                    _ => {
+                        app.setting_input.handle_key(code, modifiers);
+                    }
+                },
+                Mode::Command => match code {
+                    // A command that can't be read stays to be corrected, one that fails is
+                    // reported
+                    KeyCode::Enter => match Command::parse(app.command_input.value()) {
+                        Ok(Command::Quit) => return Ok(true),
+                        Ok(command) => {
+                            app.command_input.clear();
+                            app.mode = Mode::Normal;
+                            let result = app.run_command(command);
+                            app.report("run the command", result);
+                        }
+                        Err(err) => app.command_input.error = Some(err.to_string()),
+                    },
+                    KeyCode::Esc => {
+                        app.command_input.clear();
+                        app.mode = Mode::Normal;
+                    }
+                    // Deleting past the `:` leaves the command line, like in vim
+                    KeyCode::Backspace if app.command_input.is_empty() => app.mode = Mode::Normal,
+                    _ => {
+                        app.command_input.handle_key(code, modifiers);
+                    }
+                },
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Whether both lists hold the same expenses in the same order.
+fn same_records(a: &[Expense], b: &[Expense]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a.to_csv_record() == b.to_csv_record())
+}
+
+/// Applies the tags typed for a bulk edit: words starting with `+` are added and ones starting
+/// with `-` removed, otherwise the tags are replaced.
+fn edit_tags(tags: &[String], input: &str) -> Vec<String> {
+    let words = Expense::parse_tags(input);
+    if !words
+        .iter()
+        .any(|word| word.starts_with('+') || word.starts_with('-'))
+    {
+        return words;
+    }
+
+    let mut tags = tags.to_vec();
+    for word in words {
+        if let Some(tag) = word.strip_prefix('-') {
+            tags.retain(|existing| !existing.eq_ignore_ascii_case(tag));
+        } else {
+            let tag = word.trim_start_matches('+');
+            if !tag.is_empty()
+                && !tags
+                    .iter()
+                    .any(|existing| existing.eq_ignore_ascii_case(tag))
+            {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+    tags
+}
+
+/// Where parts of the screen were drawn, to find what a mouse click hits.
+#[derive(Debug, Default)]
+pub struct HitAreas {
+    pub header: Rect,
+    /// The column titles' horizontal extent
+    pub columns: Vec<(Column, Range<u16>)>,
+    /// Where the rows of the table are drawn, below the header
+    pub rows: Rect,
+    /// The lines each row drawn takes, and its position in the table
+    pub row_lines: Vec<(Range<u16>, usize)>,
+    /// Each bar, slice or dot of the charts and what clicking it does
+    pub charts: Vec<(Rect, Target)>,
+    /// How many bars of the charts can be scrolled past
+    pub chart_scroll: usize,
+    /// Each day of the heatmap in the Reports tab
+    pub days: Vec<(Rect, NaiveDate)>,
+}
+
+/// Selects a row with a click, scrolls with the wheel, sorts by clicking a column title,
+/// filters by a type or month by clicking its bar, selects the entry of a dot clicked in the
+/// scatter chart or shows the expenses of a day clicked in the heatmap.
+fn handle_mouse(app: &mut App, event: MouseEvent) {
+    let position = Rect::new(event.column, event.row, 1, 1);
+    match event.kind {
+        MouseEventKind::ScrollDown => app.move_selection(3),
+        MouseEventKind::ScrollUp => app.move_selection(-3),
+        MouseEventKind::Down(MouseButton::Left) => {
+            if app.areas.rows.intersects(position) {
+                if let Some((_, index)) = app
+                    .areas
+                    .row_lines
+                    .iter()
+                    .find(|(lines, _)| lines.contains(&position.y))
+                {
+                    app.table_state.select(Some(*index));
+                }
+                if let Some((column, _)) = app
+                    .areas
+                    .columns
+                    .iter()
+                    .find(|(_, range)| range.contains(&position.x))
+                {
+                    app.column = *column;
+                }
+            } else if app.areas.header.intersects(position) {
+                let clicked = app
+                    .areas
+                    .columns
+                    .iter()
+                    .find(|(_, range)| range.contains(&position.x))
+                    .map(|(column, _)| *column);
+                if let Some(column) = clicked {
+                    app.set_sort(if app.sort.column == column {
+                        Sort {
+                            descending: !app.sort.descending,
+                            ..app.sort
+                        }
+                    } else {
+                        Sort::by(column)
+                    });
+                }
+            } else if let Some(&(_, day)) = app
+                .areas
+                .days
+                .iter()
+                .find(|(area, _)| area.intersects(position))
+            {
+                app.show_day(day);
+            } else if let Some((_, target)) = app
+                .areas
+                .charts
+                .iter()
+                .find(|(area, _)| area.intersects(position))
+            {
+                match target.clone() {
+                    Target::Filter(query) => {
+                        let filtered = app.push_filter(SearchKind::Query, query);
+                        app.report("filter", filtered);
+                    }
+                    Target::Entry(index) => {
+                        if let Some(&row) = app.rows.get(index) {
+                            app.chart.focused = false;
+                            app.select_row(row);
+                        }
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A rectangle of the given size centered in `area`, shrunk to fit if needed.
+pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Builds a cell's text, highlighting the parts matched by the active searches and filters.
+fn highlighted<'a>(text: &'a str, field: Field, highlighters: &[&dyn Highlight]) -> Line<'a> {
+    let mut ranges: Vec<Range<usize>> = highlighters
+        .iter()
+        .flat_map(|highlighter| highlighter.highlights(field, text))
+        .filter(|range| text.is_char_boundary(range.start) && text.is_char_boundary(range.end))
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+
+    let mut spans = Vec::new();
+    let mut position = 0;
+    for range in ranges {
+        if range.end <= position {
+            continue;
+        }
+        let start = range.start.max(position);
+        if start > position {
+            spans.push(Span::raw(&text[position..start]));
+        }
+        spans.push(Span::styled(
+            &text[start..range.end],
+            Style::default().yellow().underlined(),
+        ));
+        position = range.end;
+    }
+    if position < text.len() {
+        spans.push(Span::raw(&text[position..]));
+    }
+    Line::from(spans)
+}
+
+fn ui(frame: &mut Frame, app: &mut App) {
+    // The table state is taken out, as the rest of the app is borrowed while drawing
+    let mut table_state = std::mem::take(&mut app.table_state);
+    app.areas = draw(frame, app, &mut table_state);
+    app.table_state = table_state;
+}
+
+/// Draws the app, returning where the table and charts ended up.
+fn draw(frame: &mut Frame, app: &App, table_state: &mut TableState) -> HitAreas {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(app.read_only as u16),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+    let (tabs_area, banner_area, body, status_area) = (chunks[0], chunks[1], chunks[2], chunks[3]);
+
+    if app.read_only {
+        frame.render_widget(
+            Paragraph::new(format!(
+                " {}: changes can't be saved until it is closed and this one restarted ",
+                READ_ONLY
+            ))
+            .style(Style::default().black().on_yellow().bold()),
+            banner_area,
+        );
+    }
+
+    let tabs = Tabs::new(Tab::ALL.map(Tab::title))
+        .select(app.tab.index())
+        .highlight_style(Style::default().reversed())
+        .padding(" ", " ");
+    frame.render_widget(tabs, tabs_area.inner(&Margin::new(1, 0)));
+
+    let body = body.inner(&Margin::new(2, 1));
+    let mut areas = HitAreas::default();
+    match app.tab {
+        Tab::Dashboard => dashboard::render(
+            frame,
+            &app.config,
+            &app.visible(),
+            &budget::progress(&app.config, &app.expenses, Local::now().date_naive()),
+            Local::now().date_naive(),
+            body,
+        ),
+        Tab::Transactions => areas = draw_transactions(frame, app, table_state, body),
+        Tab::Reports => {
+            areas.days = reports::render(
+                frame,
+                &app.config,
+                &app.visible(),
+                app.calendar_day,
+                Local::now().date_naive(),
+                body,
+            )
+        }
+        Tab::Budgets => budgets::render(
+            frame,
+            &app.config,
+            &app.expenses,
+            &budget::progress(&app.config, &app.expenses, Local::now().date_naive()),
+            Local::now().date_naive(),
+            body,
+        ),
+        Tab::Recurring => recurring::render(
+            frame,
+            &app.config,
+            &app.expenses.iter().collect::<Vec<_>>(),
+            Local::now().date_naive(),
+            body,
+        ),
+        Tab::Settings => settings::render(
+            frame,
+            &settings::settings(&app.config, &app.keymap),
+            app.settings_selected,
+            body,
+        ),
+    }
+
+    if let Some(form) = app.form.as_ref().filter(|form| !form.inline) {
+        form.render(frame);
+    }
+    if let Some(menu) = &app.menu {
+        menu.render(frame);
+    }
+    if let Some(import) = &app.import {
+        import.render(frame);
+    }
+    if let Some(picker) = &app.picker {
+        let area = centered_rect(Picker::WIDTH, picker.height(), frame.size());
+        picker.render(frame, area.x, area.y);
+    }
+    if app.mode == Mode::Command {
+        render_command_line(frame, app, status_area);
+    } else if app.mode == Mode::EditSetting {
+        render_setting_line(frame, app, status_area);
+    } else if app.mode == Mode::GotoDate {
+        render_goto_line(frame, app, status_area);
+    } else if app.mode == Mode::ExportView {
+        render_export_line(frame, app, status_area);
+    } else if app.mode == Mode::Triage {
+        render_triage_line(frame, app, status_area);
+    } else {
+        render_status_bar(frame, app, table_state.selected(), status_area);
+    }
+
+    match app.mode {
+        Mode::BulkEdit(field) => render_bulk_edit(frame, app, field),
+        Mode::Help => render_help(frame, app),
+        _ => {}
+    }
+    if let Some(modal) = &app.modal {
+        modal.render(frame);
+    }
+
+    if let Some(toast) = &app.toast {
+        toast.render(frame, body);
+    }
+
+    areas
+}
+
+/// The color of text drawn over the charts, readable on the theme's background.
+fn text_color(theme: Theme) -> Color {
+    match theme {
+        Theme::Dark => Color::White,
+        Theme::Light => Color::Black,
+    }
+}
+
+/// The color picking out keys and other things to notice.
+fn accent_color(theme: Theme) -> Color {
+    match theme {
+        Theme::Dark => Color::Yellow,
+        Theme::Light => Color::Blue,
+    }
+}
+
+/// How rows marked with Space stand out.
+fn mark_style(theme: Theme) -> Style {
+    match theme {
+        Theme::Dark => Style::default().on_dark_gray().bold(),
+        Theme::Light => Style::default().on_gray().bold(),
+    }
+}
+
+/// Draws the table with the totals below it and the charts beside it.
+fn draw_transactions(
+    frame: &mut Frame,
+    app: &App,
+    table_state: &mut TableState,
+    area: Rect,
+) -> HitAreas {
+    let mut areas = HitAreas::default();
+    let expenses = app.visible();
+    let highlighters: Vec<&dyn Highlight> = app
+        .cli_filters
+        .iter()
+        .chain(app.filters.iter().map(|filter| &filter.filter))
+        .chain(app.search.as_ref())
+        .map(Filter::highlighter)
+        .collect();
+    let highlighters = highlighters.as_slice();
+    let sparklines = if app.grouping == Grouping::Type {
+        group::sparklines(&app.rows, &app.expenses, Local::now().date_naive())
+    } else {
+        HashMap::new()
+    };
+
+    // The charts beside the table when there is room, else below it, or left out altogether
+    // in a small terminal so the table stays readable
+    let chunks = if area.width >= SIDE_BY_SIDE_WIDTH {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(area)
+    } else if area.height >= STACKED_HEIGHT {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(area)
+    } else {
+        Layout::default()
+            .constraints([Constraint::Min(0), Constraint::Length(0)].as_ref())
+            .split(area)
+    };
+
+    // Calculate the total sum of amounts
+    let total_amount: f64 = expenses.iter().map(|expense| expense.amount).sum();
+    let total_spent: f64 = expenses
+        .iter()
+        .filter(|expense| expense.amount < 0.0)
+        .map(|expense| expense.amount)
+        .sum();
+    let total_earned: f64 = expenses
+        .iter()
+        .filter(|expense| expense.amount >= 0.0)
+        .map(|expense| expense.amount)
+        .sum();
+
+    // The totals take the lines they need when the height is short
+    let totals_height = if chunks[0].height >= STACKED_HEIGHT {
+        Constraint::Percentage(30)
+    } else {
+        Constraint::Length(4)
+    };
+    let table_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), totals_height].as_ref())
+        .split(chunks[0]);
+
+    // Below the table show the search bar while searching, and a summary of the filters
+    let show_search_bar = app.mode == Mode::Search || !app.search_input.is_empty();
+    let search_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(3),
+                Constraint::Length(if show_search_bar { 3 } else { 0 }),
+            ]
+            .as_ref(),
+        )
+        .split(table_chunks[0]);
+    let table_area = search_chunks[0];
+    if show_search_bar {
+        render_search_bar(frame, app, search_chunks[1]);
+    }
+
+    // Expense Table, built only from the rows that fit so large ledgers stay fast. The borders
+    // and header take up three lines.
+    let page_height = table_area.height.saturating_sub(3);
+    let shown = Column::shown(&app.config);
+
+    // The columns are as wide as what they show in the rows that would fit on a line each, and
+    // the description takes what they leave
+    let estimate = scroll_window(
+        &mut table_state.clone(),
+        app.lines.len(),
+        page_height as usize,
+        |_| 1,
+    );
+    let widths = column_widths(table_area.width, &shown, |column| {
+        let widest = app.lines[estimate.clone()]
+            .iter()
+            .map(|line| match line {
+                TableLine::Expense(row) => {
+                    let expense = &app.expenses[*row];
+                    match column {
+                        Column::Date => app.config.format_date(&expense.date).chars().count(),
+                        Column::Description => 0,
+                        Column::Type => expense.expense_type.chars().count() + 2,
+                        Column::Amount => {
+                            masked(app.config.hide_amounts, expense.amount.to_string())
+                                .chars()
+                                .count()
+                                + if app.unusual.contains(row) { 2 } else { 0 }
+                        }
+                        Column::Tags => expense.tags.join(" ").chars().count(),
+                    }
+                }
+                TableLine::Group { key, total, .. } => match column {
+                    _ if Some(&column) == shown.first() => key.chars().count() + 2,
+                    Column::Type => sparklines.get(key).map_or(0, |line| line.chars().count()),
+                    Column::Amount => app.config.money(*total).chars().count(),
+                    _ => 0,
+                },
+            })
+            .max()
+            .unwrap_or(0);
+        // Room for the title and the sort order's arrow
+        widest.max(column.title().chars().count() + 2) as u16
+    });
+
+    // Mirror how the table lays out its columns, after the space for the highlight symbol
+    let inner = table_area.inner(&Margin::new(1, 1));
+    let selection_width = if table_state.selected().is_some() {
+        2
+    } else {
+        0
+    };
+    let columns = Layout::horizontal(widths.clone())
+        .spacing(1)
+        .split(Rect::new(
+            inner.x + selection_width,
+            inner.y,
+            inner.width.saturating_sub(selection_width),
+            1,
+        ));
+    let column_width = |column: Column| {
+        shown
+            .iter()
+            .position(|&shown| shown == column)
+            .map_or(0, |position| columns[position].width as usize)
+    };
+    let description_width = column_width(Column::Description);
+
+    // Long descriptions take two lines when wrapping
+    let line_height = |index: usize| match &app.lines[index] {
+        TableLine::Expense(row)
+            if app.config.wrap
+                && app.expenses[*row].description.chars().count() > description_width =>
+        {
+            2
+        }
+        _ => 1,
+    };
+    let window = scroll_window(
+        table_state,
+        app.lines.len(),
+        page_height as usize,
+        line_height,
+    );
+    let rows = app.lines[window.clone()]
+        .iter()
+        .enumerate()
+        .map(|(offset, line)| {
+            let row = match line {
+                TableLine::Expense(row) => row,
+                TableLine::Group {
+                    key,
+                    count,
+                    total,
+                    collapsed,
+                } => {
+                    // The key in the first column, and the count, trend and total in the
+                    // description, type and amount columns wherever they are shown
+                    let cells = shown.iter().enumerate().map(|(position, column)| {
+                        match column {
+                            _ if position == 0 => Cell::from(format!(
+                                "{} {}",
+                                if *collapsed { "▸" } else { "▾" },
+                                key
+                            )),
+                            Column::Description => match count {
+                                1 => "1 expense".to_string(),
+                                count => format!("{} expenses", count),
+                            }
+                            .into(),
+                            // In the type's color when grouping by type
+                            Column::Type => Cell::from(
+                                sparklines.get(key).cloned().unwrap_or_default(),
+                            )
+                            .style(Style::default().fg(palette::type_color(&app.config, key))),
+                            Column::Amount => app.config.money(*total).into(),
+                            Column::Date | Column::Tags => Cell::default(),
+                        }
+                    });
+                    return Row::new(cells)
+                        .style(Style::default().fg(accent_color(app.config.theme)).bold());
+                }
+            };
+            let expense = &app.expenses[*row];
+            let style = if app.marked.contains(row) {
+                mark_style(app.config.theme)
+            } else {
+                Style::default()
+            };
+            // The type after a mark in its color, and spending in red and income in green,
+            // unless the amounts are hidden
+            let mut expense_type = highlighted(&expense.expense_type, Field::Type, highlighters);
+            expense_type.spans.insert(
+                0,
+                Span::styled(
+                    "■ ",
+                    Style::default().fg(palette::type_color(&app.config, &expense.expense_type)),
+                ),
+            );
+            let amount_style = match app.config.hide_amounts {
+                true => Style::default(),
+                false if expense.amount < 0.0 => Style::default().red(),
+                false => Style::default().green(),
+            };
+            let description = highlighted(&expense.description, Field::Description, highlighters);
+            let description = match line_height(window.start + offset) {
+                2 => {
+                    let (first, rest) = wrap_line(description, description_width);
+                    Text::from(vec![first, truncate_line(rest, description_width)])
+                }
+                _ => Text::from(truncate_line(description, description_width)),
+            };
+            let mut cells: Vec<Cell> = shown
+                .iter()
+                .map(|column| match column {
+                    Column::Date => Cell::from(app.config.format_date(&expense.date)),
+                    Column::Description => Cell::from(description.clone()),
+                    Column::Type => Cell::from(truncate_line(
+                        expense_type.clone(),
+                        column_width(Column::Type),
+                    )),
+                    // Unusual amounts for the type are marked, as they are likely mistakes
+                    Column::Amount if app.unusual.contains(row) => Cell::from(Line::from(vec![
+                        Span::styled(
+                            masked(app.config.hide_amounts, expense.amount.to_string()),
+                            amount_style,
+                        ),
+                        Span::styled(" !", Style::default().yellow().dim()),
+                    ])),
+                    Column::Amount => {
+                        Cell::from(masked(app.config.hide_amounts, expense.amount.to_string()))
+                            .style(amount_style)
+                    }
+                    Column::Tags => Cell::from(truncate_line(
+                        Line::from(expense.tags.join(" ")),
+                        column_width(Column::Tags),
+                    )),
+                })
+                .collect();
+            // The selected cell, edited with `i`
+            if table_state.selected() == Some(window.start + offset) {
+                if let Some(position) = shown.iter().position(|&column| column == app.column) {
+                    let cell = &mut cells[position];
+                    *cell = std::mem::take(cell).underlined();
+                }
+            }
+            Row::new(cells)
+                .height(line_height(window.start + offset) as u16)
+                .style(style)
+        })
+        .collect::<Vec<Row>>();
+
+    let expense_table = Table::new(rows, widths)
+        .block(Block::default().borders(Borders::ALL))
+        .header(
+            Row::new(shown.iter().map(|&column| app.sort.header(column)))
+                .style(Style::default().bold()),
+        )
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">>");
+
+    let mut window_state = TableState::default().with_selected(
+        table_state
+            .selected()
+            .map(|selected| selected - window.start),
+    );
+    frame.render_stateful_widget(expense_table, table_area, &mut window_state);
+
+    areas.header = Rect::new(inner.x, inner.y, inner.width, inner.height.min(1));
+    areas.columns = shown
+        .iter()
+        .zip(columns.iter())
+        .map(|(&column, area)| (column, area.x..area.right()))
+        .collect();
+    areas.rows = Rect::new(inner.x, inner.y + 1, inner.width, page_height);
+    let mut top = areas.rows.y;
+    areas.row_lines = window
+        .clone()
+        .map(|index| {
+            let lines = top..top + line_height(index) as u16;
+            top = lines.end;
+            (lines, index)
+        })
+        .collect();
+
+    // Beside the totals, each type's share of the spending, the most spent on first, on as
+    // many lines as there are totals
+    let mut types: HashMap<String, f64> = HashMap::new();
+    for expense in expenses.iter().filter(|expense| expense.amount < 0.0) {
+        *types
+            .entry(capitalize(expense.expense_type.clone()))
+            .or_default() -= expense.amount;
+    }
+    let mut types: Vec<(String, f64)> = types.into_iter().collect();
+    types.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let shares_width = table_chunks[1].width.saturating_sub(16 + 11 + 2) as usize;
+    let mut shares: Vec<Line> = vec![Line::default(); 3];
+    let (mut line, mut used) = (0, 0);
+    for (expense_type, spent) in &types {
+        let text = format!(
+            "{} {}",
+            expense_type,
+            crate::report::share(*spent, -total_spent)
+        );
+        let width = text.chars().count() + 2;
+        if used + width > shares_width {
+            line += 1;
+            used = 0;
+        }
+        if line == shares.len() || width > shares_width {
+            break;
+        }
+        shares[line].spans.push(Span::styled(
+            text,
+            Style::default().fg(palette::type_color(&app.config, expense_type)),
+        ));
+        shares[line].spans.push(Span::raw("  "));
+        used += width;
+    }
+    let mut shares = shares.into_iter();
+
+    // The labels end where the type column would, so they fit even when it is left out
+    let rows = vec![
+        Row::new(vec![
+            Cell::from(shares.next().unwrap_or_default()),
+            Cell::from("Net Total Spent"),
+            Cell::from(app.config.money(total_amount)),
+        ])
+        .style(Style::default().bold())
+        .top_margin(1),
+        Row::new(vec![
+            Cell::from(shares.next().unwrap_or_default()),
+            Cell::from("Total Spent"),
+            Cell::from(app.config.money(total_spent)),
+        ])
+        .style(Style::default().bold()),
+        Row::new(vec![
+            Cell::from(shares.next().unwrap_or_default()),
+            Cell::from("Total Earned"),
+            Cell::from(app.config.money(total_earned)),
+        ])
+        .style(Style::default().bold()),
+    ];
+
+    let data_table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(16),
+            Constraint::Length(11),
+        ],
+    );
+
+    frame.render_widget(data_table, table_chunks[1]);
+
+    if !chunks[1].is_empty() {
+        (areas.charts, areas.chart_scroll) =
+            chart::render(frame, app.chart, &app.config, &expenses, chunks[1]);
+    }
+
+    if let (Some(form), Some(selected)) = (
+        app.form.as_ref().filter(|form| form.inline),
+        table_state.selected(),
+    ) {
+        let column = areas
+            .columns
+            .iter()
+            .find(|(column, _)| *column == app.column);
+        let row = areas.row_lines.iter().find(|(_, index)| *index == selected);
+        if let (Some((_, range)), Some((lines, _))) = (column, row) {
+            form.render_cell(
+                frame,
+                Rect::new(range.start, lines.start, range.end - range.start, 1),
+            );
+        }
+    }
+
+    areas
+}
+
+/// The widths of the `columns` of a table `width` columns wide, each as wide as the `widest`
+/// it shows up to a limit. The description takes what the others leave, and as the table
+/// narrows the limits of the date, type and tags shrink and then the type and tags are left
+/// out.
+fn column_widths(
+    width: u16,
+    columns: &[Column],
+    widest: impl Fn(Column) -> u16,
+) -> Vec<Constraint> {
+    // The date column also holds the names of the months when grouping by month
+    let (date, expense_type, tags) = match width {
+        100.. => (22, 20, 16),
+        70..=99 => (22, 12, 10),
+        _ => (15, 0, 0),
+    };
+    columns
+        .iter()
+        .map(|&column| match column {
+            Column::Date => Constraint::Length(widest(column).min(date)),
+            Column::Description => Constraint::Fill(1),
+            Column::Type => Constraint::Length(widest(column).min(expense_type)),
+            Column::Amount => Constraint::Length(widest(column).min(14)),
+            Column::Tags => Constraint::Length(widest(column).min(tags)),
+        })
+        .collect()
+}
+
+/// `line` split at its `at`th character, keeping the styles on both sides.
+fn split_line(line: Line<'_>, at: usize) -> (Line<'_>, Line<'_>) {
+    let (mut head, mut tail) = (Vec::new(), Vec::new());
+    let mut count = 0;
+    for span in line.spans {
+        let len = span.content.chars().count();
+        if count + len <= at {
+            head.push(span);
+        } else if count >= at {
+            tail.push(span);
+        } else {
+            let split = span
+                .content
+                .char_indices()
+                .nth(at - count)
+                .map_or(span.content.len(), |(index, _)| index);
+            let (first, second) = match span.content {
+                Cow::Borrowed(text) => {
+                    (Cow::Borrowed(&text[..split]), Cow::Borrowed(&text[split..]))
+                }
+                Cow::Owned(text) => (
+                    Cow::Owned(text[..split].to_string()),
+                    Cow::Owned(text[split..].to_string()),
+                ),
+            };
+            head.push(Span::styled(first, span.style));
+            tail.push(Span::styled(second, span.style));
+        }
+        count += len;
+    }
+    (
+        Line::from(head).style(line.style),
+        Line::from(tail).style(line.style),
+    )
+}
+
+/// The first `width` characters of `line`, ending in `…` where it is cut short.
+fn truncate_line(line: Line<'_>, width: usize) -> Line<'_> {
+    if line.width() <= width {
+        return line;
+    }
+    let (mut head, _) = split_line(line, width.saturating_sub(1));
+    if width > 0 {
+        head.spans.push(Span::raw("…"));
+    }
+    head
+}
+
+/// The words of `line` that fit in `width` characters, and what is left for the next line.
+fn wrap_line(line: Line<'_>, width: usize) -> (Line<'_>, Line<'_>) {
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    let space = text
+        .chars()
+        .take(width + 1)
+        .enumerate()
+        .filter(|(_, c)| *c == ' ')
+        .map(|(index, _)| index)
+        .last()
+        .filter(|&index| index > 0);
+    match space {
+        Some(space) => {
+            let (first, rest) = split_line(line, space);
+            (first, split_line(rest, 1).1)
+        }
+        None => split_line(line, width),
+    }
+}
+
+/// The rows of the table that fit in `height` lines, each taking the `line_height` of its
+/// position. The first one is kept in the offset of `table_state` and only moves as far as
+/// needed to keep the selected row in view.
+fn scroll_window(
+    table_state: &mut TableState,
+    len: usize,
+    height: usize,
+    line_height: impl Fn(usize) -> usize,
+) -> Range<usize> {
+    let height = height.max(1);
+    // The furthest the table scrolls, with its last row at the bottom
+    let (mut last_offset, mut used) = (len, 0);
+    while last_offset > 0 && used + line_height(last_offset - 1) <= height {
+        last_offset -= 1;
+        used += line_height(last_offset);
+    }
+    let mut offset = table_state.offset().min(last_offset);
+    if let Some(selected) = table_state.selected() {
+        if selected < offset {
+            offset = selected;
+        } else {
+            // Every row takes a line at least, so the ones before the page can be skipped
+            offset = offset.max((selected + 1).saturating_sub(height));
+            let mut used: usize = (offset..=selected).map(&line_height).sum();
+            while used > height && offset < selected {
+                used -= line_height(offset);
+                offset += 1;
+            }
+        }
+    }
+    *table_state.offset_mut() = offset;
+
+    let (mut end, mut used) = (offset, 0);
+    while end < len && used + line_height(end) <= height {
+        used += line_height(end);
+        end += 1;
+    }
+    // A row taller than the page is still shown
+    offset..end.max((offset + 1).min(len))
+}
+
+/// A bold, aligned label followed by a value, as shown in popups.
+fn field_line<'a>(label: &'a str, value: impl Into<std::borrow::Cow<'a, str>>) -> Line<'a> {
+    Line::from(vec![
+        Span::styled(format!("{:<12}", label), Style::default().bold()),
+        Span::raw(value),
+    ])
+}
+
+/// The formatted amount, or a mask while the amounts are hidden.
+fn masked(hidden: bool, amount: String) -> String {
+    if hidden {
+        MASK.to_string()
+    } else {
+        amount
+    }
+}
+
+/// Asks whether to delete the expenses, showing what would be deleted.
+fn delete_modal(expenses: &[&Expense], hidden: bool) -> Modal {
+    let (title, lines) = match expenses {
+        [expense] => {
+            let mut lines = vec![
+                field_line("Date", expense.date.clone()),
+                field_line("Description", expense.description.clone()),
+                field_line("Type", expense.expense_type.clone()),
+                field_line("Amount", masked(hidden, expense.amount.to_string())),
+            ];
+            if !expense.tags.is_empty() {
+                lines.push(field_line("Tags", expense.tags.join(" ")));
+            }
+            ("Delete this expense?".to_string(), lines)
+        }
+        expenses => {
+            // List the first few, the rest are summed up
+            let mut lines: Vec<Line> = expenses
+                .iter()
+                .take(5)
+                .map(|expense| {
+                    Line::raw(format!(
+                        "{}  {}  {}",
+                        expense.date,
+                        expense.description,
+                        masked(hidden, expense.amount.to_string())
+                    ))
+                })
+                .collect();
+            if expenses.len() > 5 {
+                lines.push(Line::raw(format!("and {} more", expenses.len() - 5)));
+            }
+            let total: f64 = expenses.iter().map(|expense| expense.amount).sum();
+            lines.push(field_line("Total", masked(hidden, format!("{:.2}", total))));
+            (format!("Delete these {} expenses?", expenses.len()), lines)
+        }
+    };
+    Modal::new(
+        title,
+        lines,
+        vec![
+            Button::new("Move to trash", Some('y'), Choice::Confirm),
+            Button::new("Keep", Some('n'), Choice::Dismiss),
+        ],
+    )
+    .border(Style::default().red())
+}
+
+/// Lists the keys of the table view, straight from the keymap.
+fn render_help(frame: &mut Frame, app: &App) {
+    let rows: Vec<Row> = app
+        .keymap
+        .bindings()
+        .map(|(keys, binding)| {
+            let keys: Vec<String> = keys.iter().map(Key::label).collect();
+            Row::new(vec![
+                Cell::from(keys.join(", "))
+                    .style(Style::default().fg(accent_color(app.config.theme)).bold()),
+                Cell::from(binding.description),
+            ])
+        })
+        .collect();
+
+    let area = centered_rect(80, rows.len() as u16 + 4, frame.size());
+    frame.render_widget(Clear, area);
+    let help = Table::new(rows, [Constraint::Length(20), Constraint::Min(20)])
+        .header(
+            Row::new(vec!["Keys", "Action"])
+                .style(Style::default().bold())
+                .bottom_margin(1),
+        )
+        .block(
+            Block::default()
+                .title("Keys (press any key to close)")
+                .borders(Borders::ALL),
+        );
+    frame.render_widget(help, area);
+}
+
+/// Asks for the new type or tags of the marked rows.
+fn render_bulk_edit(frame: &mut Frame, app: &App, field: BulkField) {
+    let rows = if app.marked.is_empty() {
+        format!("{} shown", app.rows.len())
+    } else {
+        format!("{} marked", app.marked.len())
+    };
+    let title = match field {
+        BulkField::Type => format!("New type for the {} expenses", rows),
+        BulkField::Tags => format!("Tags for the {} expenses (+tag adds, -tag removes)", rows),
+    };
+    let area = centered_rect(60, 3, frame.size());
+    frame.render_widget(Clear, area);
+    let input = Paragraph::new(app.bulk_input.value())
+        .block(Block::default().title(title).borders(Borders::ALL));
+    frame.render_widget(input, area);
+    frame.set_cursor(area.x + 1 + app.bulk_input.cursor_offset(), area.y + 1);
+}
+
+/// Shows every field of the expense at `row`, wrapping the ones too long for the table.
+fn details_modal(row: usize, expense: &Expense, hidden: bool) -> Modal {
+    let date = match parse_iso(&expense.date) {
+        Some(date) => format!("{} ({})", expense.date, date.format("%A")),
+        None => expense.date.clone(),
+    };
+    let tags = if expense.tags.is_empty() {
+        "-".to_string()
+    } else {
+        expense.tags.join(", ")
+    };
+    let lines = vec![
+        field_line("Record", format!("{} of expenses.csv", row + 1)),
+        field_line("Date", date),
+        field_line("Description", expense.description.clone()),
+        field_line("Type", expense.expense_type.clone()),
+        field_line("Amount", masked(hidden, format!("{:.2}", expense.amount))),
+        field_line("Tags", tags),
+    ];
+    Modal::new(
+        "Expense",
+        lines,
+        vec![
+            Button::new("Close", Some('q'), Choice::Dismiss),
+            Button::new("Edit", Some('e'), Choice::Edit),
+        ],
+    )
+    .width(80)
+}
+
+/// Shows the database, the position in the table, how many expenses match and their sum, the
+/// marked rows and the kept filters. Keys typed so far and the changes that can be undone are
+/// shown on the right.
+fn render_status_bar(frame: &mut Frame, app: &App, selected: Option<usize>, area: Rect) {
+    let mut segments: Vec<Vec<Span>> = vec![
+        vec![Span::styled(" expenses.csv ", Style::default().reversed())],
+        // The position among the rows, or the group on its header
+        vec![Span::raw(
+            match selected.and_then(|selected| app.lines.get(selected)) {
+                Some(TableLine::Expense(row)) => format!(
+                    "{}/{}",
+                    app.rows.iter().position(|r| r == row).map_or(0, |p| p + 1),
+                    app.rows.len()
+                ),
+                Some(TableLine::Group { key, .. }) => format!("{} of {}", key, app.rows.len()),
+                None => format!("0/{}", app.rows.len()),
+            },
+        )],
+    ];
+
+    if app.is_filtered() {
+        let net: f64 = app.visible().iter().map(|expense| expense.amount).sum();
+        segments.push(vec![Span::styled(
+            format!(
+                "{} {}, net {}",
+                app.rows.len(),
+                if app.rows.len() == 1 {
+                    "match"
+                } else {
+                    "matches"
+                },
+                app.config.money(net)
+            ),
+            Style::default().bold(),
+        )]);
+    }
+
+    if !app.marked.is_empty() {
+        let sum: f64 = app.marked.iter().map(|&row| app.expenses[row].amount).sum();
+        let mut segment = vec![Span::styled(
+            format!("{} marked, sum {}", app.marked.len(), app.config.money(sum)),
+            Style::default().yellow().bold(),
+        )];
+        // Marks are kept when filtering, so say when the sum counts rows that aren't shown
+        let hidden = app
+            .marked
+            .iter()
+            .filter(|row| !app.rows.contains(row))
+            .count();
+        if hidden > 0 {
+            segment.push(Span::styled(
+                format!(" ({} not shown)", hidden),
+                Style::default().dark_gray(),
+            ));
+        }
+        segments.push(segment);
+    }
+
+    if app.period != Period::All {
+        segments.push(vec![Span::styled(
+            format!("Period: {}", app.period.label()),
+            Style::default().fg(accent_color(app.config.theme)).bold(),
+        )]);
+    }
+
+    if !app.filters.is_empty() {
+        let labels: Vec<String> = app.filters.iter().map(ActiveFilter::label).collect();
+        segments.push(vec![
+            Span::raw("Filters: "),
+            Span::raw(labels.join(" › ")),
+            Span::styled(
+                "  (Backspace to remove the last)",
+                Style::default().dark_gray(),
+            ),
+        ]);
+    }
+
+    let mut spans = Vec::new();
+    for (index, segment) in segments.into_iter().enumerate() {
+        if index > 0 {
+            spans.push(Span::raw(" │ "));
+        }
+        spans.extend(segment);
+    }
+
+    let mut pending = String::new();
+    if let Some(count) = app.count {
+        pending.push_str(&count.to_string());
+    }
+    if let Some(key) = app.pending_key {
+        pending.push(key);
+    }
+    let changes = match app.history.changes() {
+        0 => String::new(),
+        1 => "1 change, u to undo".to_string(),
+        changes => format!("{} changes, u to undo", changes),
+    };
+    let right = match (pending.is_empty(), changes.is_empty()) {
+        (true, true) => String::new(),
+        (false, true) => format!("{} ", pending),
+        (true, false) => format!("{} ", changes),
+        (false, false) => format!("{} │ {} ", pending, changes),
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(right.chars().count() as u16),
+        ])
+        .split(area);
+    frame.render_widget(Paragraph::new(Line::from(spans)), chunks[0]);
+    frame.render_widget(
+        Paragraph::new(right).style(Style::default().dark_gray()),
+        chunks[1],
+    );
+}
+
+/// The new value of a setting being typed, in place of the status bar.
+fn render_setting_line(frame: &mut Frame, app: &App, area: Rect) {
+    let settings = settings::settings(&app.config, &app.keymap);
+    let key = settings
+        .get(app.settings_selected)
+        .map_or("", |setting| setting.key.as_str());
+    let prompt = format!("{}: ", key);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(prompt.as_str(), Style::default().bold()),
+            Span::raw(app.setting_input.value()),
+            hint_or_error(
+                &app.setting_input,
+                "  (Enter to save, Esc to cancel, empty for the default)",
+            ),
+        ])),
+        area,
+    );
+    frame.set_cursor(
+        area.x + prompt.chars().count() as u16 + app.setting_input.cursor_offset(),
+        area.y,
+    );
+}
+
+/// The types on the number keys while triaging, in place of the status bar.
+fn render_triage_line(frame: &mut Frame, app: &App, area: Rect) {
+    let mut spans = vec![Span::styled(" Triage ", Style::default().reversed())];
+    for (index, expense_type) in app.triage_types.iter().enumerate() {
+        spans.push(Span::styled(
+            format!(" {}", index + 1),
+            Style::default().fg(accent_color(app.config.theme)).bold(),
+        ));
+        spans.push(Span::raw(format!(" {}", expense_type)));
+    }
+    spans.push(Span::styled(
+        "  (Esc to stop)",
+        Style::default().dark_gray(),
+    ));
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// The date to go to being typed, in place of the status bar.
+fn render_goto_line(frame: &mut Frame, app: &App, area: Rect) {
+    let prompt = "Go to: ";
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(prompt, Style::default().bold()),
+            Span::raw(app.goto_input.value()),
+            hint_or_error(
+                &app.goto_input,
+                "  (a date like 2024-03-15, yesterday or 12/6, a month like 2024-03, or g for the top)",
+            ),
+        ])),
+        area,
+    );
+    frame.set_cursor(
+        area.x + prompt.len() as u16 + app.goto_input.cursor_offset(),
+        area.y,
+    );
+}
+
+/// The file to export the table to being typed, in place of the status bar.
+fn render_export_line(frame: &mut Frame, app: &App, area: Rect) {
+    let prompt = format!("Export {} rows to: ", app.rows.len());
+    let format = ExportFormat::of(Path::new(app.export_input.value()));
+    let hint = format!(
+        "  ({}, Tab: next format among csv, json and md)",
+        format.extension()
+    );
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(prompt.as_str(), Style::default().bold()),
+            Span::raw(app.export_input.value()),
+            hint_or_error(&app.export_input, &hint),
+        ])),
+        area,
+    );
+    frame.set_cursor(
+        area.x + prompt.len() as u16 + app.export_input.cursor_offset(),
+        area.y,
+    );
+}
+
+/// The command being typed, in place of the status bar.
+fn render_command_line(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::raw(format!(":{}", app.command_input.value())),
+            hint_or_error(&app.command_input, ""),
+        ])),
+        area,
+    );
+    frame.set_cursor(area.x + 1 + app.command_input.cursor_offset(), area.y);
+}
+
+/// Why the typed text can't be used in red, or else a hint for what to type.
+fn hint_or_error<'a>(input: &'a TextInput, hint: &'a str) -> Span<'a> {
+    match &input.error {
+        Some(err) => Span::styled(format!("  {}", err), Style::default().red()),
+        None => Span::styled(hint, Style::default().dark_gray()),
+    }
+}
+
+fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
+    let (title, style) = match &app.search_input.error {
+        Some(err) => (
+            format!(
+                "{}: {}",
+                app.search_kind.title(),
+                err.split_whitespace().collect::<Vec<_>>().join(" ")
+            ),
+            Style::default().red(),
+        ),
+        None => (
+            format!(
+                "{} (Tab to switch, Enter to keep, Esc to clear)",
+                app.search_kind.title()
+            ),
+            Style::default(),
+        ),
+    };
+    let search_bar = Paragraph::new(app.search_input.value()).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(style),
+    );
+    frame.render_widget(search_bar, area);
+
+    if app.mode == Mode::Search {
+        frame.set_cursor(area.x + 1 + app.search_input.cursor_offset(), area.y + 1);
+    }
+}
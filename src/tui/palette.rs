@@ -0,0 +1,43 @@
+//! The color of each type, the same in the charts, their legends and the table, so Food is
+//! always the same green. A type takes the color set for it in the `[colors]` section of the
+//! config, or else one picked from its name.
+
+use ratatui::style::Color;
+
+use budget_tracker::config::Config;
+
+/// The colors picked from for the types without one set in the config.
+const PALETTE: [Color; 12] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Green,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+    Color::LightCyan,
+    Color::LightMagenta,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightRed,
+];
+
+/// The color of `expense_type`, whatever its case: the one set in the config, or else one of
+/// the palette picked by a hash of its name, which stays the same from one start to the next.
+pub fn type_color(config: &Config, expense_type: &str) -> Color {
+    if let Some(color) = config
+        .color(expense_type)
+        .and_then(|color| color.parse().ok())
+    {
+        return color;
+    }
+    // FNV-1a, as the standard library's hashes may change between releases
+    let hash = expense_type
+        .trim()
+        .to_lowercase()
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    PALETTE[(hash % PALETTE.len() as u64) as usize]
+}
@@ -0,0 +1,71 @@
+//! Restricting the whole TUI to a recent period, switched with `p`.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+use budget_tracker::date::DateRange;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Period {
+    #[default]
+    All,
+    Month,
+    ThreeMonths,
+    YearToDate,
+    Year,
+}
+
+impl Period {
+    pub const ALL: [Period; 5] = [
+        Period::All,
+        Period::Month,
+        Period::ThreeMonths,
+        Period::YearToDate,
+        Period::Year,
+    ];
+
+    /// The short name shown in the status bar.
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::All => "All",
+            Period::Month => "1M",
+            Period::ThreeMonths => "3M",
+            Period::YearToDate => "YTD",
+            Period::Year => "1Y",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&period| period == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&period| period == self)
+            .unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// The days of the period up to today, or `None` for all time. Rolling periods start the day
+    /// after the same date a month, three months or a year ago.
+    pub fn range(self, today: NaiveDate) -> Option<DateRange> {
+        let months_back = |months| {
+            today
+                .checked_sub_months(Months::new(months))
+                .and_then(|date| date.checked_add_days(Days::new(1)))
+                .map(|start| DateRange::new(start, today))
+        };
+        match self {
+            Period::All => None,
+            Period::Month => months_back(1),
+            Period::ThreeMonths => months_back(3),
+            Period::YearToDate => NaiveDate::from_ymd_opt(today.year(), 1, 1)
+                .map(|start| DateRange::new(start, today)),
+            Period::Year => months_back(12),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! The Recurring tab: the regular expenses found in the entries, the payees paid in each of the
+//! two months before this one, and whether they were paid yet this month.
+
+use chrono::NaiveDate;
+use ratatui::{prelude::*, widgets::*};
+
+use budget_tracker::config::Config;
+use budget_tracker::expense::Expense;
+use budget_tracker::forecast::Forecast;
+
+/// A row per regular expense, the ones still to come in yellow, under a line of what they add
+/// up to.
+pub fn render(
+    frame: &mut Frame,
+    config: &Config,
+    expenses: &[&Expense],
+    today: NaiveDate,
+    area: Rect,
+) {
+    let forecast = Forecast::new(expenses, today).filter(|forecast| !forecast.regular.is_empty());
+    let Some(forecast) = forecast else {
+        let paragraph = Paragraph::new(vec![
+            Line::from("No regular expenses were found yet."),
+            Line::from(""),
+            Line::from("A payee paid in each of the two months before this one, like the rent or"),
+            Line::from("a subscription, is listed here with whether it was paid this month."),
+        ])
+        .style(Style::default().dark_gray())
+        .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(paragraph, area);
+        return;
+    };
+
+    let paid = forecast.regular.iter().filter(|regular| regular.is_paid());
+    let title = format!(
+        "Regular expenses of {}: {} paid, {} to come",
+        forecast.month.month_name(),
+        config.money(paid.map(|regular| regular.paid).sum()),
+        config.money(forecast.upcoming_total())
+    );
+    let rows = forecast.regular.iter().map(|regular| {
+        let (status, style) = match regular.is_paid() {
+            true => ("Paid", Style::default().green()),
+            false => ("To come", Style::default().yellow()),
+        };
+        Row::new(vec![
+            Cell::from(regular.name.clone()),
+            Cell::from(config.money(regular.amount)),
+            Cell::from(match regular.is_paid() {
+                true => config.money(regular.paid),
+                false => String::new(),
+            }),
+            Cell::from(status).style(style),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(8),
+        ],
+    )
+    .header(Row::new(["Payee", "Usually", "This month", "Status"]).style(Style::default().bold()))
+    .block(Block::default().title(title).borders(Borders::ALL));
+    frame.render_widget(table, area);
+}
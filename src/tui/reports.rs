@@ -0,0 +1,242 @@
+//! The Reports tab: what was spent each day, totals of the expenses in the table by month, by
+//! type and by payee, and the net worth over the last months.
+
+use chrono::NaiveDate;
+use ratatui::{prelude::*, widgets::*};
+use std::collections::BTreeMap;
+
+use budget_tracker::account::net_worth;
+use budget_tracker::config::Config;
+use budget_tracker::date::{parse_iso, DateRange};
+use budget_tracker::expense::{capitalize, Expense};
+use budget_tracker::payee::payees;
+
+use super::calendar;
+use super::chart::ticks;
+use crate::report::share;
+
+/// How many months the net worth is charted over.
+const NET_WORTH_MONTHS: u32 = 12;
+
+/// What was spent and earned over some expenses.
+#[derive(Debug, Default, Clone, Copy)]
+struct Totals {
+    spent: f64,
+    earned: f64,
+    count: usize,
+}
+
+impl Totals {
+    fn add(&mut self, amount: f64) {
+        if amount < 0.0 {
+            self.spent += amount;
+        } else {
+            self.earned += amount;
+        }
+        self.count += 1;
+    }
+
+    fn net(&self) -> f64 {
+        self.spent + self.earned
+    }
+}
+
+/// Draws the tab with `selected` the day picked in the heatmap, returning the area of each day.
+pub fn render(
+    frame: &mut Frame,
+    config: &Config,
+    expenses: &[&Expense],
+    selected: NaiveDate,
+    today: NaiveDate,
+    area: Rect,
+) -> Vec<(Rect, NaiveDate)> {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(calendar::HEIGHT), Constraint::Min(0)])
+        .split(area);
+    let days = calendar::render(frame, config, expenses, selected, today, rows[0]);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    // Newest month first
+    let mut months: BTreeMap<NaiveDate, Totals> = BTreeMap::new();
+    for expense in expenses {
+        if let Some(month) = parse_iso(&expense.date).and_then(DateRange::month_of) {
+            months.entry(month.start).or_default().add(expense.amount);
+        }
+    }
+    let names: Vec<String> = months
+        .keys()
+        .rev()
+        .filter_map(|&start| DateRange::month_of(start))
+        .map(|month| month.month_name())
+        .collect();
+    let name_width = names
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let month_rows = names
+        .iter()
+        .zip(months.values().rev())
+        .map(|(name, totals)| {
+            Row::new(vec![
+                name.clone(),
+                config.money(totals.spent),
+                config.money(totals.earned),
+                config.money(totals.net()),
+                totals.count.to_string(),
+            ])
+        });
+    let month_table = Table::new(
+        month_rows,
+        [
+            Constraint::Length(name_width.max(5) as u16),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(8),
+        ],
+    )
+    .header(Row::new(["Month", "Spent", "Earned", "Net", "Entries"]).style(Style::default().bold()))
+    .block(Block::default().title("By month").borders(Borders::ALL));
+    let left = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[0]);
+    frame.render_widget(month_table, left[0]);
+    render_net_worth(frame, config, expenses, today, left[1]);
+
+    // Biggest spending first, then income
+    let mut types: BTreeMap<String, Totals> = BTreeMap::new();
+    for expense in expenses {
+        types
+            .entry(capitalize(expense.expense_type.clone()))
+            .or_default()
+            .add(expense.amount);
+    }
+    let mut types: Vec<(String, Totals)> = types.into_iter().collect();
+    types.sort_by(|a, b| a.1.net().total_cmp(&b.1.net()));
+    let total_spent: f64 = types.iter().map(|(_, totals)| totals.spent).sum();
+    let type_rows = types.iter().map(|(expense_type, totals)| {
+        Row::new(vec![
+            expense_type.clone(),
+            config.money(totals.net()),
+            share(-totals.spent, -total_spent),
+            totals.count.to_string(),
+        ])
+    });
+    let type_table = Table::new(
+        type_rows,
+        [
+            Constraint::Length(20),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(8),
+        ],
+    )
+    .header(Row::new(["Type", "Net", "Of spent", "Entries"]).style(Style::default().bold()))
+    .block(Block::default().title("By type").borders(Borders::ALL));
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    frame.render_widget(type_table, right[0]);
+
+    // The payees read from the descriptions, the most spent on first
+    let payee_rows = payees(expenses).into_iter().map(|payee| {
+        Row::new(vec![
+            payee.name.clone(),
+            config.money(payee.spent),
+            payee.count.to_string(),
+            config.money(payee.average()),
+        ])
+    });
+    let payee_table = Table::new(
+        payee_rows,
+        [
+            Constraint::Length(20),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(12),
+        ],
+    )
+    .header(Row::new(["Payee", "Spent", "Entries", "Average"]).style(Style::default().bold()))
+    .block(Block::default().title("By payee").borders(Borders::ALL));
+    frame.render_widget(payee_table, right[1]);
+    days
+}
+
+/// A line of what the accounts held together at the end of each of the last months, from their
+/// opening balances and the expenses.
+fn render_net_worth(
+    frame: &mut Frame,
+    config: &Config,
+    expenses: &[&Expense],
+    today: NaiveDate,
+    area: Rect,
+) {
+    let worth = net_worth(config, expenses, NET_WORTH_MONTHS, today);
+    let points: Vec<(f64, f64)> = worth
+        .iter()
+        .enumerate()
+        .map(|(month, (_, total))| (month as f64, *total))
+        .collect();
+    let now = worth.last().map_or(0.0, |(_, total)| *total);
+    let first = worth.first().map_or(0.0, |(_, total)| *total);
+    let title = format!(
+        "Net worth {}, {}{} in {} months",
+        config.money(now),
+        if now >= first { "+" } else { "" },
+        config.money(now - first),
+        worth.len()
+    );
+
+    let low = points
+        .iter()
+        .map(|(_, total)| *total)
+        .fold(f64::MAX, f64::min);
+    let high = points
+        .iter()
+        .map(|(_, total)| *total)
+        .fold(f64::MIN, f64::max);
+    let count = (area.height.saturating_sub(4) / 3).clamp(2, 6) as usize;
+    let ticks = ticks(low.min(high), high, count, 0.01);
+    let label = |index: usize| {
+        worth
+            .get(index)
+            .map(|(month, _)| month.start.format("%b %y").to_string())
+            .unwrap_or_default()
+    };
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().cyan())
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, points.len().saturating_sub(1).max(1) as f64])
+                .labels(vec![
+                    label(0).into(),
+                    label(points.len() / 2).into(),
+                    label(points.len().saturating_sub(1)).into(),
+                ])
+                .style(Style::default().dark_gray()),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([ticks[0], ticks[ticks.len() - 1]])
+                .labels(
+                    ticks
+                        .iter()
+                        .map(|tick| config.short_money(*tick).into())
+                        .collect(),
+                )
+                .style(Style::default().dark_gray()),
+        );
+    frame.render_widget(chart, area);
+}
@@ -0,0 +1,90 @@
+//! The Settings tab: the config and the keys, edited in place and saved to the config file.
+
+use ratatui::{prelude::*, widgets::*};
+
+use budget_tracker::config::Config;
+
+use super::keymap::{Key, Keymap};
+
+/// A line of the Settings tab.
+pub struct Setting {
+    /// The name given to [Config::set]
+    pub key: String,
+    pub description: String,
+    pub value: String,
+}
+
+/// The settings of the config followed by the budgets, where they roll over from, the colors of
+/// types, the accounts and the keys of every action.
+pub fn settings(config: &Config, keymap: &Keymap) -> Vec<Setting> {
+    let options = Config::SETTINGS.iter().map(|(key, description)| Setting {
+        key: key.to_string(),
+        description: description.to_string(),
+        value: config.get(key).unwrap_or_default(),
+    });
+    let keys = keymap.bindings().map(|(keys, binding)| Setting {
+        key: format!("keys.{}", binding.action.name()),
+        description: binding.description.to_string(),
+        value: keys.iter().map(Key::label).collect::<Vec<_>>().join(" "),
+    });
+    let budgets = config.budgets.iter().map(|(expense_type, budget)| Setting {
+        key: format!("budgets.{}", expense_type.to_lowercase()),
+        description: format!("Monthly budget of {}, empty to remove it", expense_type),
+        value: budget.to_string(),
+    });
+    let rollover = config.budgets.keys().map(|expense_type| Setting {
+        key: format!("rollover.{}", expense_type.to_lowercase()),
+        description: format!(
+            "Month the budget of {} carries over from, e.g. 2024-06, or true",
+            expense_type
+        ),
+        value: config
+            .get(&format!("rollover.{}", expense_type))
+            .unwrap_or_default(),
+    });
+    let colors = config.colors.iter().map(|(expense_type, color)| Setting {
+        key: format!("colors.{}", expense_type.to_lowercase()),
+        description: format!("Color of {}, empty to pick one from its name", expense_type),
+        value: color.clone(),
+    });
+    let accounts = config.accounts.iter().map(|(name, opening)| Setting {
+        key: format!("accounts.{}", name.to_lowercase()),
+        description: format!("Opening balance of {}, empty to remove it", name),
+        value: opening.to_string(),
+    });
+    options
+        .chain(budgets)
+        .chain(rollover)
+        .chain(colors)
+        .chain(accounts)
+        .chain(keys)
+        .collect()
+}
+
+pub fn render(frame: &mut Frame, settings: &[Setting], selected: usize, area: Rect) {
+    let rows = settings.iter().map(|setting| {
+        Row::new(vec![
+            Cell::from(setting.key.as_str()).style(Style::default().bold()),
+            Cell::from(setting.value.as_str()),
+            Cell::from(setting.description.as_str()).style(Style::default().dark_gray()),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(24),
+            Constraint::Length(24),
+            Constraint::Min(20),
+        ],
+    )
+    .header(Row::new(["Setting", "Value", ""]).style(Style::default().bold()))
+    .block(
+        Block::default()
+            .title("Settings (Enter to change, saved to the config file)")
+            .borders(Borders::ALL),
+    )
+    .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">>");
+    let mut state = TableState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
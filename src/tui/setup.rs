@@ -0,0 +1,185 @@
+//! The questions asked on the first start, before there is a config or a database.
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+use std::{fs, io, path::PathBuf};
+
+use budget_tracker::config::expand_home;
+use budget_tracker::expense::capitalize;
+
+use super::centered_rect;
+use super::widgets::TextInput;
+
+const CURRENCY: usize = 0;
+const BALANCE: usize = 1;
+const CATEGORIES: usize = 2;
+const DATA_DIR: usize = 3;
+
+/// Each question with what it is for.
+const QUESTIONS: [(&str, &str); 4] = [
+    (
+        "Currency",
+        "The symbol put before amounts, like $ or €. Leave it empty for none.",
+    ),
+    (
+        "Starting balance",
+        "What the account holds today, added as the first entry. Leave it empty to start from zero.",
+    ),
+    (
+        "Categories",
+        "The types expenses are sorted into, separated by commas, like Food, Rent, Travel. More can be added as you go.",
+    ),
+    (
+        "Data location",
+        "The directory expenses.csv is kept in. An expenses.csv already there is used as it is.",
+    ),
+];
+
+/// The answers to the questions.
+pub struct Setup {
+    pub currency: String,
+    pub balance: f64,
+    pub categories: Vec<String>,
+    pub data_dir: PathBuf,
+}
+
+struct SetupWizard {
+    step: usize,
+    inputs: [TextInput; 4],
+}
+
+impl SetupWizard {
+    /// The starting balance, or `None` with the error on its input if it isn't an amount.
+    fn balance(&mut self) -> Option<f64> {
+        let input = &mut self.inputs[BALANCE];
+        match input.value().trim() {
+            "" => Some(0.0),
+            balance => {
+                let parsed = balance.parse().ok();
+                if parsed.is_none() {
+                    input.error = Some(format!("'{}' isn't an amount", balance));
+                }
+                parsed
+            }
+        }
+    }
+
+    /// Goes on to the next question, unless the answer to this one can't be used.
+    fn next(&mut self) {
+        if self.step + 1 < QUESTIONS.len() && (self.step != BALANCE || self.balance().is_some()) {
+            self.step += 1;
+        }
+    }
+
+    /// The answers if they can be used, or else the question to correct with why it can't.
+    fn answers(&mut self) -> Option<Setup> {
+        let Some(balance) = self.balance() else {
+            self.step = BALANCE;
+            return None;
+        };
+        let data_dir = expand_home(self.inputs[DATA_DIR].value().trim());
+        if let Err(err) = fs::create_dir_all(&data_dir) {
+            self.inputs[DATA_DIR].error = Some(format!("Couldn't create the directory: {}", err));
+            self.step = DATA_DIR;
+            return None;
+        }
+        Some(Setup {
+            currency: self.inputs[CURRENCY].value().trim().to_string(),
+            balance,
+            categories: self.inputs[CATEGORIES]
+                .value()
+                .split(',')
+                .map(|category| capitalize(category.trim().to_string()))
+                .filter(|category| !category.is_empty())
+                .collect(),
+            data_dir,
+        })
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let area = centered_rect(64, 12, frame.size());
+        frame.render_widget(Clear, area);
+        let (question, explanation) = QUESTIONS[self.step];
+        let input = &self.inputs[self.step];
+        let lines = vec![
+            Line::styled(question, Style::default().bold()),
+            Line::styled(explanation, Style::default().dark_gray()),
+        ];
+        let block = Block::default()
+            .title(format!(
+                "Welcome to budget-tracker, step {} of {}",
+                self.step + 1,
+                QUESTIONS.len()
+            ))
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        if inner.height < 7 {
+            return;
+        }
+
+        let text_area = Rect::new(inner.x, inner.y, inner.width, 4);
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), text_area);
+        let input_area = Rect::new(inner.x, inner.y + 5, inner.width, 1);
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("> ", Style::default().yellow()),
+                Span::raw(input.value()),
+            ])),
+            input_area,
+        );
+        frame.set_cursor(input_area.x + 2 + input.cursor_offset(), input_area.y);
+
+        let hint = match &input.error {
+            Some(err) => Line::styled(err.as_str(), Style::default().red()),
+            None if self.step == 0 => Line::styled(
+                "Enter: next, Esc: skip and use the defaults",
+                Style::default().dark_gray(),
+            ),
+            None => Line::styled(
+                "Enter: next, Shift-Tab: back, Esc: skip and use the defaults",
+                Style::default().dark_gray(),
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(hint),
+            Rect::new(inner.x, inner.bottom() - 1, inner.width, 1),
+        );
+    }
+}
+
+/// Asks the questions one after the other, with `data_dir` as the suggested location. Returns
+/// `None` if they were skipped.
+pub fn run<B: Backend>(terminal: &mut Terminal<B>, data_dir: PathBuf) -> io::Result<Option<Setup>> {
+    let mut wizard = SetupWizard {
+        step: 0,
+        inputs: Default::default(),
+    };
+    wizard.inputs[DATA_DIR] = TextInput::with_value(data_dir.display().to_string());
+    loop {
+        terminal.draw(|frame| wizard.render(frame))?;
+        match event::read()? {
+            Event::Paste(text) => wizard.inputs[wizard.step].paste(&text),
+            Event::Key(KeyEvent {
+                kind: KeyEventKind::Press,
+                code,
+                modifiers,
+                ..
+            }) => match code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+                KeyCode::Enter if wizard.step + 1 == QUESTIONS.len() => {
+                    if let Some(setup) = wizard.answers() {
+                        return Ok(Some(setup));
+                    }
+                }
+                KeyCode::Enter | KeyCode::Tab | KeyCode::Down => wizard.next(),
+                KeyCode::BackTab | KeyCode::Up => wizard.step = wizard.step.saturating_sub(1),
+                _ => {
+                    wizard.inputs[wizard.step].handle_key(code, modifiers);
+                }
+            },
+            _ => {}
+        }
+    }
+}
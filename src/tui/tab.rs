@@ -0,0 +1,46 @@
+//! The views of the TUI, switched with Tab and Shift-Tab.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Tab {
+    #[default]
+    Dashboard,
+    Transactions,
+    Reports,
+    Budgets,
+    Recurring,
+    Settings,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 6] = [
+        Tab::Dashboard,
+        Tab::Transactions,
+        Tab::Reports,
+        Tab::Budgets,
+        Tab::Recurring,
+        Tab::Settings,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Dashboard => "Dashboard",
+            Tab::Transactions => "Transactions",
+            Tab::Reports => "Reports",
+            Tab::Budgets => "Budgets",
+            Tab::Recurring => "Recurring",
+            Tab::Settings => "Settings",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|&tab| tab == self).unwrap_or(0)
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
@@ -0,0 +1,46 @@
+//! Watching the database for changes made outside the TUI, like an entry added with `--add` in
+//! another terminal or a sync client bringing in changes from another device.
+
+use log::trace;
+use notify::{recommended_watcher, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+
+pub struct FileWatcher {
+    /// Kept alive, as dropping it stops the watching
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    path: PathBuf,
+}
+
+impl FileWatcher {
+    /// Watches the file at `path`. The directory it is in is watched rather than the file, as
+    /// the file is replaced by a new one on every write.
+    pub fn new(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let dir = path.parent().ok_or("The database has no directory")?;
+        let (sender, events) = channel();
+        let mut watcher = recommended_watcher(sender)?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        trace!("Watching {} for changes ...", path.display());
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            path,
+        })
+    }
+
+    /// Whether the file was written, created or removed since the last call, without waiting.
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        for event in self.events.try_iter().flatten() {
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) && event.paths.contains(&self.path)
+            {
+                changed = true;
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,108 @@
+//! A month calendar to pick a date with the arrow keys, opened from the date field of the forms.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use crossterm::event::KeyCode;
+use ratatui::{prelude::*, widgets::*};
+
+use budget_tracker::date::{day_of_week, week_start};
+
+/// What the calendar wants to happen after a key press.
+pub enum CalendarAction {
+    Continue,
+    Pick(NaiveDate),
+    Cancel,
+}
+
+pub struct Calendar {
+    /// The selected day
+    pub date: NaiveDate,
+    today: NaiveDate,
+}
+
+impl Calendar {
+    pub fn new(date: NaiveDate, today: NaiveDate) -> Self {
+        Self { date, today }
+    }
+
+    /// Arrows move by a day or a week, PageUp and PageDown by a month, and `t` goes to today.
+    pub fn handle_key(&mut self, code: KeyCode) -> CalendarAction {
+        let moved = match code {
+            KeyCode::Enter | KeyCode::Char(' ') => return CalendarAction::Pick(self.date),
+            KeyCode::Esc | KeyCode::Char('q') => return CalendarAction::Cancel,
+            KeyCode::Left | KeyCode::Char('h') => self.date.checked_sub_days(Days::new(1)),
+            KeyCode::Right | KeyCode::Char('l') => self.date.checked_add_days(Days::new(1)),
+            KeyCode::Up | KeyCode::Char('k') => self.date.checked_sub_days(Days::new(7)),
+            KeyCode::Down | KeyCode::Char('j') => self.date.checked_add_days(Days::new(7)),
+            KeyCode::PageUp => self.date.checked_sub_months(Months::new(1)),
+            KeyCode::PageDown => self.date.checked_add_months(Months::new(1)),
+            KeyCode::Char('t') => Some(self.today),
+            _ => None,
+        };
+        if let Some(date) = moved {
+            self.date = date;
+        }
+        CalendarAction::Continue
+    }
+
+    /// Draws the month of the selected day with its top left corner at `x` and `y`, moved in if
+    /// it wouldn't fit on the screen.
+    pub fn render(&self, frame: &mut Frame, x: u16, y: u16) {
+        let screen = frame.size();
+        let (width, height) = (24, 10);
+        let area = Rect::new(
+            x.min(screen.right().saturating_sub(width)),
+            y.min(screen.bottom().saturating_sub(height)),
+            width,
+            height,
+        )
+        .intersection(screen);
+
+        // The days of the week from the one weeks start on
+        let mut names = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+        names.rotate_left(week_start().num_days_from_monday() as usize);
+        let mut lines = vec![Line::styled(names.join(" "), Style::default().bold())];
+        let first = self.date.with_day(1).unwrap_or(self.date);
+        let mut week = vec![Span::raw(
+            "   ".repeat(day_of_week(first.weekday()) as usize),
+        )];
+        let mut day = first;
+        while day.month() == first.month() {
+            let mut style = Style::default();
+            if day == self.today {
+                style = style.yellow().bold();
+            }
+            if day == self.date {
+                style = style.reversed();
+            }
+            week.push(Span::styled(format!("{:>2}", day.day()), style));
+            week.push(Span::raw(" "));
+            if day_of_week(day.weekday()) == 6 {
+                lines.push(Line::from(std::mem::take(&mut week)));
+            }
+            match day.succ_opt() {
+                Some(next) => day = next,
+                None => break,
+            }
+        }
+        if !week.is_empty() {
+            lines.push(Line::from(week));
+        }
+        while lines.len() < 7 {
+            lines.push(Line::raw(""));
+        }
+        lines.push(Line::styled(
+            "t: today, Enter: pick",
+            Style::default().dark_gray(),
+        ));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .title(self.date.format("%B %Y").to_string())
+                    .borders(Borders::ALL),
+            ),
+            area,
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! A line of text being typed, with a cursor that can be moved and the usual editing keys of a
+//! shell: Ctrl-U and Ctrl-W delete back to the start and over the last word, Ctrl-K to the end.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    value: String,
+    /// The position of the cursor, in chars
+    cursor: usize,
+    /// Why the value can't be used, shown next to it
+    pub error: Option<String>,
+}
+
+impl TextInput {
+    /// An input holding `value`, with the cursor at its end.
+    pub fn with_value(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.chars().count(),
+            value,
+            error: None,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Empties the input, returning what was typed.
+    pub fn take(&mut self) -> String {
+        std::mem::take(self).value
+    }
+
+    /// How many columns the text before the cursor takes, to place the terminal's cursor.
+    pub fn cursor_offset(&self) -> u16 {
+        self.cursor as u16
+    }
+
+    /// Edits the text with the key, returning whether it changed. Keys that aren't for editing,
+    /// like Enter or Esc, are left to the caller.
+    pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let before = self.value.clone();
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            match code {
+                KeyCode::Char('u') => self.delete(0..self.cursor),
+                KeyCode::Char('w') => self.delete(self.word_start()..self.cursor),
+                KeyCode::Char('k') => self.delete(self.cursor..self.len()),
+                KeyCode::Char('a') => self.cursor = 0,
+                KeyCode::Char('e') => self.cursor = self.len(),
+                _ => {}
+            }
+        } else {
+            match code {
+                KeyCode::Char(c) => self.insert(c),
+                KeyCode::Backspace if self.cursor > 0 => self.delete(self.cursor - 1..self.cursor),
+                KeyCode::Delete if self.cursor < self.len() => {
+                    self.delete(self.cursor..self.cursor + 1)
+                }
+                KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+                KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+                KeyCode::Home => self.cursor = 0,
+                KeyCode::End => self.cursor = self.len(),
+                _ => {}
+            }
+        }
+        let changed = self.value != before;
+        if changed {
+            self.error = None;
+        }
+        changed
+    }
+
+    /// Inserts pasted text at the cursor, on a single line.
+    pub fn paste(&mut self, text: &str) {
+        for c in text.trim_end_matches(['\r', '\n']).chars() {
+            self.insert(if c == '\r' || c == '\n' { ' ' } else { c });
+        }
+        self.error = None;
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    /// The byte position of the char at `index`.
+    fn byte(&self, index: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(index)
+            .map_or(self.value.len(), |(byte, _)| byte)
+    }
+
+    fn insert(&mut self, c: char) {
+        let byte = self.byte(self.cursor);
+        self.value.insert(byte, c);
+        self.cursor += 1;
+    }
+
+    /// Removes the chars in `range` and puts the cursor where they were.
+    fn delete(&mut self, range: std::ops::Range<usize>) {
+        let (start, end) = (self.byte(range.start), self.byte(range.end));
+        self.value.replace_range(start..end, "");
+        self.cursor = range.start;
+    }
+
+    /// Where the word before the cursor starts, skipping the spaces after it.
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().take(self.cursor).collect();
+        let mut start = chars.len();
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        start
+    }
+}
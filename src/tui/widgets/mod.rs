@@ -0,0 +1,13 @@
+//! Widgets shared by the views of the TUI.
+
+mod calendar;
+mod input;
+mod modal;
+mod picker;
+mod toast;
+
+pub use calendar::{Calendar, CalendarAction};
+pub use input::TextInput;
+pub use modal::{Button, Choice, Modal};
+pub use picker::{Picker, PickerAction};
+pub use toast::Toast;
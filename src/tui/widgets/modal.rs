@@ -0,0 +1,143 @@
+//! A dialog over the TUI showing some lines and a row of buttons, answered with a button's key,
+//! or by moving to a button and pressing Enter.
+
+use crossterm::event::KeyCode;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::tui::centered_rect;
+
+/// What the button pressed in a dialog stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Choice {
+    /// Go ahead with what was asked, e.g. delete
+    Confirm,
+    Edit,
+    /// Close the dialog without doing anything, also what Esc does
+    Dismiss,
+}
+
+pub struct Button {
+    pub label: &'static str,
+    /// Presses the button from anywhere in the dialog
+    pub key: Option<char>,
+    pub choice: Choice,
+}
+
+impl Button {
+    pub fn new(label: &'static str, key: Option<char>, choice: Choice) -> Self {
+        Self { label, key, choice }
+    }
+}
+
+pub struct Modal {
+    title: String,
+    body: Vec<Line<'static>>,
+    buttons: Vec<Button>,
+    /// The button Enter presses
+    selected: usize,
+    border: Style,
+    width: u16,
+}
+
+impl Modal {
+    /// A dialog with the first button selected, 60 columns wide.
+    pub fn new(title: impl Into<String>, body: Vec<Line<'static>>, buttons: Vec<Button>) -> Self {
+        Self {
+            title: title.into(),
+            body,
+            buttons,
+            selected: 0,
+            border: Style::default(),
+            width: 60,
+        }
+    }
+
+    /// An error with a single button to close it.
+    pub fn error(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(
+            title,
+            vec![Line::raw(message.into())],
+            vec![Button::new("OK", None, Choice::Dismiss)],
+        )
+        .border(Style::default().red())
+    }
+
+    pub fn selected(mut self, selected: usize) -> Self {
+        self.selected = selected.min(self.buttons.len().saturating_sub(1));
+        self
+    }
+
+    pub fn border(mut self, border: Style) -> Self {
+        self.border = border;
+        self
+    }
+
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// The choice made with the key, if any. Left and Right (or Tab) move between the buttons.
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<Choice> {
+        let count = self.buttons.len().max(1);
+        match code {
+            KeyCode::Esc => Some(Choice::Dismiss),
+            KeyCode::Enter => self.buttons.get(self.selected).map(|button| button.choice),
+            KeyCode::Right | KeyCode::Tab => {
+                self.selected = (self.selected + 1) % count;
+                None
+            }
+            KeyCode::Left | KeyCode::BackTab => {
+                self.selected = (self.selected + count - 1) % count;
+                None
+            }
+            KeyCode::Char(c) => self
+                .buttons
+                .iter()
+                .find(|button| button.key == Some(c))
+                .map(|button| button.choice),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let width = self.width.min(frame.size().width);
+        let inner_width = width.saturating_sub(2).max(1) as usize;
+        // Room for the lines wrapping, then a blank line and the buttons
+        let body_height: usize = self
+            .body
+            .iter()
+            .map(|line| line.width().div_ceil(inner_width).max(1))
+            .sum();
+        let height = body_height as u16 + 4;
+
+        let mut buttons = Vec::new();
+        for (index, button) in self.buttons.iter().enumerate() {
+            let label = match button.key {
+                Some(key) => format!(" {}: {} ", key, button.label),
+                None => format!(" {} ", button.label),
+            };
+            let style = if index == self.selected {
+                Style::default().reversed()
+            } else {
+                Style::default()
+            };
+            buttons.push(Span::styled(label, style));
+            buttons.push(Span::raw(" "));
+        }
+
+        let mut lines = self.body.clone();
+        lines.push(Line::raw(""));
+        lines.push(Line::from(buttons));
+
+        let area = centered_rect(width, height, frame.size());
+        frame.render_widget(Clear, area);
+        let dialog = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title(self.title.as_str())
+                .borders(Borders::ALL)
+                .border_style(self.border),
+        );
+        frame.render_widget(dialog, area);
+    }
+}
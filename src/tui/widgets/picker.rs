@@ -0,0 +1,159 @@
+//! A list to pick from by typing part of the choice, as in `fd` for Food, and moving through
+//! what matches with the arrow keys.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use ratatui::{prelude::*, widgets::*};
+
+use super::TextInput;
+
+/// What the picker wants to happen after a key press.
+pub enum PickerAction {
+    Continue,
+    /// The value of the chosen line
+    Pick(String),
+    Cancel,
+}
+
+pub struct Picker {
+    title: String,
+    /// The lines to pick from and the value each one stands for
+    choices: Vec<(String, String)>,
+    query: TextInput,
+    /// The choices matching the query, best first, as positions into `choices`
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl Picker {
+    /// How wide the picker is drawn.
+    pub const WIDTH: u16 = 32;
+    /// The most choices listed at once.
+    const LINES: usize = 8;
+
+    /// A picker of `choices` as labels and values, in the order they are given, filtered with
+    /// `query` to begin with.
+    pub fn new(title: &str, choices: Vec<(String, String)>, query: &str) -> Self {
+        let mut picker = Self {
+            title: title.to_string(),
+            choices,
+            query: TextInput::with_value(query),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        picker.update_matches();
+        picker
+    }
+
+    /// Typing filters the list, Up and Down (or Ctrl-p and Ctrl-n) move through it.
+    pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PickerAction {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let len = self.matches.len();
+        match code {
+            KeyCode::Esc => return PickerAction::Cancel,
+            KeyCode::Enter => {
+                if let Some(&index) = self.matches.get(self.selected) {
+                    return PickerAction::Pick(self.choices[index].1.clone());
+                }
+            }
+            KeyCode::Down | KeyCode::Tab if len > 0 => self.selected = (self.selected + 1) % len,
+            KeyCode::Char('n') if ctrl && len > 0 => self.selected = (self.selected + 1) % len,
+            KeyCode::Up | KeyCode::BackTab if len > 0 => {
+                self.selected = (self.selected + len - 1) % len
+            }
+            KeyCode::Char('p') if ctrl && len > 0 => {
+                self.selected = (self.selected + len - 1) % len
+            }
+            _ => {
+                if self.query.handle_key(code, modifiers) {
+                    self.update_matches();
+                }
+            }
+        }
+        PickerAction::Continue
+    }
+
+    fn update_matches(&mut self) {
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let query = self.query.value().trim();
+        let mut scored: Vec<(i64, usize)> = self
+            .choices
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (label, _))| {
+                matcher
+                    .fuzzy_match(label, query)
+                    .map(|score| (score, index))
+            })
+            .collect();
+        // Best first; stable, so choices that match as well keep their order
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = 0;
+    }
+
+    /// How many lines the picker takes with its border.
+    pub fn height(&self) -> u16 {
+        self.matches.len().clamp(1, Self::LINES) as u16 + 3
+    }
+
+    /// Draws the query and the matching choices with the top left corner at `x` and `y`, moved
+    /// in if they wouldn't fit on the screen.
+    pub fn render(&self, frame: &mut Frame, x: u16, y: u16) {
+        let screen = frame.size();
+        let (width, height) = (Self::WIDTH, self.height());
+        let area = Rect::new(
+            x.min(screen.right().saturating_sub(width)),
+            y.min(screen.bottom().saturating_sub(height)),
+            width,
+            height,
+        )
+        .intersection(screen);
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(self.title.as_str())
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        if inner.height == 0 {
+            return;
+        }
+
+        let query_area = Rect::new(inner.x, inner.y, inner.width, 1);
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("> ", Style::default().dark_gray()),
+                Span::raw(self.query.value()),
+            ])),
+            query_area,
+        );
+        frame.set_cursor(inner.x + 2 + self.query.cursor_offset(), inner.y);
+
+        let list_area = Rect::new(
+            inner.x,
+            inner.y + 1,
+            inner.width,
+            inner.height.saturating_sub(1),
+        );
+        if self.matches.is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::styled(
+                    "Nothing matches",
+                    Style::default().dark_gray(),
+                )),
+                list_area,
+            );
+            return;
+        }
+        let items: Vec<&str> = self
+            .matches
+            .iter()
+            .map(|&index| self.choices[index].0.as_str())
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">>");
+        let mut list_state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, list_area, &mut list_state);
+    }
+}
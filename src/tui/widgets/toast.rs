@@ -0,0 +1,88 @@
+//! A short message shown over the bottom right corner for a few seconds, for the outcome of an
+//! action.
+
+use ratatui::{prelude::*, widgets::*};
+use std::time::{Duration, Instant};
+
+/// How long a confirmation stays on screen.
+const INFO_DURATION: Duration = Duration::from_secs(3);
+/// Errors and warnings stay longer, as they are more likely to need reading twice.
+const ERROR_DURATION: Duration = Duration::from_secs(6);
+/// The widest a toast gets, longer messages are wrapped.
+const MAX_WIDTH: u16 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastKind {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub kind: ToastKind,
+    shown: Instant,
+}
+
+impl Toast {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self::new(text.into(), ToastKind::Info)
+    }
+
+    pub fn warning(text: impl Into<String>) -> Self {
+        Self::new(text.into(), ToastKind::Warning)
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self::new(text.into(), ToastKind::Error)
+    }
+
+    fn new(text: String, kind: ToastKind) -> Self {
+        Self {
+            text,
+            kind,
+            shown: Instant::now(),
+        }
+    }
+
+    /// Whether the toast has been shown long enough to go away.
+    pub fn expired(&self) -> bool {
+        let duration = match self.kind {
+            ToastKind::Info => INFO_DURATION,
+            ToastKind::Warning | ToastKind::Error => ERROR_DURATION,
+        };
+        self.shown.elapsed() >= duration
+    }
+
+    /// Draws the toast in the bottom right corner of `area`.
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let text_width = self.text.chars().count() as u16;
+        let width = (text_width + 4).min(MAX_WIDTH).min(area.width);
+        let inner_width = width.saturating_sub(4).max(1);
+        let height = (text_width.div_ceil(inner_width) + 2).min(area.height);
+        let toast_area = Rect::new(
+            area.right().saturating_sub(width),
+            area.bottom().saturating_sub(height),
+            width,
+            height,
+        );
+
+        let (title, color) = match self.kind {
+            ToastKind::Info => ("", Color::Green),
+            ToastKind::Warning => ("Warning", Color::Yellow),
+            ToastKind::Error => ("Error", Color::Red),
+        };
+        let toast = Paragraph::new(self.text.as_str())
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(color))
+                    .padding(Padding::horizontal(1)),
+            );
+        frame.render_widget(Clear, toast_area);
+        frame.render_widget(toast, toast_area);
+    }
+}